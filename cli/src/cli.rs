@@ -1,21 +1,19 @@
 use clap::Parser;
 
 use bip39::Mnemonic;
-use web3_hd::{
-    types::{
-        crypto::Crypto,
-        hdseed::{FromSeed, HDSeed},
-    },
-    wallet::{ethereum::EthereumWallet, tron::TronWallet, Wallet},
-};
+use web3_hd::types::network::Network;
 
 use crate::{commands::Commands, error::Error, settings::Settings, wallet_manager::WalletManager};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
+    /// Chain to operate on: a built-in name or the name of a configured `[[chains]]` entry.
     #[arg(short, long)]
-    pub crypto: Option<Crypto>,
+    pub crypto: Option<String>,
+    /// Network to operate on: mainnet, testnet or a custom network name.
+    #[arg(short, long, default_value = "mainnet")]
+    pub network: Network,
     #[arg(default_value = "./config.toml")]
     pub path: String,
     #[command(subcommand)]
@@ -23,37 +21,37 @@ pub struct Cli {
 }
 
 pub async fn handle_command(args: Cli, config: Settings) -> Result<(), Error> {
-    let manager = WalletManager::new(config);
+    let manager = WalletManager::new(config, args.network)?;
+    let crypto = args
+        .crypto
+        .map(|name| manager.get_crypto(&name))
+        .transpose()?;
     match args.command {
         Commands::Balance { c } => {
-            manager.handle_balance(args.crypto, c).await?;
+            manager.handle_balance(crypto, c).await?;
         }
         Commands::BalancesRange { c_from, c_to } => {
-            manager.handle_balances(args.crypto, c_from, c_to).await?;
+            manager.handle_balances(crypto, c_from, c_to).await?;
         }
         Commands::TokenBalance { c } => {
-            manager.handle_balance_tokens(args.crypto, c).await?;
+            manager.handle_balance_tokens(crypto, c).await?;
         }
         Commands::TokenBalancesRange { c_from, c_to } => {
-            manager
-                .handle_balances_tokens(args.crypto, c_from, c_to)
-                .await?;
+            manager.handle_balances_tokens(crypto, c_from, c_to).await?;
         }
         Commands::TotalBalance { c } => {
-            manager.handle_total_balance(args.crypto, c).await?;
+            manager.handle_total_balance(crypto, c).await?;
         }
         Commands::TotalBalancesRange { c_from, c_to } => {
             manager
-                .handle_total_balances_range(args.crypto, c_from, c_to)
+                .handle_total_balances_range(crypto, c_from, c_to)
                 .await?;
         }
-        Commands::Refill {c }=> {
+        Commands::Refill { c: _ } => {
             // Логика для пополнения кошелька
         }
-        Commands::Sweep { c } => {
-            //let address = wallet.address(c as i32)?;
-            //let (tx, balance) = wallet.sweep(c as i32, &address, &config.eth_provider)?;
-            //println!("Swept {} from address {}", balance, address);
+        Commands::Sweep { c, to } => {
+            manager.handle_sweep(crypto, c, to).await?;
         }
         Commands::GenPhrase => {
             let a = Mnemonic::new(bip39::MnemonicType::Words12, bip39::Language::English);
@@ -62,41 +60,131 @@ pub async fn handle_command(args: Cli, config: Settings) -> Result<(), Error> {
             println!("{:?}", phrase);
         }
         Commands::PrivKey { c } => {
-            manager.handle_priv_key(args.crypto, c)?;
+            manager.handle_priv_key(crypto, c)?;
         }
-        Commands::SendMain { c_from, c_to } => {
-            manager.handle_transfer(args.crypto, c_from, c_to).await?;
+        Commands::SendMain {
+            c_from,
+            c_to,
+            amount,
+        } => {
+            manager
+                .handle_transfer(crypto, c_from, c_to, amount)
+                .await?;
         }
         Commands::SendToken {
             c_from,
             c_to,
             c_token,
+            amount,
+        } => {
+            manager
+                .handle_transfer_token(crypto, c_from, c_to, c_token, amount)
+                .await?;
+        }
+        Commands::Freeze {
+            c,
+            amount,
+            resource,
+        } => {
+            manager.handle_freeze(c, amount, resource).await?;
+        }
+        Commands::Unfreeze {
+            c,
+            amount,
+            resource,
+        } => {
+            manager.handle_unfreeze(c, amount, resource).await?;
+        }
+        Commands::WithdrawUnfrozen { c } => {
+            manager.handle_withdraw_unfrozen(c).await?;
+        }
+        Commands::Delegate {
+            c_from,
+            c_to,
+            amount,
+            resource,
+            lock_period,
+        } => {
+            manager
+                .handle_delegate(c_from, c_to, amount, resource, lock_period)
+                .await?;
+        }
+        Commands::Undelegate {
+            c_from,
+            c_to,
+            amount,
+            resource,
+        } => {
+            manager
+                .handle_undelegate(c_from, c_to, amount, resource)
+                .await?;
+        }
+        Commands::Activate { c, address } => {
+            manager.handle_activate(c, address).await?;
+        }
+        Commands::UpdatePermissions {
+            c,
+            owner_threshold,
+            owner_keys,
+            active_threshold,
+            active_keys,
+        } => {
+            manager
+                .handle_update_permissions(
+                    c,
+                    owner_threshold,
+                    owner_keys,
+                    active_threshold,
+                    active_keys,
+                )
+                .await?;
+        }
+        Commands::MultisigTransfer {
+            c_from,
+            c_to,
+            amount,
+            file,
+            permission_id,
+            expiration,
+        } => {
+            manager
+                .handle_multisig_transfer(c_from, c_to, amount, file, permission_id, expiration)
+                .await?;
+        }
+        Commands::SignTx { file, indices } => {
+            manager.handle_sign_tx(file, indices)?;
+        }
+        Commands::SignWeight { file } => {
+            manager.handle_sign_weight(file).await?;
+        }
+        Commands::BroadcastTx { file } => {
+            manager.handle_broadcast_tx(file).await?;
+        }
+        Commands::SignMessage { c, message } => {
+            manager.handle_sign_message(c, message)?;
+        }
+        Commands::VerifyMessage {
+            message,
+            signature,
+            address,
+        } => {
+            manager.handle_verify_message(message, signature, address)?;
+        }
+        Commands::Resources { c } => {
+            manager.handle_resources(c).await?;
+        }
+        Commands::EnergySweep {
+            c_from,
+            c_to,
+            c_token,
+            source,
+            dry_run,
         } => {
-            // Логика отправки токенов
+            manager
+                .handle_energy_sweep(c_from, c_to, c_token, source, dry_run)
+                .await?;
         }
     }
 
     Ok(())
 }
-
-fn create_wallet_with_provider(
-    crypto: Crypto,
-    config: &Settings,
-    seed: HDSeed,
-) -> (Box<dyn Wallet>, &str) {
-    match crypto {
-        Crypto::Tron => (Box::new(TronWallet::from_seed(seed)), &config.tron_provider),
-        Crypto::Eth => (
-            Box::new(EthereumWallet::from_seed(seed)),
-            &config.eth_provider,
-        ),
-        Crypto::BSC => (
-            Box::new(EthereumWallet::from_seed(seed)),
-            &config.bsc_provider,
-        ),
-        Crypto::Polygon => (
-            Box::new(EthereumWallet::from_seed(seed)),
-            &config.plg_provider,
-        ),
-    }
-}
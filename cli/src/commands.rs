@@ -1,4 +1,5 @@
 use clap::Subcommand;
+use web3_hd::wallet::tron::api::Resource;
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
@@ -39,13 +40,13 @@ pub enum Commands {
         c_to: Option<u32>,
     },
     /// Refills the wallet balance. Specific implementation details can vary.
-    Refill{
-        c: u32
-    },
+    Refill { c: u32 },
     /// Sweeps the balance of the main currency to another address.
     Sweep {
         /// The address index from which to sweep the balance.
         c: u32,
+        /// The recipient's address or ENS name.
+        to: String,
     },
     /// Generates a new mnemonic phrase for wallet creation.
     GenPhrase,
@@ -58,16 +59,167 @@ pub enum Commands {
     SendMain {
         /// The sending address index.
         c_from: u32,
-        /// The recipient's address.
+        /// The recipient's address or ENS name.
         c_to: String,
+        /// The amount to send, in the chain's smallest units, e.g. wei or sun.
+        amount: String,
     },
     /// Sends a specified token to another address.
     SendToken {
         /// The sending address index.
         c_from: u32,
-        /// The recipient's address.
+        /// The recipient's address or ENS name.
         c_to: String,
         /// The token address to send.
         c_token: String,
+        /// The amount to send, in the token's smallest units.
+        amount: String,
+    },
+    /// Stakes TRX of a Tron address for bandwidth or energy (Stake 2.0).
+    Freeze {
+        /// The staking address index.
+        c: u32,
+        /// The amount to stake, in sun.
+        amount: u64,
+        /// The resource to stake for: bandwidth or energy.
+        resource: Resource,
+    },
+    /// Starts unstaking TRX of a Tron address, withdrawable after 14 days.
+    Unfreeze {
+        /// The staking address index.
+        c: u32,
+        /// The amount to unstake, in sun.
+        amount: u64,
+        /// The resource the TRX is staked for: bandwidth or energy.
+        resource: Resource,
+    },
+    /// Withdraws the expired unstakes of a Tron address to its balance.
+    WithdrawUnfrozen {
+        /// The staking address index.
+        c: u32,
+    },
+    /// Delegates the resources of staked TRX to another Tron address.
+    Delegate {
+        /// The delegating address index.
+        c_from: u32,
+        /// The receiving address.
+        c_to: String,
+        /// The amount of staked TRX whose resources are delegated, in sun.
+        amount: u64,
+        /// The resource to delegate: bandwidth or energy.
+        resource: Resource,
+        /// Blocks the delegation is locked for.
+        #[arg(long)]
+        lock_period: Option<u64>,
+    },
+    /// Takes back resources delegated to another Tron address.
+    Undelegate {
+        /// The delegating address index.
+        c_from: u32,
+        /// The receiving address.
+        c_to: String,
+        /// The amount of staked TRX whose resources are taken back, in sun.
+        amount: u64,
+        /// The resource to take back: bandwidth or energy.
+        resource: Resource,
+    },
+    /// Activates a Tron address, paying its creation fee from an address index.
+    Activate {
+        /// The paying address index.
+        c: u32,
+        /// The address to activate.
+        address: String,
+    },
+    /// Replaces the owner and active permissions of a Tron address with multi-signature
+    /// ones. Burns the permission update fee, 100 TRX on mainnet.
+    UpdatePermissions {
+        /// The address index whose permissions are replaced.
+        c: u32,
+        /// Weight the owner keys' signatures need to reach.
+        #[arg(long, default_value_t = 1)]
+        owner_threshold: u64,
+        /// An owner key, as ADDRESS or ADDRESS:WEIGHT. Repeat for each key.
+        #[arg(long = "owner-key", required = true)]
+        owner_keys: Vec<String>,
+        /// Weight the active keys' signatures need to reach.
+        #[arg(long, default_value_t = 1)]
+        active_threshold: u64,
+        /// An active key, as ADDRESS or ADDRESS:WEIGHT. Repeat for each key.
+        #[arg(long = "active-key", required = true)]
+        active_keys: Vec<String>,
+    },
+    /// Writes an unsigned TRX transfer of a multi-signature Tron address to a file, for
+    /// its signers to sign.
+    MultisigTransfer {
+        /// The sending address index.
+        c_from: u32,
+        /// The recipient's address.
+        c_to: String,
+        /// The amount to send, in sun.
+        amount: String,
+        /// The file to write the transaction to.
+        file: String,
+        /// The permission the transaction is signed under, 2 for the first active one.
+        #[arg(long, default_value_t = 2)]
+        permission_id: u32,
+        /// Seconds the signers have to sign and broadcast it, at most 24 hours.
+        #[arg(long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..=86_400))]
+        expiration: u64,
+    },
+    /// Adds signatures to a Tron transaction file.
+    SignTx {
+        /// The transaction file.
+        file: String,
+        /// The address indices to sign with.
+        #[arg(required = true)]
+        indices: Vec<u32>,
+    },
+    /// Shows the weight of the signatures of a Tron transaction file.
+    SignWeight {
+        /// The transaction file.
+        file: String,
+    },
+    /// Broadcasts a Tron transaction file once its signatures reach the threshold.
+    BroadcastTx {
+        /// The transaction file.
+        file: String,
+    },
+    /// Signs a message with a Tron address, as TronLink's signMessageV2 does.
+    SignMessage {
+        /// The signing address index.
+        c: u32,
+        /// The message to sign.
+        message: String,
+    },
+    /// Recovers the Tron address that signed a message, and checks it if given.
+    VerifyMessage {
+        /// The signed message.
+        message: String,
+        /// The hex signature.
+        signature: String,
+        /// The address expected to have signed.
+        #[arg(long)]
+        address: Option<String>,
+    },
+    /// Shows the stakes, available resources and delegations of a Tron address.
+    Resources {
+        /// The address index.
+        c: u32,
+    },
+    /// Sweeps a TRC-20 token from a range of Tron addresses to the Tron safe, with energy
+    /// delegated by a staked source address.
+    EnergySweep {
+        /// The starting address index of the range to sweep.
+        c_from: u32,
+        /// The ending address index of the range to sweep.
+        c_to: u32,
+        /// The token address to sweep.
+        c_token: String,
+        /// The address index whose staked TRX provides the energy.
+        #[arg(long, default_value_t = 0)]
+        source: u32,
+        /// Only plans the sweep and reports it, without sending anything.
+        #[arg(long)]
+        dry_run: bool,
     },
 }
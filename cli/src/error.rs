@@ -8,8 +8,18 @@ pub enum Error {
     Web3HDError(#[from] web3_hd::error::Error),
     #[error("The 'crypto' argument is required for this command.")]
     ArgsError,
+    #[error("Unknown chain '{0}', the registered chains are: {1}.")]
+    UnknownCryptoError(String, String),
+    #[error("Chain '{0}' is not configured for {1}.")]
+    UnknownChainError(String, web3_hd::types::network::Network),
     #[error("A 'provider_url' must be specified in the configuration.")]
     ProviderUrlError,
-    #[error("Amount parse error")]
-    EtherParseAmountError(#[from] ethers::utils::ConversionError),
+    #[error("Amount {0} is not a valid integer amount")]
+    AmountParseError(String),
+    #[error("Key {0} is not an ADDRESS or ADDRESS:WEIGHT")]
+    PermissionKeyParseError(String),
+    #[error("Transaction file error")]
+    IoError(#[from] std::io::Error),
+    #[error("Transaction file is not a Tron transaction")]
+    JsonError(#[from] serde_json::Error),
 }
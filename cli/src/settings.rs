@@ -9,15 +9,62 @@ pub struct Settings {
     pub eth_tokens: Vec<String>,
     pub eth_safe: String,
     pub eth_provider: String,
+    /// TRC-20 contract addresses, and numeric ids of TRC-10 assets, e.g. `1002000`.
     pub tron_tokens: Vec<String>,
     pub tron_safe: String,
+    /// HTTP API of a TRON full node or TronGrid, e.g. `https://api.trongrid.io`.
     pub tron_provider: String,
+    /// Most a TRC-20 transfer may burn, in sun, 100 TRX when unset.
+    #[serde(default)]
+    pub tron_fee_limit: Option<u64>,
     pub plg_tokens: Vec<String>,
     pub plg_safe: String,
     pub plg_provider: String,
     pub bsc_tokens: Vec<String>,
     pub bsc_safe: String,
     pub bsc_provider: String,
+    /// Esplora API of the Bitcoin mainnet, e.g. `https://blockstream.info/api`.
+    #[serde(default)]
+    pub btc_provider: String,
+    /// Esplora-compatible APIs of the Litecoin and Dogecoin mainnets.
+    #[serde(default)]
+    pub ltc_provider: String,
+    #[serde(default)]
+    pub doge_provider: String,
+    /// Solana JSON-RPC node and SPL token mints.
+    #[serde(default)]
+    pub sol_provider: String,
+    #[serde(default)]
+    pub sol_tokens: Vec<String>,
+    /// Horizon API of the Stellar network and assets as `CODE:ISSUER`.
+    #[serde(default)]
+    pub xlm_provider: String,
+    #[serde(default)]
+    pub xlm_tokens: Vec<String>,
+    /// LCD (REST) endpoints of Cosmos Hub and Osmosis, and extra bank denoms to report.
+    #[serde(default)]
+    pub atom_provider: String,
+    #[serde(default)]
+    pub atom_tokens: Vec<String>,
+    #[serde(default)]
+    pub osmo_provider: String,
+    #[serde(default)]
+    pub osmo_tokens: Vec<String>,
+    /// rippled JSON-RPC endpoint of the XRP Ledger, e.g. `https://s1.ripple.com:51234`.
+    #[serde(default)]
+    pub xrp_provider: String,
+    /// ENS registry overrides, the mainnet ENS registry is used when unset.
+    #[serde(default)]
+    pub eth_ens_registry: Option<String>,
+    #[serde(default)]
+    pub plg_ens_registry: Option<String>,
+    #[serde(default)]
+    pub bsc_ens_registry: Option<String>,
+    /// Additional chains, or overrides of the built-in ones, as `[[chains]]` tables. An
+    /// override only needs the `name`, `network` and the fields it changes, e.g. the
+    /// `provider` of `tron` on `testnet`.
+    #[serde(default)]
+    pub chains: Vec<serde_json::Value>,
 }
 
 impl Settings {
@@ -1,57 +1,202 @@
+use std::time::Duration;
+
 use ethers::types::U256;
 use web3_hd::{
     types::{
+        chain::{ChainConfig, ChainRegistry},
         crypto::Crypto,
         hdseed::{FromSeed, HDSeed},
+        network::Network,
+        transfer_receipt::TransferReceipt,
+    },
+    wallet::{
+        registry::{BoxedWallet, WalletRegistry},
+        tron::{
+            address::{tron_address_to_hex, tron_hex_to_address},
+            api::{BlockHeader, Permission, PermissionKey, Resource, Transaction, TronClient},
+            message::verify_message,
+            permission::DEFAULT_ACTIVE_OPERATIONS,
+            transaction::MAX_EXPIRATION,
+            TronWallet,
+        },
+        Wallet,
     },
-    wallet::{ethereum::EthereumWallet, tron::TronWallet, Wallet},
 };
 
 use crate::{error::Error, settings::Settings};
 
 pub struct WalletManager {
     pub config: Settings,
+    pub chains: ChainRegistry,
+    /// Wallet implementations by chain family, extendable with `WalletRegistry::register`.
+    pub wallets: WalletRegistry,
+    pub network: Network,
 }
 
 impl WalletManager {
-    pub fn new(config: Settings) -> Self {
-        WalletManager { config }
+    pub fn new(config: Settings, network: Network) -> Result<Self, Error> {
+        let chains = Self::build_chains(&config)?;
+        Ok(WalletManager {
+            config,
+            chains,
+            wallets: WalletRegistry::builtin(),
+            network,
+        })
     }
 
-    pub fn get_provider(&self, crypto: &Crypto) -> &String {
-        match crypto {
-            Crypto::Tron => &self.config.tron_provider,
-            Crypto::Eth => &self.config.eth_provider,
-            Crypto::BSC => &self.config.bsc_provider,
-            Crypto::Polygon => &self.config.plg_provider,
+    /// Built-in chains with their mainnets filled from the per-chain settings, extended
+    /// by the `[[chains]]` entries of the configuration and overlaid with them.
+    fn build_chains(config: &Settings) -> Result<ChainRegistry, Error> {
+        let mut chains = ChainRegistry::builtin();
+        let legacy = [
+            (
+                Crypto::Eth,
+                &config.eth_provider,
+                &config.eth_tokens,
+                &config.eth_ens_registry,
+            ),
+            (
+                Crypto::BSC,
+                &config.bsc_provider,
+                &config.bsc_tokens,
+                &config.bsc_ens_registry,
+            ),
+            (
+                Crypto::Polygon,
+                &config.plg_provider,
+                &config.plg_tokens,
+                &config.plg_ens_registry,
+            ),
+            (
+                Crypto::Tron,
+                &config.tron_provider,
+                &config.tron_tokens,
+                &None,
+            ),
+            (Crypto::Btc, &config.btc_provider, &vec![], &None),
+            (Crypto::Ltc, &config.ltc_provider, &vec![], &None),
+            (Crypto::Doge, &config.doge_provider, &vec![], &None),
+            (Crypto::Sol, &config.sol_provider, &config.sol_tokens, &None),
+            (Crypto::Xlm, &config.xlm_provider, &config.xlm_tokens, &None),
+            (
+                Crypto::Atom,
+                &config.atom_provider,
+                &config.atom_tokens,
+                &None,
+            ),
+            (
+                Crypto::Osmo,
+                &config.osmo_provider,
+                &config.osmo_tokens,
+                &None,
+            ),
+            (Crypto::Xrp, &config.xrp_provider, &vec![], &None),
+        ];
+        for (crypto, provider, tokens, ens_registry) in legacy {
+            if let Some(chain) = chains.get_mut(crypto.chain_name(), &Network::Mainnet) {
+                chain.provider = provider.clone();
+                chain.tokens = tokens.clone();
+                chain.ens_registry = ens_registry.clone();
+            }
+        }
+        if let Some(chain) = chains.get_mut(Crypto::Tron.chain_name(), &Network::Mainnet) {
+            chain.fee_limit = config.tron_fee_limit;
+        }
+        for chain in &config.chains {
+            chains.merge(chain.clone())?;
         }
+        Ok(chains)
+    }
+
+    /// Chain named `name`, built in or registered from the configuration.
+    pub fn get_crypto(&self, name: &str) -> Result<Crypto, Error> {
+        Crypto::resolve(name, &self.chains).ok_or_else(|| {
+            Error::UnknownCryptoError(name.to_owned(), self.chains.names().join(", "))
+        })
+    }
+
+    pub fn get_chain(&self, crypto: &Crypto) -> Result<&ChainConfig, Error> {
+        self.chains
+            .get(crypto.chain_name(), &self.network)
+            .ok_or_else(|| Error::UnknownChainError(crypto.to_string(), self.network.clone()))
+    }
+
+    pub fn get_provider(&self, crypto: &Crypto) -> Result<&String, Error> {
+        let provider = &self.get_chain(crypto)?.provider;
+        if provider.is_empty() {
+            return Err(Error::ProviderUrlError);
+        }
+        Ok(provider)
+    }
+
+    /// Wallet of `crypto` on the selected network, built by the factory registered for
+    /// the chain's family.
+    pub fn get_wallet(&self, crypto: &Crypto, seed: HDSeed) -> Result<BoxedWallet, Error> {
+        Ok(self.wallets.wallet(seed, self.get_chain(crypto)?)?)
+    }
+
+    /// `TronWallet` for the Tron-only commands, with the configured fee limit.
+    pub fn get_tron_wallet(&self) -> Result<TronWallet, Error> {
+        let seed = HDSeed::new(&self.config.hd_phrase)?;
+        let wallet = TronWallet::from_seed(seed);
+        Ok(match self.get_chain(&Crypto::Tron)?.fee_limit {
+            Some(fee_limit) => wallet.with_fee_limit(fee_limit),
+            None => wallet,
+        })
+    }
+
+    pub fn get_wallet_tokens(&self, crypto: &Crypto) -> Result<&Vec<String>, Error> {
+        Ok(&self.get_chain(crypto)?.tokens)
+    }
+
+    /// Prints which chain and network a report is for.
+    fn print_header(&self, crypto: &Crypto) {
+        println!("{} ({})", crypto, self.network);
     }
-    pub fn get_wallet(&self, crypto: &Crypto, seed: HDSeed) -> Box<dyn Wallet> {
-        match crypto {
-            Crypto::Tron => Box::new(TronWallet::from_seed(seed)) as Box<dyn Wallet>,
-            Crypto::Eth => Box::new(EthereumWallet::from_seed(seed)) as Box<dyn Wallet>,
-            Crypto::BSC => Box::new(EthereumWallet::from_seed(seed)) as Box<dyn Wallet>,
-            Crypto::Polygon => Box::new(EthereumWallet::from_seed(seed)) as Box<dyn Wallet>,
+
+    /// Prints the explorer link of a sent transaction, if the chain has an explorer.
+    fn print_explorer_tx(&self, crypto: &Crypto, txid: &str) -> Result<(), Error> {
+        if let Some(url) = self.get_chain(crypto)?.explorer_tx_url(txid) {
+            println!("Explorer: {}", url);
         }
+        Ok(())
     }
 
-    pub fn get_wallet_tokens(&self, crypto: &Crypto) -> &Vec<String> {
-        match crypto {
-            Crypto::Tron => &self.config.tron_tokens,
-            Crypto::Eth => &self.config.eth_tokens,
-            Crypto::BSC => &self.config.bsc_tokens,
-            Crypto::Polygon => &self.config.plg_tokens,
+    /// Formats an address together with its reverse-resolved name, if it has one. Chains
+    /// without ENS are not asked.
+    async fn display_address(
+        &self,
+        crypto: &Crypto,
+        wallet: &dyn Wallet,
+        index: u32,
+        provider_url: &str,
+    ) -> Result<String, Error> {
+        let address = wallet.address(index)?;
+        if !self.get_chain(crypto)?.has_ens() {
+            return Ok(address);
+        }
+        match wallet.reverse_name(index, provider_url).await? {
+            Some(name) => Ok(format!("{} ({})", address, name)),
+            None => Ok(address),
         }
     }
 
     pub async fn handle_balance(&self, ocrypto: Option<Crypto>, c: u32) -> Result<(), Error> {
         if let Some(crypto) = ocrypto {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let address = wallet.address(c)?;
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            let chain = self.get_chain(&crypto)?;
+            self.print_header(&crypto);
+            let address = self
+                .display_address(&crypto, wallet.as_ref(), c, provider_url)
+                .await?;
             let balance = wallet.balance(c, provider_url).await?;
-            println!("Address: {}, Balance: {}", address, balance);
+            println!(
+                "Address: {}, Balance: {}",
+                address,
+                chain.format_amount(balance)
+            );
             Ok(())
         } else {
             Err(Error::ArgsError)
@@ -68,12 +213,20 @@ impl WalletManager {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
             let c_from = c_from.unwrap_or(0);
             let c_to = c_to.unwrap_or(10);
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            let chain = self.get_chain(&crypto)?;
+            self.print_header(&crypto);
             for index in c_from..=c_to {
-                let address = wallet.address(index)?;
+                let address = self
+                    .display_address(&crypto, wallet.as_ref(), index, provider_url)
+                    .await?;
                 let balance = wallet.balance(index, provider_url).await?;
-                println!("Address: {}, Balance: {}", address, balance);
+                println!(
+                    "Address: {}, Balance: {}",
+                    address,
+                    chain.format_amount(balance)
+                );
             }
             Ok(())
         } else {
@@ -88,12 +241,15 @@ impl WalletManager {
     ) -> Result<(), Error> {
         if let Some(crypto) = ocrypto {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let tokens = self.get_wallet_tokens(&crypto);
-            let address = wallet.address(c)?;
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            self.print_header(&crypto);
+            let tokens = self.get_wallet_tokens(&crypto)?;
+            let address = self
+                .display_address(&crypto, wallet.as_ref(), c, provider_url)
+                .await?;
             for token in tokens {
-                let balance = wallet.balance_token(c, &token, &provider_url).await?;
+                let balance = wallet.balance_token(c, token, provider_url).await?;
                 println!(
                     "Address: {},\n Token: {}, Balance: {}",
                     address, token, balance
@@ -115,13 +271,16 @@ impl WalletManager {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
             let c_from = c_from.unwrap_or(0);
             let c_to = c_to.unwrap_or(10);
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let tokens = self.get_wallet_tokens(&crypto);
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            self.print_header(&crypto);
+            let tokens = self.get_wallet_tokens(&crypto)?;
             for token in tokens {
                 for index in c_from..=c_to {
-                    let address = wallet.address(index)?;
-                    let balance = wallet.balance_token(index, &token, &provider_url).await?;
+                    let address = self
+                        .display_address(&crypto, wallet.as_ref(), index, provider_url)
+                        .await?;
+                    let balance = wallet.balance_token(index, token, provider_url).await?;
                     println!(
                         "Address: {},\n Token: {}, Balance: {}",
                         address, token, balance
@@ -137,15 +296,19 @@ impl WalletManager {
     pub async fn handle_total_balance(&self, ocrypto: Option<Crypto>, c: u32) -> Result<(), Error> {
         if let Some(crypto) = ocrypto {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let address = wallet.address(c)?;
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            let chain = self.get_chain(&crypto)?;
+            self.print_header(&crypto);
+            let address = self
+                .display_address(&crypto, wallet.as_ref(), c, provider_url)
+                .await?;
             let balance = wallet.balance(c, provider_url).await?;
-            let tokens = self.get_wallet_tokens(&crypto);
+            let tokens = self.get_wallet_tokens(&crypto)?;
             println!("Total Balance for Address: {}", address);
-            println!("Main Currency: {}", balance);
+            println!("Main Currency: {}", chain.format_amount(balance));
             for token in tokens {
-                let token_balance = wallet.balance_token(c, &token, provider_url).await?;
+                let token_balance = wallet.balance_token(c, token, provider_url).await?;
                 println!("Token: {}, Balance: {}", token, token_balance);
             }
             Ok(())
@@ -164,16 +327,20 @@ impl WalletManager {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
             let c_from = c_from.unwrap_or(0);
             let c_to = c_to.unwrap_or(10);
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let tokens = self.get_wallet_tokens(&crypto);
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            let chain = self.get_chain(&crypto)?;
+            self.print_header(&crypto);
+            let tokens = self.get_wallet_tokens(&crypto)?;
             for index in c_from..=c_to {
-                let address = wallet.address(index)?;
+                let address = self
+                    .display_address(&crypto, wallet.as_ref(), index, provider_url)
+                    .await?;
                 let balance = wallet.balance(index, provider_url).await?;
                 println!("Total Balance for Address: {}", address);
-                println!("Main Currency: {}", balance);
+                println!("Main Currency: {}", chain.format_amount(balance));
                 for token in tokens {
-                    let token_balance = wallet.balance_token(index, &token, provider_url).await?;
+                    let token_balance = wallet.balance_token(index, token, provider_url).await?;
                     println!("Token: {}, Balance: {}", token, token_balance);
                 }
             }
@@ -186,7 +353,7 @@ impl WalletManager {
     pub fn handle_priv_key(&self, ocrypto: Option<Crypto>, c: u32) -> Result<(), Error> {
         if let Some(crypto) = ocrypto {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
-            let wallet = self.get_wallet(&crypto, seed);
+            let wallet = self.get_wallet(&crypto, seed)?;
             println!(
                 "Address: {}\n Private: {}",
                 wallet.address(c)?,
@@ -203,22 +370,511 @@ impl WalletManager {
         ocrypto: Option<Crypto>,
         c_from: u32,
         c_to: String,
+        amount: String,
+    ) -> Result<(), Error> {
+        if let Some(crypto) = ocrypto {
+            let seed = HDSeed::new(&self.config.hd_phrase)?;
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            self.print_header(&crypto);
+            let amount =
+                U256::from_dec_str(&amount).map_err(|_| Error::AmountParseError(amount.clone()))?;
+            let receipt = wallet.transfer(c_from, &c_to, amount, provider_url).await?;
+            println!("Transaction Receipt {:?}", receipt);
+            self.print_explorer_tx(&crypto, &receipt.txid())?;
+            Ok(())
+        } else {
+            Err(Error::ArgsError)
+        }
+    }
+
+    pub async fn handle_sweep(
+        &self,
+        ocrypto: Option<Crypto>,
+        c: u32,
+        to: String,
+    ) -> Result<(), Error> {
+        if let Some(crypto) = ocrypto {
+            let seed = HDSeed::new(&self.config.hd_phrase)?;
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            self.print_header(&crypto);
+            let (receipt, amount) = wallet.sweep(c, &to, provider_url).await?;
+            println!(
+                "Swept {} from address {}",
+                self.get_chain(&crypto)?.format_amount(amount),
+                wallet.address(c)?
+            );
+            println!("Transaction Receipt {:?}", receipt);
+            self.print_explorer_tx(&crypto, &receipt.txid())?;
+            Ok(())
+        } else {
+            Err(Error::ArgsError)
+        }
+    }
+
+    pub async fn handle_transfer_token(
+        &self,
+        ocrypto: Option<Crypto>,
+        c_from: u32,
+        c_to: String,
+        c_token: String,
+        amount: String,
     ) -> Result<(), Error> {
         if let Some(crypto) = ocrypto {
             let seed = HDSeed::new(&self.config.hd_phrase)?;
-            let wallet = self.get_wallet(&crypto, seed);
-            let provider_url = &self.get_provider(&crypto);
-            let amount = U256::from(ethers::utils::parse_ether(0.000000000000123)?);
-            let balance = wallet.balance(c_from, provider_url).await?;
-            println!("balance {:?}", balance);
-            println!("amount {:?}", amount);
+            let wallet = self.get_wallet(&crypto, seed)?;
+            let provider_url = self.get_provider(&crypto)?;
+            self.print_header(&crypto);
+            let amount =
+                U256::from_dec_str(&amount).map_err(|_| Error::AmountParseError(amount.clone()))?;
             let receipt = wallet
-                .transfer(c_from, &c_to, amount, &provider_url)
+                .transfer_token(c_from, &c_token, &c_to, amount, provider_url)
                 .await?;
             println!("Transaction Receipt {:?}", receipt);
+            self.print_explorer_tx(&crypto, &receipt.txid())?;
             Ok(())
         } else {
             Err(Error::ArgsError)
         }
     }
+
+    /// Prints a Tron receipt and its explorer link.
+    fn print_tron_receipt(&self, receipt: &TransferReceipt) -> Result<(), Error> {
+        println!("Transaction Receipt {:?}", receipt);
+        self.print_explorer_tx(&Crypto::Tron, &receipt.txid())
+    }
+
+    pub async fn handle_freeze(
+        &self,
+        c: u32,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        println!(
+            "Staking {} sun of {} for {}",
+            amount,
+            wallet.address(c)?,
+            resource
+        );
+        let receipt = wallet.freeze(c, amount, resource, provider_url).await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_unfreeze(
+        &self,
+        c: u32,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        println!(
+            "Unstaking {} sun of {} for {}",
+            amount,
+            wallet.address(c)?,
+            resource
+        );
+        let receipt = wallet.unfreeze(c, amount, resource, provider_url).await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_withdraw_unfrozen(&self, c: u32) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let receipt = wallet.withdraw_unfrozen(c, provider_url).await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_delegate(
+        &self,
+        c_from: u32,
+        c_to: String,
+        amount: u64,
+        resource: Resource,
+        lock_period: Option<u64>,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        println!(
+            "Delegating {} of {} sun from {} to {}",
+            resource,
+            amount,
+            wallet.address(c_from)?,
+            c_to
+        );
+        let receipt = wallet
+            .delegate(c_from, &c_to, amount, resource, lock_period, provider_url)
+            .await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_undelegate(
+        &self,
+        c_from: u32,
+        c_to: String,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        println!(
+            "Taking back {} of {} sun from {} to {}",
+            resource,
+            amount,
+            wallet.address(c_from)?,
+            c_to
+        );
+        let receipt = wallet
+            .undelegate(c_from, &c_to, amount, resource, provider_url)
+            .await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_activate(&self, c: u32, address: String) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        println!("Activating {} from {}", address, wallet.address(c)?);
+        match wallet.activate(c, &address, provider_url).await? {
+            Some(receipt) => self.print_tron_receipt(&receipt),
+            None => {
+                println!("{} is already activated", address);
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn handle_update_permissions(
+        &self,
+        c: u32,
+        owner_threshold: u64,
+        owner_keys: Vec<String>,
+        active_threshold: u64,
+        active_keys: Vec<String>,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let owner = Permission::owner(owner_threshold, parse_permission_keys(&owner_keys)?);
+        let active = Permission::active(
+            "active",
+            active_threshold,
+            DEFAULT_ACTIVE_OPERATIONS,
+            parse_permission_keys(&active_keys)?,
+        );
+        println!(
+            "Setting the permissions of {}: owner {} of {:?}, active {} of {:?}",
+            wallet.address(c)?,
+            owner_threshold,
+            owner_keys,
+            active_threshold,
+            active_keys
+        );
+        let receipt = wallet
+            .update_permissions(c, owner, vec![active], provider_url)
+            .await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub async fn handle_multisig_transfer(
+        &self,
+        c_from: u32,
+        c_to: String,
+        amount: String,
+        file: String,
+        permission_id: u32,
+        expiration: u64,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        let amount =
+            U256::from_dec_str(&amount).map_err(|_| Error::AmountParseError(amount.clone()))?;
+        let block = TronClient::new(provider_url).get_now_block().await?;
+        let tx = build_multisig_transfer(
+            &wallet,
+            c_from,
+            &c_to,
+            amount,
+            permission_id,
+            Duration::from_secs(expiration),
+            &block,
+        )?;
+        write_transaction(&file, &tx)?;
+        println!(
+            "Transaction {} of {} sun from {} to {} written to {}, expiring at {}",
+            tx.tx_id,
+            amount,
+            wallet.address(c_from)?,
+            c_to,
+            file,
+            tx.raw_data["expiration"]
+        );
+        Ok(())
+    }
+
+    pub fn handle_sign_tx(&self, file: String, indices: Vec<u32>) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let mut tx = read_transaction(&file)?;
+        for index in indices {
+            wallet.sign_transaction(index, &mut tx)?;
+            println!("Signed {} with {}", tx.tx_id, wallet.address(index)?);
+        }
+        write_transaction(&file, &tx)
+    }
+
+    pub async fn handle_sign_weight(&self, file: String) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let tx = read_transaction(&file)?;
+        let weight = wallet.sign_weight(&tx, provider_url).await?;
+        println!("Transaction: {}", tx.tx_id);
+        if let Some(permission) = &weight.permission {
+            println!(
+                "Permission: {} ({})",
+                permission.permission_name, permission.id
+            );
+        }
+        for signer in &weight.approved_list {
+            println!("Signed by: {}", tron_hex_to_address(signer)?);
+        }
+        println!(
+            "Weight: {} of {}, {} {}",
+            weight.current_weight,
+            weight.threshold(),
+            weight.result.code,
+            weight.result.message
+        );
+        Ok(())
+    }
+
+    pub async fn handle_broadcast_tx(&self, file: String) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let tx = read_transaction(&file)?;
+        let receipt = wallet.broadcast_multisig(tx, provider_url).await?;
+        self.print_tron_receipt(&receipt)
+    }
+
+    pub fn handle_sign_message(&self, c: u32, message: String) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        println!("Address: {}", wallet.address(c)?);
+        println!("Signature: {}", wallet.sign_message(c, message.as_bytes())?);
+        Ok(())
+    }
+
+    pub fn handle_verify_message(
+        &self,
+        message: String,
+        signature: String,
+        address: Option<String>,
+    ) -> Result<(), Error> {
+        let signer = verify_message(message.as_bytes(), &signature)?;
+        println!("Signed by: {}", signer);
+        if let Some(address) = address {
+            let verdict = if address == signer {
+                "valid"
+            } else {
+                "invalid"
+            };
+            println!("Signature of {}: {}", address, verdict);
+        }
+        Ok(())
+    }
+
+    pub async fn handle_resources(&self, c: u32) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let summary = wallet.stake_summary(c, provider_url).await?;
+        println!("Address: {}", wallet.address(c)?);
+        println!(
+            "Staked: {} sun for bandwidth, {} sun for energy",
+            summary.frozen_bandwidth, summary.frozen_energy
+        );
+        println!(
+            "Delegatable: {} sun for bandwidth, {} sun for energy",
+            summary.delegatable_bandwidth, summary.delegatable_energy
+        );
+        println!(
+            "Available: {} bandwidth ({} free), {} energy",
+            summary.resource.bandwidth(),
+            summary.resource.free_bandwidth(),
+            summary.resource.energy()
+        );
+        for unfrozen in &summary.unfreezing {
+            println!(
+                "Unstaking: {} sun of {}, withdrawable at {}",
+                unfrozen.unfreeze_amount, unfrozen.resource, unfrozen.unfreeze_expire_time
+            );
+        }
+        println!("Withdrawable: {} sun", summary.withdrawable);
+        for delegation in wallet.delegations(c, provider_url).await? {
+            println!(
+                "Delegated to {}: {} sun for bandwidth, {} sun for energy",
+                tron_hex_to_address(&delegation.to)?,
+                delegation.frozen_balance_for_bandwidth,
+                delegation.frozen_balance_for_energy
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn handle_energy_sweep(
+        &self,
+        c_from: u32,
+        c_to: u32,
+        c_token: String,
+        source: u32,
+        dry_run: bool,
+    ) -> Result<(), Error> {
+        let wallet = self.get_tron_wallet()?;
+        let provider_url = self.get_provider(&Crypto::Tron)?;
+        self.print_header(&Crypto::Tron);
+        let indices: Vec<u32> = (c_from..=c_to).filter(|&index| index != source).collect();
+        let mut plan = wallet
+            .plan_token_sweep(
+                source,
+                &c_token,
+                &self.config.tron_safe,
+                &indices,
+                provider_url,
+            )
+            .await?;
+        println!(
+            "Sweeping {} from {} addresses to {}, energy from {}",
+            c_token,
+            indices.len(),
+            plan.to,
+            wallet.address(source)?
+        );
+        if !dry_run {
+            wallet.execute_token_sweep(&mut plan, provider_url).await;
+        }
+        for item in &plan.items {
+            println!(
+                "Address: {} ({}), Amount: {}, Energy: {}, Delegation: {} sun, Status: {}",
+                item.address,
+                item.index,
+                item.amount,
+                item.estimate.energy,
+                item.delegation,
+                item.status
+            );
+        }
+        if dry_run {
+            println!("Planned: {}", plan.planned_amount());
+        } else {
+            println!("Swept: {}", plan.swept_amount());
+        }
+        Ok(())
+    }
+}
+
+/// Keys of a permission given as `ADDRESS` or `ADDRESS:WEIGHT`, weight 1 by default.
+fn parse_permission_keys(keys: &[String]) -> Result<Vec<PermissionKey>, Error> {
+    keys.iter()
+        .map(|key| {
+            let (address, weight) = key.split_once(':').unwrap_or((key, "1"));
+            let weight = weight
+                .parse()
+                .map_err(|_| Error::PermissionKeyParseError(key.clone()))?;
+            Ok(PermissionKey {
+                address: tron_address_to_hex(address)?,
+                weight,
+            })
+        })
+        .collect()
+}
+
+fn read_transaction(file: &str) -> Result<Transaction, Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(file)?)?)
+}
+
+fn write_transaction(file: &str, tx: &Transaction) -> Result<(), Error> {
+    Ok(std::fs::write(file, serde_json::to_string_pretty(tx)?)?)
+}
+
+/// Unsigned transfer under `permission_id`, valid for `expiration` after `block`, at
+/// most `MAX_EXPIRATION`. The default validity is too short to collect signatures.
+fn build_multisig_transfer(
+    wallet: &TronWallet,
+    c_from: u32,
+    c_to: &str,
+    amount: U256,
+    permission_id: u32,
+    expiration: Duration,
+    block: &BlockHeader,
+) -> Result<Transaction, Error> {
+    let expiration = expiration.min(MAX_EXPIRATION);
+    Ok(wallet
+        .build_transfer(c_from, c_to, amount, block)?
+        .permission_id(permission_id)
+        .expiration(block.timestamp + expiration.as_millis() as u64)
+        .into_transaction())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multisig_transfer_expiration() {
+        let mnemonic = bip39::Mnemonic::from_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            bip39::Language::English,
+        )
+        .unwrap();
+        let wallet = TronWallet::from_seed(HDSeed { mnemonic });
+        let block = BlockHeader {
+            id: "0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718".to_owned(),
+            number: 61272498,
+            timestamp: 1713000000000,
+        };
+        let to = wallet.address(1).unwrap();
+        let tx = build_multisig_transfer(
+            &wallet,
+            0,
+            &to,
+            U256::from(1_000_000),
+            2,
+            Duration::from_secs(3600),
+            &block,
+        )
+        .unwrap();
+
+        let file = std::env::temp_dir().join(format!("multisig-{}.json", tx.tx_id));
+        let file = file.to_str().unwrap();
+        write_transaction(file, &tx).unwrap();
+        let written = read_transaction(file).unwrap();
+        std::fs::remove_file(file).unwrap();
+        assert_eq!(written.raw_data["expiration"], 1713000000000u64 + 3_600_000);
+        assert_eq!(written.raw_data["contract"][0]["Permission_id"], 2);
+        assert_eq!(written.tx_id, tx.tx_id);
+
+        // nodes reject transactions valid for more than a day
+        let tx = build_multisig_transfer(
+            &wallet,
+            0,
+            &to,
+            U256::from(1_000_000),
+            2,
+            Duration::from_secs(7 * 86_400),
+            &block,
+        )
+        .unwrap();
+        assert_eq!(
+            tx.raw_data["expiration"],
+            1713000000000u64 + MAX_EXPIRATION.as_millis() as u64
+        );
+    }
 }
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+use bip39::{Language, Mnemonic, Seed};
+use bitcoin::bip32::DerivationPath;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use web3_hd::{
+    types::hdseed::{FromSeed, HDSeed},
+    utils::key::{get_extended_keypair, keccak256, pubkey_hash},
+    wallet::{
+        ethereum::{address::extended_pubk_to_addr, EthereumWallet},
+        tron::{address::extended_pubk_to_addr_tron, TronWallet},
+        Wallet,
+    },
+};
+
+const PHRASE: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn seed_bytes() -> Vec<u8> {
+    let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+    Seed::new(&mnemonic, "").as_bytes().to_vec()
+}
+
+fn bench_hashing(c: &mut Criterion) {
+    let seed = seed_bytes();
+    let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+    let (_, pubk) = get_extended_keypair(&seed, &path).unwrap();
+
+    c.bench_function("keccak256_64_bytes", |b| {
+        let data = [0x5au8; 64];
+        b.iter(|| keccak256(black_box(&data)))
+    });
+    c.bench_function("pubkey_hash", |b| {
+        b.iter(|| pubkey_hash(black_box(&pubk.public_key)))
+    });
+}
+
+fn bench_pubk_to_addr(c: &mut Criterion) {
+    let seed = seed_bytes();
+    let path = DerivationPath::from_str("m/44'/60'/0'/0/0").unwrap();
+    let (_, pubk) = get_extended_keypair(&seed, &path).unwrap();
+
+    c.bench_function("extended_pubk_to_addr", |b| {
+        b.iter(|| extended_pubk_to_addr(black_box(&pubk)).unwrap())
+    });
+    c.bench_function("extended_pubk_to_addr_tron", |b| {
+        b.iter(|| extended_pubk_to_addr_tron(black_box(&pubk)).unwrap())
+    });
+}
+
+fn bench_derivation(c: &mut Criterion) {
+    let seed = seed_bytes();
+
+    c.bench_function("get_extended_keypair", |b| {
+        b.iter_batched(
+            || DerivationPath::from_str("m/44'/60'/0'/0/7").unwrap(),
+            |path| get_extended_keypair(black_box(&seed), &path).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_wallet_address(c: &mut Criterion) {
+    let eth = EthereumWallet::from_seed(HDSeed::new(PHRASE).unwrap());
+    let tron = TronWallet::from_seed(HDSeed::new(PHRASE).unwrap());
+
+    let mut group = c.benchmark_group("wallet_address");
+    group.sample_size(20);
+    group.bench_function("ethereum", |b| {
+        b.iter(|| eth.address(black_box(3)).unwrap())
+    });
+    group.bench_function("tron", |b| b.iter(|| tron.address(black_box(3)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_hashing,
+    bench_pubk_to_addr,
+    bench_derivation,
+    bench_wallet_address
+);
+criterion_main!(benches);
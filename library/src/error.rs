@@ -28,6 +28,30 @@ pub enum Error {
     TronAddrDecodingError,
     #[error("TronAddr is invalid length")]
     TronAddrLengthError,
+    #[error("TRON API error: {0}")]
+    TronApiError(String),
+    #[error("Transaction id {0} does not match the transaction's raw data")]
+    TronTxIdMismatch(String),
+    #[error("Transaction {txid} ran out of energy, {fee} sun burnt")]
+    TronOutOfEnergy { txid: String, fee: u64 },
+    #[error("Transaction {txid} reverted: {reason}")]
+    TronReverted { txid: String, reason: String },
+    #[error("Transaction {txid} failed: {reason}")]
+    TronContractFailed { txid: String, reason: String },
+    #[error("Sending to the inactive account {address} needs {needed} sun with its activation, the sender has {balance}")]
+    TronActivationUnfunded {
+        address: String,
+        needed: ethers::types::U256,
+        balance: ethers::types::U256,
+    },
+    #[error("Signatures weigh {weight} of the {threshold} needed: {reason}")]
+    TronSignWeightTooLow {
+        weight: u64,
+        threshold: u64,
+        reason: String,
+    },
+    #[error("Invalid signature")]
+    SignatureError(#[from] ethers::types::SignatureError),
     #[error("Serde parse error")]
     SerdeParseError(#[from] serde_json::Error),
     #[error("Ethers Contract ABI error")]
@@ -46,8 +70,94 @@ pub enum Error {
             ethers::signers::Wallet<ecdsa::SigningKey<ethers::core::k256::Secp256k1>>,
         >,
     ),
-    #[error("Web3 Contract error")]
-    Web3ContractError(#[from] web3::contract::Error),
+    #[error("Ethers signer contract error")]
+    EthersSignerContractError(
+        #[from]
+        ethers::contract::ContractError<
+            signer::SignerMiddleware<
+                Provider<Http>,
+                ethers::signers::Wallet<ecdsa::SigningKey<ethers::core::k256::Secp256k1>>,
+            >,
+        >,
+    ),
     #[error("Eth transfer tx receipt is none")]
     EthNoneTransferTransactionReceiptError,
+    #[error("Provider is on chain {actual}, refusing to sign for chain {expected}")]
+    ChainIdMismatch {
+        expected: u64,
+        actual: ethers::types::U256,
+    },
+    #[error("Transaction sender {0} is not the address of the signing key")]
+    SenderMismatch(String),
+    #[error("ENS name {0} does not resolve to an address")]
+    EnsNameNotFound(String),
+    #[error("HTTP request error")]
+    HttpError(#[from] reqwest::Error),
+    #[error("Transaction broadcast rejected: {0}")]
+    BroadcastError(String),
+    #[error("No wallet is registered for the chain family {0}")]
+    UnknownWalletFamily(String),
+    #[error("Chain {chain} does not configure the parameters of the {family} family")]
+    UnsupportedChain { family: String, chain: String },
+    #[error("Tokens are not supported on this chain")]
+    TokensNotSupported,
+    #[error("Amount {0} does not fit the chain's amount type")]
+    AmountOverflow(ethers::types::U256),
+    #[error("Insufficient funds: {needed} needed, {available} available")]
+    InsufficientFunds { needed: u64, available: u64 },
+    #[error("Nothing to sweep, the balance is zero")]
+    NothingToSweep,
+    #[error("Amount {amount} is below the dust limit of {dust_limit}")]
+    AmountBelowDust { amount: u64, dust_limit: u64 },
+    #[error("Balance of {available} does not cover the fee of {fee}")]
+    InsufficientFundsForFee {
+        fee: ethers::types::U256,
+        available: ethers::types::U256,
+    },
+    #[error("No fee estimate available")]
+    FeeEstimateUnavailable,
+    #[error("Invalid address: {0}")]
+    UtxoAddressError(String),
+    #[error("Address type not supported on {0}")]
+    UnsupportedAddressType(&'static str),
+    #[error("Bitcoin hex error")]
+    BitcoinHexError(#[from] bitcoin::hashes::hex::HexToArrayError),
+    #[error("PSBT error")]
+    PsbtError(#[from] bitcoin::psbt::Error),
+    #[error("Bitcoin transaction decoding error")]
+    BitcoinEncodeError(#[from] bitcoin::consensus::encode::Error),
+    #[error("Sighash error")]
+    SighashError(#[from] bitcoin::sighash::Error),
+    #[error("PSBT input {0} can't be signed or finalized")]
+    PsbtInputError(usize),
+    #[error("SLIP-10 ed25519 derivation only supports hardened indices")]
+    Slip10NonHardenedError,
+    #[error("Invalid Solana address: {0}")]
+    SolanaAddressError(String),
+    #[error("Solana RPC error: {0}")]
+    SolanaRpcError(String),
+    #[error("Invalid Stellar address: {0}")]
+    StellarAddressError(String),
+    #[error("Invalid Stellar asset: {0}")]
+    StellarAssetError(String),
+    #[error("Stellar account {0} does not exist")]
+    StellarAccountNotFound(String),
+    #[error("Stellar account {0} has no trustline for the asset")]
+    StellarNoTrustline(String),
+    #[error("Horizon error: {0}")]
+    HorizonError(String),
+    #[error("Invalid Cosmos address: {0}")]
+    CosmosAddressError(String),
+    #[error("Cosmos LCD error: {0}")]
+    CosmosLcdError(String),
+    #[error("Invalid XRP Ledger address: {0}")]
+    XrpAddressError(String),
+    #[error("XRP Ledger account {0} does not exist")]
+    XrpAccountNotFound(String),
+    #[error(
+        "Paying {address} creates it and must send at least the base reserve of {reserve} drops"
+    )]
+    XrpUnfundedDestination { address: String, reserve: u64 },
+    #[error("XRP Ledger RPC error: {0}")]
+    XrplRpcError(String),
 }
@@ -0,0 +1,827 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bitcoin::bip32::{DerivationPath, Error};
+use ethers::{types::U256, utils::format_units};
+use serde::{Deserialize, Serialize};
+
+use crate::wallet::cosmos::network::CosmosNetwork;
+
+use super::network::Network;
+
+/// Description of a chain on one of its networks.
+///
+/// The wallet serving a chain is picked by its `family` in a `WalletRegistry`. Any EVM
+/// chain is served by `EthereumWallet`, so new EVM networks can be added from
+/// configuration without code changes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainConfig {
+    /// Registry key, e.g. `arbitrum`. Matched case-insensitively.
+    pub name: String,
+    /// Wallet implementation serving the chain, e.g. `evm` or `utxo`, `evm` when omitted.
+    #[serde(default = "default_family")]
+    pub family: String,
+    /// Network this entry describes, mainnet when omitted.
+    #[serde(default)]
+    pub network: Network,
+    /// Chain id, as returned by `eth_chainId`.
+    pub chain_id: u64,
+    /// SLIP-44 coin type used in the derivation path `m/44'/coin_type'/0'/0/i`.
+    #[serde(default = "default_coin_type")]
+    pub coin_type: u32,
+    /// Symbol of the native currency, e.g. `ETH`.
+    pub native_symbol: String,
+    /// Decimals of the native currency.
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+    /// Whether the chain accepts EIP-1559 (type 2) transactions. Transfers and sweeps are
+    /// sent as type 2 when set, as legacy transactions otherwise.
+    #[serde(default)]
+    pub eip1559: bool,
+    /// Token contracts reported for this chain and network.
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    /// JSON-RPC endpoint.
+    #[serde(default)]
+    pub provider: String,
+    /// Block explorer links.
+    #[serde(default)]
+    pub explorer: Option<ExplorerConfig>,
+    /// ENS registry override, the mainnet ENS registry is used when unset.
+    #[serde(default)]
+    pub ens_registry: Option<String>,
+    /// How the chain charges for transactions, `l1` when omitted.
+    #[serde(default)]
+    pub fee_model: FeeModel,
+    /// Most a contract call may burn, in base units, on chains that cap it (TRON's
+    /// `fee_limit`). The wallet's default applies when unset.
+    #[serde(default)]
+    pub fee_limit: Option<u64>,
+    /// Network of a `utxo` chain, by its `UtxoNetwork` name, e.g. `litecoin-testnet`.
+    #[serde(default)]
+    pub utxo_network: Option<String>,
+    /// Address prefix, chain id, denomination and gas price of a `cosmos` chain.
+    #[serde(default)]
+    pub cosmos: Option<CosmosNetwork>,
+}
+
+/// How an EVM chain charges for a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeModel {
+    /// Gas times gas price, as estimated by `eth_estimateGas`.
+    #[default]
+    L1,
+    /// OP-stack rollups (Optimism, Base) charge an L1 data fee on top of the gas,
+    /// quoted by the `GasPriceOracle` predeploy.
+    OpStack,
+    /// Arbitrum charges the L1 data cost as extra gas, quoted by `NodeInterface`.
+    Arbitrum,
+}
+
+/// Block explorer URL templates, `{}` is replaced by the transaction id or address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplorerConfig {
+    pub tx: String,
+    pub address: String,
+}
+
+impl ExplorerConfig {
+    /// Etherscan-style explorer at `base`, e.g. `https://etherscan.io`.
+    pub fn etherscan(base: &str) -> Self {
+        ExplorerConfig {
+            tx: format!("{}/tx/{{}}", base),
+            address: format!("{}/address/{{}}", base),
+        }
+    }
+
+    /// Tronscan-style explorer at `base`, e.g. `https://tronscan.org`.
+    pub fn tronscan(base: &str) -> Self {
+        ExplorerConfig {
+            tx: format!("{}/#/transaction/{{}}", base),
+            address: format!("{}/#/address/{{}}", base),
+        }
+    }
+
+    /// Solscan explorer, on `cluster` when it isn't mainnet.
+    pub fn solscan(cluster: Option<&str>) -> Self {
+        let query = cluster.map_or(String::new(), |c| format!("?cluster={}", c));
+        ExplorerConfig {
+            tx: format!("https://solscan.io/tx/{{}}{}", query),
+            address: format!("https://solscan.io/account/{{}}{}", query),
+        }
+    }
+}
+
+fn default_family() -> String {
+    "evm".to_owned()
+}
+
+fn default_coin_type() -> u32 {
+    60
+}
+
+fn default_decimals() -> u8 {
+    18
+}
+
+impl ChainConfig {
+    pub fn new(name: &str, chain_id: u64, native_symbol: &str) -> Self {
+        ChainConfig {
+            name: name.to_lowercase(),
+            family: default_family(),
+            network: Network::Mainnet,
+            chain_id,
+            coin_type: default_coin_type(),
+            native_symbol: native_symbol.to_owned(),
+            decimals: default_decimals(),
+            eip1559: false,
+            tokens: vec![],
+            provider: String::new(),
+            explorer: None,
+            ens_registry: None,
+            fee_model: FeeModel::L1,
+            fee_limit: None,
+            utxo_network: None,
+            cosmos: None,
+        }
+    }
+
+    pub fn ethereum() -> Self {
+        ChainConfig {
+            eip1559: true,
+            explorer: Some(ExplorerConfig::etherscan("https://etherscan.io")),
+            ..ChainConfig::new("eth", 1, "ETH")
+        }
+    }
+
+    /// Ethereum Sepolia.
+    pub fn ethereum_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            eip1559: true,
+            explorer: Some(ExplorerConfig::etherscan("https://sepolia.etherscan.io")),
+            ..ChainConfig::new("eth", 11155111, "ETH")
+        }
+    }
+
+    pub fn bsc() -> Self {
+        ChainConfig {
+            explorer: Some(ExplorerConfig::etherscan("https://bscscan.com")),
+            ..ChainConfig::new("bsc", 56, "BNB")
+        }
+    }
+
+    pub fn bsc_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: Some(ExplorerConfig::etherscan("https://testnet.bscscan.com")),
+            ..ChainConfig::new("bsc", 97, "tBNB")
+        }
+    }
+
+    pub fn polygon() -> Self {
+        ChainConfig {
+            eip1559: true,
+            explorer: Some(ExplorerConfig::etherscan("https://polygonscan.com")),
+            ..ChainConfig::new("polygon", 137, "MATIC")
+        }
+    }
+
+    /// Polygon Amoy.
+    pub fn polygon_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            eip1559: true,
+            explorer: Some(ExplorerConfig::etherscan("https://amoy.polygonscan.com")),
+            ..ChainConfig::new("polygon", 80002, "POL")
+        }
+    }
+
+    pub fn optimism() -> Self {
+        ChainConfig {
+            eip1559: true,
+            fee_model: FeeModel::OpStack,
+            explorer: Some(ExplorerConfig::etherscan("https://optimistic.etherscan.io")),
+            ..ChainConfig::new("optimism", 10, "ETH")
+        }
+    }
+
+    /// OP Sepolia.
+    pub fn optimism_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            chain_id: 11155420,
+            explorer: Some(ExplorerConfig::etherscan(
+                "https://sepolia-optimism.etherscan.io",
+            )),
+            ..ChainConfig::optimism()
+        }
+    }
+
+    pub fn base() -> Self {
+        ChainConfig {
+            eip1559: true,
+            fee_model: FeeModel::OpStack,
+            explorer: Some(ExplorerConfig::etherscan("https://basescan.org")),
+            ..ChainConfig::new("base", 8453, "ETH")
+        }
+    }
+
+    /// Base Sepolia.
+    pub fn base_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            chain_id: 84532,
+            explorer: Some(ExplorerConfig::etherscan("https://sepolia.basescan.org")),
+            ..ChainConfig::base()
+        }
+    }
+
+    /// Arbitrum One.
+    pub fn arbitrum() -> Self {
+        ChainConfig {
+            eip1559: true,
+            fee_model: FeeModel::Arbitrum,
+            explorer: Some(ExplorerConfig::etherscan("https://arbiscan.io")),
+            ..ChainConfig::new("arbitrum", 42161, "ETH")
+        }
+    }
+
+    /// Arbitrum Sepolia.
+    pub fn arbitrum_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            chain_id: 421614,
+            explorer: Some(ExplorerConfig::etherscan("https://sepolia.arbiscan.io")),
+            ..ChainConfig::arbitrum()
+        }
+    }
+
+    /// zkSync Era. Paymasters and custom pubdata limits need `EthereumWallet::send_zksync`.
+    pub fn zksync() -> Self {
+        ChainConfig {
+            eip1559: true,
+            explorer: Some(ExplorerConfig::etherscan("https://explorer.zksync.io")),
+            ..ChainConfig::new("zksync", 324, "ETH")
+        }
+    }
+
+    /// zkSync Era Sepolia.
+    pub fn zksync_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            chain_id: 300,
+            explorer: Some(ExplorerConfig::etherscan(
+                "https://sepolia.explorer.zksync.io",
+            )),
+            ..ChainConfig::zksync()
+        }
+    }
+
+    pub fn tron() -> Self {
+        ChainConfig {
+            family: "tron".to_owned(),
+            coin_type: 195,
+            decimals: 6,
+            explorer: Some(ExplorerConfig::tronscan("https://tronscan.org")),
+            ..ChainConfig::new("tron", 728126428, "TRX")
+        }
+    }
+
+    /// Tron Nile.
+    pub fn tron_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: Some(ExplorerConfig::tronscan("https://nile.tronscan.org")),
+            ..ChainConfig {
+                chain_id: 3448148188,
+                ..ChainConfig::tron()
+            }
+        }
+    }
+
+    /// Tron Shasta.
+    pub fn tron_shasta() -> Self {
+        ChainConfig {
+            network: Network::Custom("shasta".to_owned()),
+            explorer: Some(ExplorerConfig::tronscan("https://shasta.tronscan.org")),
+            ..ChainConfig {
+                chain_id: 2494104990,
+                ..ChainConfig::tron()
+            }
+        }
+    }
+
+    /// Bitcoin, with `provider` pointing at an Esplora-compatible API.
+    /// Bitcoin has no chain id, `chain_id` is 0.
+    pub fn bitcoin() -> Self {
+        ChainConfig {
+            family: "utxo".to_owned(),
+            coin_type: 0,
+            decimals: 8,
+            explorer: Some(ExplorerConfig::etherscan("https://mempool.space")),
+            utxo_network: Some("bitcoin".to_owned()),
+            ..ChainConfig::new("btc", 0, "BTC")
+        }
+    }
+
+    pub fn bitcoin_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            coin_type: 1,
+            explorer: Some(ExplorerConfig::etherscan("https://mempool.space/testnet")),
+            utxo_network: Some("bitcoin-testnet".to_owned()),
+            ..ChainConfig::bitcoin()
+        }
+    }
+
+    /// Litecoin, with `provider` pointing at an Esplora-compatible API.
+    pub fn litecoin() -> Self {
+        ChainConfig {
+            family: "utxo".to_owned(),
+            coin_type: 2,
+            decimals: 8,
+            explorer: Some(ExplorerConfig::etherscan("https://litecoinspace.org")),
+            utxo_network: Some("litecoin".to_owned()),
+            ..ChainConfig::new("ltc", 0, "LTC")
+        }
+    }
+
+    pub fn litecoin_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            coin_type: 1,
+            explorer: Some(ExplorerConfig::etherscan(
+                "https://litecoinspace.org/testnet",
+            )),
+            utxo_network: Some("litecoin-testnet".to_owned()),
+            ..ChainConfig::litecoin()
+        }
+    }
+
+    /// Dogecoin, with `provider` pointing at an Esplora-compatible API.
+    pub fn dogecoin() -> Self {
+        ChainConfig {
+            family: "utxo".to_owned(),
+            coin_type: 3,
+            decimals: 8,
+            explorer: Some(ExplorerConfig::etherscan("https://dogechain.info")),
+            utxo_network: Some("dogecoin".to_owned()),
+            ..ChainConfig::new("doge", 0, "DOGE")
+        }
+    }
+
+    pub fn dogecoin_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            coin_type: 1,
+            explorer: None,
+            utxo_network: Some("dogecoin-testnet".to_owned()),
+            ..ChainConfig::dogecoin()
+        }
+    }
+
+    /// Solana, with `provider` pointing at a JSON-RPC node.
+    /// Solana has no numeric chain id, `chain_id` is 0.
+    pub fn solana() -> Self {
+        ChainConfig {
+            family: "solana".to_owned(),
+            coin_type: 501,
+            decimals: 9,
+            explorer: Some(ExplorerConfig::solscan(None)),
+            ..ChainConfig::new("sol", 0, "SOL")
+        }
+    }
+
+    /// Solana devnet.
+    pub fn solana_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: Some(ExplorerConfig::solscan(Some("devnet"))),
+            ..ChainConfig::solana()
+        }
+    }
+
+    /// Stellar, with `provider` pointing at a Horizon API.
+    pub fn stellar() -> Self {
+        ChainConfig {
+            family: "stellar".to_owned(),
+            coin_type: 148,
+            decimals: 7,
+            explorer: Some(ExplorerConfig {
+                tx: "https://stellar.expert/explorer/public/tx/{}".to_owned(),
+                address: "https://stellar.expert/explorer/public/account/{}".to_owned(),
+            }),
+            ..ChainConfig::new("xlm", 0, "XLM")
+        }
+    }
+
+    /// Stellar test network.
+    pub fn stellar_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: Some(ExplorerConfig {
+                tx: "https://stellar.expert/explorer/testnet/tx/{}".to_owned(),
+                address: "https://stellar.expert/explorer/testnet/account/{}".to_owned(),
+            }),
+            ..ChainConfig::stellar()
+        }
+    }
+
+    /// Cosmos Hub, with `provider` pointing at an LCD (REST) endpoint.
+    pub fn cosmos_hub() -> Self {
+        ChainConfig {
+            family: "cosmos".to_owned(),
+            coin_type: 118,
+            decimals: 6,
+            explorer: Some(ExplorerConfig {
+                tx: "https://www.mintscan.io/cosmos/tx/{}".to_owned(),
+                address: "https://www.mintscan.io/cosmos/address/{}".to_owned(),
+            }),
+            cosmos: Some(CosmosNetwork::cosmos_hub()),
+            ..ChainConfig::new("atom", 0, "ATOM")
+        }
+    }
+
+    pub fn cosmos_hub_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: None,
+            cosmos: Some(CosmosNetwork::cosmos_hub_testnet()),
+            ..ChainConfig::cosmos_hub()
+        }
+    }
+
+    /// Osmosis, with `provider` pointing at an LCD (REST) endpoint.
+    pub fn osmosis() -> Self {
+        ChainConfig {
+            family: "cosmos".to_owned(),
+            coin_type: 118,
+            decimals: 6,
+            explorer: Some(ExplorerConfig {
+                tx: "https://www.mintscan.io/osmosis/tx/{}".to_owned(),
+                address: "https://www.mintscan.io/osmosis/address/{}".to_owned(),
+            }),
+            cosmos: Some(CosmosNetwork::osmosis()),
+            ..ChainConfig::new("osmo", 0, "OSMO")
+        }
+    }
+
+    pub fn osmosis_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: None,
+            cosmos: Some(CosmosNetwork::osmosis_testnet()),
+            ..ChainConfig::osmosis()
+        }
+    }
+
+    /// XRP Ledger, with `provider` pointing at a rippled JSON-RPC endpoint.
+    pub fn xrp() -> Self {
+        ChainConfig {
+            family: "xrp".to_owned(),
+            coin_type: 144,
+            decimals: 6,
+            explorer: Some(ExplorerConfig {
+                tx: "https://livenet.xrpl.org/transactions/{}".to_owned(),
+                address: "https://livenet.xrpl.org/accounts/{}".to_owned(),
+            }),
+            ..ChainConfig::new("xrp", 0, "XRP")
+        }
+    }
+
+    /// XRP Ledger testnet.
+    pub fn xrp_testnet() -> Self {
+        ChainConfig {
+            network: Network::Testnet,
+            explorer: Some(ExplorerConfig {
+                tx: "https://testnet.xrpl.org/transactions/{}".to_owned(),
+                address: "https://testnet.xrpl.org/accounts/{}".to_owned(),
+            }),
+            ..ChainConfig::xrp()
+        }
+    }
+
+    /// Whether ENS names resolve on the chain: EVM chains with an `ens_registry`, and
+    /// Ethereum mainnet and Sepolia, which have the default registry.
+    pub fn has_ens(&self) -> bool {
+        self.family == "evm"
+            && (self.ens_registry.is_some() || [1, 11155111].contains(&self.chain_id))
+    }
+
+    pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
+        DerivationPath::from_str(&format!("m/44'/{}'/0'/0/{}", self.coin_type, index))
+    }
+
+    /// `amount` of the native currency in whole units with its symbol, e.g. `1.5 ETH`
+    /// for 1.5e18 wei.
+    pub fn format_amount(&self, amount: U256) -> String {
+        let units = format_units(amount, self.decimals as u32).unwrap_or_default();
+        let units = match units.split_once('.') {
+            Some((whole, fraction)) if fraction.trim_end_matches('0').is_empty() => whole,
+            Some(_) => units.trim_end_matches('0'),
+            None => &units,
+        };
+        format!("{} {}", units, self.native_symbol)
+    }
+
+    /// Explorer link to a transaction, if an explorer is configured.
+    pub fn explorer_tx_url(&self, txid: &str) -> Option<String> {
+        self.explorer
+            .as_ref()
+            .map(|explorer| explorer.tx.replace("{}", txid))
+    }
+
+    /// Explorer link to an address, if an explorer is configured.
+    pub fn explorer_address_url(&self, address: &str) -> Option<String> {
+        self.explorer
+            .as_ref()
+            .map(|explorer| explorer.address.replace("{}", address))
+    }
+}
+
+/// Set of known chains, keyed by lowercase chain name and network.
+#[derive(Debug, Clone, Default)]
+pub struct ChainRegistry {
+    chains: HashMap<(String, Network), ChainConfig>,
+}
+
+impl ChainRegistry {
+    pub fn new() -> Self {
+        ChainRegistry::default()
+    }
+
+    /// Registry with the chains that `Crypto` has dedicated variants for,
+    /// on their mainnets and public testnets.
+    pub fn builtin() -> Self {
+        ChainRegistry::from_configs(vec![
+            ChainConfig::ethereum(),
+            ChainConfig::ethereum_testnet(),
+            ChainConfig::bsc(),
+            ChainConfig::bsc_testnet(),
+            ChainConfig::polygon(),
+            ChainConfig::polygon_testnet(),
+            ChainConfig::optimism(),
+            ChainConfig::optimism_testnet(),
+            ChainConfig::base(),
+            ChainConfig::base_testnet(),
+            ChainConfig::arbitrum(),
+            ChainConfig::arbitrum_testnet(),
+            ChainConfig::zksync(),
+            ChainConfig::zksync_testnet(),
+            ChainConfig::tron(),
+            ChainConfig::tron_testnet(),
+            ChainConfig::tron_shasta(),
+            ChainConfig::bitcoin(),
+            ChainConfig::bitcoin_testnet(),
+            ChainConfig::litecoin(),
+            ChainConfig::litecoin_testnet(),
+            ChainConfig::dogecoin(),
+            ChainConfig::dogecoin_testnet(),
+            ChainConfig::solana(),
+            ChainConfig::solana_testnet(),
+            ChainConfig::stellar(),
+            ChainConfig::stellar_testnet(),
+            ChainConfig::cosmos_hub(),
+            ChainConfig::cosmos_hub_testnet(),
+            ChainConfig::osmosis(),
+            ChainConfig::osmosis_testnet(),
+            ChainConfig::xrp(),
+            ChainConfig::xrp_testnet(),
+        ])
+    }
+
+    pub fn from_configs(configs: Vec<ChainConfig>) -> Self {
+        let mut registry = ChainRegistry::new();
+        for config in configs {
+            registry.register(config);
+        }
+        registry
+    }
+
+    /// Adds a chain, replacing any chain registered under the same name and network.
+    pub fn register(&mut self, mut config: ChainConfig) {
+        config.name = config.name.to_lowercase();
+        self.chains
+            .insert((config.name.clone(), config.network.clone()), config);
+    }
+
+    /// Lays the fields of `overlay`, a chain in configuration form such as a `[[chains]]`
+    /// table, over the chain registered under the same name and network. Chains not
+    /// registered yet are added as given, the fields left out taking their defaults.
+    pub fn merge(&mut self, overlay: serde_json::Value) -> Result<(), serde_json::Error> {
+        #[derive(Deserialize)]
+        struct Key {
+            name: String,
+            #[serde(default)]
+            network: Network,
+        }
+
+        let key: Key = serde_json::from_value(overlay.clone())?;
+        let config = match (self.get(&key.name, &key.network), overlay) {
+            (Some(chain), serde_json::Value::Object(fields)) => {
+                let mut merged = serde_json::to_value(chain)?;
+                if let serde_json::Value::Object(object) = &mut merged {
+                    object.extend(fields);
+                }
+                serde_json::from_value(merged)?
+            }
+            (_, overlay) => serde_json::from_value(overlay)?,
+        };
+        self.register(config);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str, network: &Network) -> Option<&ChainConfig> {
+        self.chains.get(&(name.to_lowercase(), network.clone()))
+    }
+
+    pub fn get_mut(&mut self, name: &str, network: &Network) -> Option<&mut ChainConfig> {
+        self.chains.get_mut(&(name.to_lowercase(), network.clone()))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.chains.keys().map(|(name, _)| name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Networks a chain is registered on.
+    pub fn networks(&self, name: &str) -> Vec<&Network> {
+        let name = name.to_lowercase();
+        self.chains
+            .keys()
+            .filter(|(chain, _)| *chain == name)
+            .map(|(_, network)| network)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_config_from_json() {
+        let config: ChainConfig = serde_json::from_str(
+            r#"{"name": "Arbitrum", "chain_id": 42161, "native_symbol": "ETH", "eip1559": true}"#,
+        )
+        .unwrap();
+        assert_eq!(config.network, Network::Mainnet);
+        assert_eq!(config.coin_type, 60);
+        assert_eq!(config.family, "evm");
+        assert_eq!(config.decimals, 18);
+        assert_eq!(config.fee_model, FeeModel::L1);
+        assert!(config.tokens.is_empty());
+
+        let registry = ChainRegistry::from_configs(vec![config]);
+        assert_eq!(
+            registry
+                .get("arbitrum", &Network::Mainnet)
+                .unwrap()
+                .chain_id,
+            42161
+        );
+        assert_eq!(
+            registry.get("ARBITRUM", &Network::Mainnet).unwrap().name,
+            "arbitrum"
+        );
+        assert!(registry.get("arbitrum", &Network::Testnet).is_none());
+    }
+
+    #[test]
+    fn test_fee_model_from_json() {
+        let config: ChainConfig = serde_json::from_str(
+            r#"{"name": "zora", "chain_id": 7777777, "native_symbol": "ETH", "fee_model": "op_stack"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.fee_model, FeeModel::OpStack);
+        let registry = ChainRegistry::builtin();
+        assert_eq!(
+            registry
+                .get("arbitrum", &Network::Testnet)
+                .unwrap()
+                .fee_model,
+            FeeModel::Arbitrum
+        );
+    }
+
+    #[test]
+    fn test_merge_over_builtin() {
+        let mut registry = ChainRegistry::builtin();
+        registry
+            .merge(serde_json::json!({
+                "name": "Tron",
+                "network": "testnet",
+                "provider": "https://nile.trongrid.io"
+            }))
+            .unwrap();
+        let nile = registry.get("tron", &Network::Testnet).unwrap();
+        assert_eq!(nile.provider, "https://nile.trongrid.io");
+        assert_eq!(nile.family, "tron");
+        assert_eq!(nile.decimals, 6);
+        assert_eq!(nile.explorer, ChainConfig::tron_testnet().explorer);
+        assert!(nile.explorer.is_some());
+        assert_eq!(
+            registry.get("tron", &Network::Mainnet).unwrap(),
+            &ChainConfig::tron()
+        );
+
+        registry
+            .merge(serde_json::json!({
+                "name": "eth",
+                "network": "testnet",
+                "provider": "https://sepolia.example",
+                "tokens": ["0x1c7D4B196Cb0C7B01d743Fbc6116a902379C7238"]
+            }))
+            .unwrap();
+        let sepolia = registry.get("eth", &Network::Testnet).unwrap();
+        assert!(sepolia.eip1559);
+        assert_eq!(sepolia.chain_id, 11155111);
+        assert_eq!(sepolia.tokens.len(), 1);
+        assert!(sepolia.explorer.is_some());
+
+        // chains that aren't built in take the defaults
+        registry
+            .merge(serde_json::json!({"name": "linea", "chain_id": 59144, "native_symbol": "ETH"}))
+            .unwrap();
+        let linea = registry.get("linea", &Network::Mainnet).unwrap();
+        assert_eq!(linea.family, "evm");
+        assert_eq!(linea.coin_type, 60);
+        assert!(registry
+            .merge(serde_json::json!({"name": "scroll", "native_symbol": "ETH"}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_networks_are_separate() {
+        let mut registry = ChainRegistry::builtin();
+        registry.get_mut("eth", &Network::Mainnet).unwrap().tokens =
+            vec!["0xdAC17F958D2ee523a2206206994597C13D831ec7".to_owned()];
+
+        let sepolia = registry.get("eth", &Network::Testnet).unwrap();
+        assert_eq!(sepolia.chain_id, 11155111);
+        assert!(sepolia.tokens.is_empty());
+
+        let shasta = Network::Custom("shasta".to_owned());
+        assert_eq!(registry.get("tron", &shasta).unwrap().chain_id, 2494104990);
+        assert_eq!(registry.networks("polygon").len(), 2);
+    }
+
+    #[test]
+    fn test_hd_path() {
+        let mut config = ChainConfig::new("avalanche", 43114, "AVAX");
+        config.coin_type = 9000;
+        assert_eq!(
+            config.get_hd_path(3).unwrap().to_string(),
+            "m/44'/9000'/0'/0/3"
+        );
+    }
+
+    #[test]
+    fn test_has_ens() {
+        assert!(ChainConfig::ethereum().has_ens());
+        assert!(ChainConfig::ethereum_testnet().has_ens());
+        assert!(!ChainConfig::bsc().has_ens());
+        assert!(!ChainConfig::polygon_testnet().has_ens());
+        assert!(!ChainConfig::tron().has_ens());
+        assert!(!ChainConfig::bitcoin().has_ens());
+        assert!(ChainConfig {
+            ens_registry: Some("0x08f8fe9a8d1c3e8eae3a1e1e84bd2cdf2b0ab4e4".to_owned()),
+            ..ChainConfig::bsc()
+        }
+        .has_ens());
+    }
+
+    #[test]
+    fn test_explorer_urls() {
+        let config = ChainConfig::ethereum_testnet();
+        assert_eq!(
+            config.explorer_tx_url("0xabc").unwrap(),
+            "https://sepolia.etherscan.io/tx/0xabc"
+        );
+        assert_eq!(
+            ChainConfig::tron().explorer_address_url("TUEZ").unwrap(),
+            "https://tronscan.org/#/address/TUEZ"
+        );
+        assert!(ChainConfig::new("devnet", 31337, "ETH")
+            .explorer_address_url("0xabc")
+            .is_none());
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let eth = ChainConfig::ethereum();
+        assert_eq!(
+            eth.format_amount(U256::from(1_500_000_000_000_000_000u64)),
+            "1.5 ETH"
+        );
+        assert_eq!(eth.format_amount(U256::exp10(18) * 3), "3 ETH");
+        assert_eq!(eth.format_amount(U256::zero()), "0 ETH");
+        assert_eq!(
+            ChainConfig::tron().format_amount(U256::from(1_000_001)),
+            "1.000001 TRX"
+        );
+    }
+}
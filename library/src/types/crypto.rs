@@ -2,22 +2,73 @@ use std::str::FromStr;
 
 use bitcoin::bip32::{DerivationPath, Error};
 
+use super::chain::ChainRegistry;
+
 #[derive(Debug, Clone)]
 pub enum Crypto {
     Eth,
     Tron,
     Polygon,
     BSC,
+    Btc,
+    Ltc,
+    Doge,
+    Sol,
+    Xlm,
+    Atom,
+    Osmo,
+    Xrp,
+    /// Any other chain, looked up by name in a `ChainRegistry`. Built with `Crypto::resolve`.
+    Evm(String),
 }
 
 impl Crypto {
     pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
         let str_path = match self {
-            Crypto::Eth | Crypto::Polygon | Crypto::BSC => format!("m/44'/60'/0'/0/{}", index),
+            Crypto::Eth | Crypto::Polygon | Crypto::BSC | Crypto::Evm(_) => {
+                format!("m/44'/60'/0'/0/{}", index)
+            }
             Crypto::Tron => format!("m/44'/195'/0'/0/{}", index),
+            Crypto::Btc => format!("m/84'/0'/0'/0/{}", index),
+            Crypto::Ltc => format!("m/84'/2'/0'/0/{}", index),
+            Crypto::Doge => format!("m/44'/3'/0'/0/{}", index),
+            Crypto::Sol => format!("m/44'/501'/{}'/0'", index),
+            Crypto::Xlm => format!("m/44'/148'/{}'", index),
+            Crypto::Atom | Crypto::Osmo => format!("m/44'/118'/0'/0/{}", index),
+            Crypto::Xrp => format!("m/44'/144'/0'/0/{}", index),
         };
         DerivationPath::from_str(&str_path)
     }
+
+    /// Name under which the chain is found in a `ChainRegistry`.
+    pub fn chain_name(&self) -> &str {
+        match self {
+            Crypto::Eth => "eth",
+            Crypto::Tron => "tron",
+            Crypto::Polygon => "polygon",
+            Crypto::BSC => "bsc",
+            Crypto::Btc => "btc",
+            Crypto::Ltc => "ltc",
+            Crypto::Doge => "doge",
+            Crypto::Sol => "sol",
+            Crypto::Xlm => "xlm",
+            Crypto::Atom => "atom",
+            Crypto::Osmo => "osmo",
+            Crypto::Xrp => "xrp",
+            Crypto::Evm(name) => name,
+        }
+    }
+
+    /// Parses `name` as a built-in chain, or as any other chain `chains` has an entry for.
+    pub fn resolve(name: &str, chains: &ChainRegistry) -> Option<Crypto> {
+        Crypto::from_str(name).ok().or_else(|| {
+            let name = name.to_lowercase();
+            chains
+                .names()
+                .contains(&name.as_str())
+                .then_some(Crypto::Evm(name))
+        })
+    }
 }
 
 impl FromStr for Crypto {
@@ -29,6 +80,14 @@ impl FromStr for Crypto {
             "tron" => Ok(Crypto::Tron),
             "polygon" => Ok(Crypto::Polygon),
             "bsc" => Ok(Crypto::BSC),
+            "btc" | "bitcoin" => Ok(Crypto::Btc),
+            "ltc" | "litecoin" => Ok(Crypto::Ltc),
+            "doge" | "dogecoin" => Ok(Crypto::Doge),
+            "sol" | "solana" => Ok(Crypto::Sol),
+            "xlm" | "stellar" => Ok(Crypto::Xlm),
+            "atom" | "cosmos" => Ok(Crypto::Atom),
+            "osmo" | "osmosis" => Ok(Crypto::Osmo),
+            "xrp" | "ripple" => Ok(Crypto::Xrp),
             _ => Err("Unknown crypto"),
         }
     }
@@ -41,7 +100,49 @@ impl std::fmt::Display for Crypto {
             Crypto::Tron => "Tron",
             Crypto::Polygon => "Polygon",
             Crypto::BSC => "Binance Smart Chain",
+            Crypto::Btc => "Bitcoin",
+            Crypto::Ltc => "Litecoin",
+            Crypto::Doge => "Dogecoin",
+            Crypto::Sol => "Solana",
+            Crypto::Xlm => "Stellar",
+            Crypto::Atom => "Cosmos Hub",
+            Crypto::Osmo => "Osmosis",
+            Crypto::Xrp => "XRP Ledger",
+            Crypto::Evm(name) => name,
         };
         write!(f, "{}", s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::chain::ChainConfig;
+
+    #[test]
+    fn test_from_str() {
+        assert!(matches!(Crypto::from_str("ETH"), Ok(Crypto::Eth)));
+        assert!(matches!(Crypto::from_str("dogecoin"), Ok(Crypto::Doge)));
+        assert!(Crypto::from_str("etth").is_err());
+        assert!(Crypto::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut chains = ChainRegistry::builtin();
+        chains.register(ChainConfig::new("Avalanche", 43114, "AVAX"));
+        assert!(matches!(
+            Crypto::resolve("tron", &chains),
+            Some(Crypto::Tron)
+        ));
+        assert!(matches!(
+            Crypto::resolve("Avalanche", &chains),
+            Some(Crypto::Evm(name)) if name == "avalanche"
+        ));
+        assert!(matches!(
+            Crypto::resolve("arbitrum", &chains),
+            Some(Crypto::Evm(name)) if name == "arbitrum"
+        ));
+        assert!(Crypto::resolve("fantom", &chains).is_none());
+    }
+}
@@ -1,6 +1,9 @@
 pub mod balance_state;
+pub mod chain;
 pub mod crypto;
 pub mod hdseed;
+pub mod network;
 pub mod token_data;
+pub mod transfer_receipt;
 pub mod wallet_address;
 pub mod wallet_state;
@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Network of a chain a wallet operates on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    /// The main public testnet of the chain, e.g. Sepolia or Nile.
+    Testnet,
+    /// Any other network, e.g. Tron Shasta or a local devnet.
+    Custom(String),
+}
+
+impl Network {
+    pub fn is_mainnet(&self) -> bool {
+        *self == Network::Mainnet
+    }
+}
+
+impl FromStr for Network {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "" => Err("Unknown network"),
+            name => Ok(Network::Custom(name.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for Network {
+    type Error = &'static str;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Network::from_str(&s)
+    }
+}
+
+impl From<Network> for String {
+    fn from(network: Network) -> Self {
+        network.to_string()
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let s = match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Custom(name) => name,
+        };
+        write!(f, "{}", s)
+    }
+}
@@ -0,0 +1,29 @@
+use ethers::types::TransactionReceipt;
+
+/// Outcome of a transfer, in the form the chain reports it.
+#[derive(Debug, Clone)]
+pub enum TransferReceipt {
+    /// Receipt of a mined EVM transaction.
+    Evm(Box<TransactionReceipt>),
+    /// Transaction accepted by the node, not yet confirmed.
+    Broadcast { txid: String },
+    /// Tron transaction, with the block it was confirmed in, if it was by the time the
+    /// wallet stopped waiting, and the fee it burnt in sun.
+    Tron {
+        txid: String,
+        block_number: Option<u64>,
+        fee: u64,
+    },
+}
+
+impl TransferReceipt {
+    /// Transaction id as displayed by the chain's explorers.
+    pub fn txid(&self) -> String {
+        match self {
+            TransferReceipt::Evm(receipt) => format!("{:?}", receipt.transaction_hash),
+            TransferReceipt::Broadcast { txid } | TransferReceipt::Tron { txid, .. } => {
+                txid.clone()
+            }
+        }
+    }
+}
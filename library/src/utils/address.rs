@@ -9,5 +9,5 @@ pub fn address_str_to_h160(address_str: &str) -> Result<H160, Error> {
     let trimmed_address = address_str.trim_start_matches("0x");
 
     // Преобразовать строку в H160
-    H160::from_str(trimmed_address).map_err(|e| Error::AddrToH160Error(e))
+    H160::from_str(trimmed_address).map_err(Error::AddrToH160Error)
 }
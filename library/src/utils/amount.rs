@@ -0,0 +1,30 @@
+use ethers::types::U256;
+
+use crate::error::Error;
+
+/// `amount` as the integer type a chain encodes amounts in, e.g. `u64` lamports or
+/// `i64` stroops. Fails with `Error::AmountOverflow` when it doesn't fit.
+pub fn amount_as<T: TryFrom<u128>>(amount: U256) -> Result<T, Error> {
+    u128::try_from(amount)
+        .ok()
+        .and_then(|amount| T::try_from(amount).ok())
+        .ok_or(Error::AmountOverflow(amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_as() {
+        assert_eq!(amount_as::<u64>(U256::from(u64::MAX)).unwrap(), u64::MAX);
+        assert_eq!(amount_as::<i64>(U256::from(i64::MAX)).unwrap(), i64::MAX);
+        assert!(matches!(
+            amount_as::<i64>(U256::from(i64::MAX) + 1),
+            Err(Error::AmountOverflow(amount)) if amount == U256::from(i64::MAX) + 1
+        ));
+        assert_eq!(amount_as::<u128>(U256::from(u128::MAX)).unwrap(), u128::MAX);
+        assert!(amount_as::<u128>(U256::from(u128::MAX) + 1).is_err());
+        assert!(amount_as::<u64>(U256::MAX).is_err());
+    }
+}
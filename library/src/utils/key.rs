@@ -1,10 +1,12 @@
 use bip39::{Mnemonic, Seed};
 use bitcoin::{
-    bip32::{DerivationPath, Xpriv, Xpub},
+    bip32::{ChildNumber, DerivationPath, Fingerprint, Xpriv, Xpub},
     Network,
 };
+use hmac::{Hmac, Mac};
 use secp256k1::Secp256k1;
 use serde::Serialize;
+use sha2::Sha512;
 use sha3::{Digest, Keccak256};
 
 use crate::error::Error;
@@ -22,10 +24,20 @@ pub fn keccak_hash<T>(data: &T) -> String
 where
     T: ?Sized + Serialize + AsRef<[u8]>,
 {
-    let mut hasher = Keccak256::new();
-    hasher.update(data);
-    let result = hasher.finalize();
-    hex::encode(result)
+    hex::encode(keccak256(data.as_ref()))
+}
+
+/// Keccak-256 digest of `data` as raw bytes.
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Keccak-256 of the 64-byte uncompressed public key (without the `0x04` tag).
+///
+/// The last 20 bytes of this digest are the account id shared by Ethereum and Tron addresses.
+pub fn pubkey_hash(pubk: &secp256k1::PublicKey) -> [u8; 32] {
+    let uncompressed = pubk.serialize_uncompressed();
+    keccak256(&uncompressed[1..])
 }
 
 pub fn keypair_by_index(
@@ -37,3 +49,91 @@ pub fn keypair_by_index(
 
     Ok((privk, pubk))
 }
+
+/// Fingerprint of the BIP32 master key, used as the key origin in PSBTs.
+pub fn master_fingerprint(mnemonic: &Mnemonic) -> Result<Fingerprint, Error> {
+    let secp = Secp256k1::new();
+    let seed_m = Seed::new(mnemonic, "");
+    let master = Xpriv::new_master(Network::Bitcoin, seed_m.as_bytes())?;
+    Ok(master.fingerprint(&secp))
+}
+
+type HmacSha512 = Hmac<Sha512>;
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = HmacSha512::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for part in data {
+        mac.update(part);
+    }
+    let digest = mac.finalize().into_bytes();
+    let (mut key, mut chain_code) = ([0u8; 32], [0u8; 32]);
+    key.copy_from_slice(&digest[..32]);
+    chain_code.copy_from_slice(&digest[32..]);
+    (key, chain_code)
+}
+
+/// SLIP-10 ed25519 private key of `seed` at `hd_path`.
+///
+/// ed25519 only has hardened derivation, a path with a normal index is rejected.
+pub fn slip10_ed25519(seed: &[u8], hd_path: &DerivationPath) -> Result<[u8; 32], Error> {
+    let (mut key, mut chain_code) = hmac_sha512(b"ed25519 seed", &[seed]);
+    for child in hd_path {
+        let index = match child {
+            ChildNumber::Hardened { index } => index | 0x8000_0000,
+            ChildNumber::Normal { .. } => return Err(Error::Slip10NonHardenedError),
+        };
+        (key, chain_code) = hmac_sha512(&chain_code, &[&[0], &key, &index.to_be_bytes()]);
+    }
+    Ok(key)
+}
+
+/// SLIP-10 ed25519 private key of the mnemonic at `derivation_path`.
+pub fn ed25519_key_by_index(
+    mnemonic: &Mnemonic,
+    derivation_path: &DerivationPath,
+) -> Result<[u8; 32], Error> {
+    let seed_m = Seed::new(mnemonic, "");
+    slip10_ed25519(seed_m.as_bytes(), derivation_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    // SLIP-10 test vector 1 for ed25519
+    const SEED: &str = "000102030405060708090a0b0c0d0e0f";
+
+    #[test]
+    fn test_slip10_ed25519_vectors() {
+        let seed = hex::decode(SEED).unwrap();
+        let vectors = [
+            (
+                "m",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            ),
+            (
+                "m/0'",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+            ),
+            (
+                "m/0'/1'/2'/2'/1000000000'",
+                "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            ),
+        ];
+        for (path, key) in vectors {
+            let path = DerivationPath::from_str(path).unwrap();
+            assert_eq!(hex::encode(slip10_ed25519(&seed, &path).unwrap()), key);
+        }
+    }
+
+    #[test]
+    fn test_slip10_ed25519_rejects_normal_index() {
+        let seed = hex::decode(SEED).unwrap();
+        let path = DerivationPath::from_str("m/44'/501'/0'/0").unwrap();
+        assert!(matches!(
+            slip10_ed25519(&seed, &path),
+            Err(Error::Slip10NonHardenedError)
+        ));
+    }
+}
@@ -1,2 +1,6 @@
 pub mod address;
+pub mod amount;
 pub mod key;
+pub mod protobuf;
+#[cfg(test)]
+pub mod test_server;
@@ -0,0 +1,107 @@
+//! Minimal protobuf (proto3) encoder for the transactions the wallets sign.
+//!
+//! Fields are written in the order they are added; callers add them by ascending field
+//! number, as canonical encoders do. Zero values are skipped, as proto3 requires.
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LEN: u64 = 2;
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Builder of an encoded protobuf message.
+#[derive(Debug, Clone, Default)]
+pub struct ProtoWriter(Vec<u8>);
+
+impl ProtoWriter {
+    pub fn new() -> Self {
+        ProtoWriter::default()
+    }
+
+    fn key(&mut self, field: u32, wire_type: u64) {
+        put_varint(&mut self.0, ((field as u64) << 3) | wire_type);
+    }
+
+    /// `uint32`, `uint64`, `int64` with non-negative values, `bool` and enum fields.
+    pub fn uint64(mut self, field: u32, value: u64) -> Self {
+        if value != 0 {
+            self.key(field, WIRE_VARINT);
+            put_varint(&mut self.0, value);
+        }
+        self
+    }
+
+    /// `int64` fields, negative values take ten bytes.
+    pub fn int64(self, field: u32, value: i64) -> Self {
+        self.uint64(field, value as u64)
+    }
+
+    pub fn bytes(mut self, field: u32, value: &[u8]) -> Self {
+        if !value.is_empty() {
+            self.key(field, WIRE_LEN);
+            put_varint(&mut self.0, value.len() as u64);
+            self.0.extend_from_slice(value);
+        }
+        self
+    }
+
+    pub fn string(self, field: u32, value: &str) -> Self {
+        self.bytes(field, value.as_bytes())
+    }
+
+    /// Embedded message field. Unlike scalars, an empty message is still written, since
+    /// its presence is meaningful.
+    pub fn message(mut self, field: u32, value: &[u8]) -> Self {
+        self.key(field, WIRE_LEN);
+        put_varint(&mut self.0, value.len() as u64);
+        self.0.extend_from_slice(value);
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// `google.protobuf.Any` wrapping the encoded message `value` of type `type_url`.
+pub fn any(type_url: &str, value: &[u8]) -> Vec<u8> {
+    ProtoWriter::new()
+        .string(1, type_url)
+        .bytes(2, value)
+        .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoding() {
+        // examples of the protobuf encoding guide
+        assert_eq!(
+            ProtoWriter::new().uint64(1, 150).into_bytes(),
+            [0x08, 0x96, 0x01]
+        );
+        assert_eq!(
+            ProtoWriter::new().string(2, "testing").into_bytes(),
+            b"\x12\x07testing"
+        );
+        assert_eq!(
+            ProtoWriter::new()
+                .message(3, &ProtoWriter::new().uint64(1, 150).into_bytes())
+                .into_bytes(),
+            [0x1a, 0x03, 0x08, 0x96, 0x01]
+        );
+        assert!(ProtoWriter::new()
+            .uint64(1, 0)
+            .string(2, "")
+            .into_bytes()
+            .is_empty());
+        assert_eq!(ProtoWriter::new().int64(1, -1).into_bytes().len(), 11);
+    }
+}
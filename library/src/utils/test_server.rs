@@ -0,0 +1,146 @@
+//! Minimal HTTP stand-in for tests of the HTTP API clients.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// A canned response, returned for requests with a matching method and path
+/// whose body contains `body_contains`, if set.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub method: &'static str,
+    pub path: String,
+    pub body_contains: Option<String>,
+    pub status: u16,
+    pub body: String,
+}
+
+impl Route {
+    pub fn get(path: &str, body: &str) -> Self {
+        Route {
+            method: "GET",
+            path: path.to_owned(),
+            body_contains: None,
+            status: 200,
+            body: body.to_owned(),
+        }
+    }
+
+    pub fn post(path: &str, body: &str) -> Self {
+        Route {
+            method: "POST",
+            path: path.to_owned(),
+            body_contains: None,
+            status: 200,
+            body: body.to_owned(),
+        }
+    }
+
+    /// JSON-RPC call of `rpc_method` at `/`, answered with `result`.
+    pub fn rpc(rpc_method: &str, result: &str) -> Self {
+        Route {
+            body_contains: Some(format!(r#""method":"{}""#, rpc_method)),
+            ..Route::post(
+                "/",
+                &format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, result),
+            )
+        }
+    }
+}
+
+/// A request received by the stand-in.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+pub struct TestServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl TestServer {
+    /// Starts serving `routes` on a random local port.
+    pub async fn start(routes: Vec<Route>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let routes = Arc::new(routes);
+
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let routes = routes.clone();
+                let log = log.clone();
+                tokio::spawn(async move { handle(stream, &routes, &log).await });
+            }
+        });
+
+        TestServer { url, requests }
+    }
+
+    /// Requests received so far, in order of arrival.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn handle(mut stream: TcpStream, routes: &[Route], log: &Mutex<Vec<Request>>) {
+    let mut buf = vec![];
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.unwrap_or(0);
+        if n == 0 {
+            return;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_owned();
+    let path = request_line.next().unwrap_or_default().to_owned();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+
+    let (status, response) = match routes.iter().find(|route| {
+        route.method == method
+            && route.path == path
+            && route
+                .body_contains
+                .as_ref()
+                .is_none_or(|needle| body.contains(needle.as_str()))
+    }) {
+        Some(route) => (route.status, route.body.clone()),
+        None => (404, "not found".to_owned()),
+    };
+    log.lock().unwrap().push(Request { method, path, body });
+
+    let reply = format!(
+        "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response.len(),
+        response
+    );
+    let _ = stream.write_all(reply.as_bytes()).await;
+}
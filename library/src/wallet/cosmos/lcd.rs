@@ -0,0 +1,136 @@
+//! Client for the LCD (REST) API of Cosmos SDK nodes.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::error::Error;
+
+#[derive(Debug, Deserialize)]
+struct CoinResponse {
+    amount: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BalanceResponse {
+    balance: Option<CoinResponse>,
+}
+
+/// Number and sequence of an account, both needed to sign its transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+    pub account_number: u64,
+    pub sequence: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TxResponse {
+    txhash: String,
+    code: u32,
+    raw_log: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BroadcastResponse {
+    tx_response: TxResponse,
+}
+
+fn parse_u64(value: &serde_json::Value) -> Option<u64> {
+    value.as_str()?.parse().ok()
+}
+
+pub struct LcdClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl LcdClient {
+    /// Creates a client for the API at `base_url`, e.g. `https://cosmos-rest.publicnode.com`.
+    pub fn new(base_url: &str) -> Self {
+        LcdClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Bank balance of `address` in `denom`.
+    pub async fn balance(&self, address: &str, denom: &str) -> Result<u128, Error> {
+        let response: BalanceResponse = self
+            .client
+            .get(format!(
+                "{}/cosmos/bank/v1beta1/balances/{}/by_denom",
+                self.base_url, address
+            ))
+            .query(&[("denom", denom)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match response.balance {
+            Some(coin) => coin
+                .amount
+                .parse()
+                .map_err(|_| Error::CosmosLcdError(format!("bad amount {}", coin.amount))),
+            None => Ok(0),
+        }
+    }
+
+    /// Account number and sequence of `address`, `None` if it never received funds.
+    pub async fn account(&self, address: &str) -> Result<Option<AccountInfo>, Error> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/cosmos/auth/v1beta1/accounts/{}",
+                self.base_url, address
+            ))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let body: serde_json::Value = response.error_for_status()?.json().await?;
+        let account = &body["account"];
+        // vesting accounts nest their base account
+        let base = if account["base_vesting_account"].is_object() {
+            &account["base_vesting_account"]["base_account"]
+        } else {
+            account
+        };
+        match (
+            parse_u64(&base["account_number"]),
+            parse_u64(&base["sequence"]),
+        ) {
+            (Some(account_number), Some(sequence)) => Ok(Some(AccountInfo {
+                account_number,
+                sequence,
+            })),
+            _ => Err(Error::CosmosLcdError(format!(
+                "unsupported account type of {}",
+                address
+            ))),
+        }
+    }
+
+    /// Broadcasts an encoded `TxRaw` and returns its hash once it passed `CheckTx`.
+    pub async fn broadcast(&self, tx_bytes: &[u8]) -> Result<String, Error> {
+        let response: BroadcastResponse = self
+            .client
+            .post(format!("{}/cosmos/tx/v1beta1/txs", self.base_url))
+            .json(&json!({
+                "tx_bytes": BASE64.encode(tx_bytes),
+                "mode": "BROADCAST_MODE_SYNC",
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let tx = response.tx_response;
+        if tx.code != 0 {
+            return Err(Error::BroadcastError(tx.raw_log));
+        }
+        Ok(tx.txhash)
+    }
+}
@@ -0,0 +1,428 @@
+pub mod lcd;
+pub mod network;
+pub mod tx;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bitcoin::{
+    bech32::{self, Bech32, Hrp},
+    bip32::DerivationPath,
+    hashes::{hash160, Hash},
+};
+use ethers::types::U256;
+use secp256k1::{PublicKey, SecretKey};
+
+use crate::{
+    error::Error,
+    types::{
+        hdseed::{FromSeed, HDSeed},
+        token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    },
+    utils::{amount::amount_as, key::keypair_by_index},
+};
+
+use self::{
+    lcd::LcdClient,
+    network::CosmosNetwork,
+    tx::{msg_send, sign_tx, Coin, SignerData},
+};
+use super::Wallet;
+
+/// Wallet for Cosmos SDK chains, deriving secp256k1 keys under `m/44'/118'/0'/0/i`.
+/// Token addresses are bank denominations, e.g. IBC `ibc/...` denoms; amounts are in
+/// base units of the denomination.
+pub struct CosmosWallet {
+    pub seed: HDSeed,
+    pub network: CosmosNetwork,
+}
+
+impl FromSeed for CosmosWallet {
+    fn from_seed(seed: HDSeed) -> Self {
+        CosmosWallet::new(seed, CosmosNetwork::cosmos_hub())
+    }
+}
+
+impl CosmosWallet {
+    pub fn new(seed: HDSeed, network: CosmosNetwork) -> Self {
+        CosmosWallet { seed, network }
+    }
+
+    pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
+        Ok(DerivationPath::from_str(&format!(
+            "m/44'/118'/0'/0/{}",
+            index
+        ))?)
+    }
+
+    fn keys(&self, index: u32) -> Result<(SecretKey, PublicKey), Error> {
+        let (privk, pubk) = keypair_by_index(&self.seed.mnemonic, &self.get_hd_path(index)?)?;
+        Ok((privk.private_key, pubk.public_key))
+    }
+
+    /// Bech32 account address of `public_key` with the network's prefix.
+    pub fn address_of(&self, public_key: &PublicKey) -> Result<String, Error> {
+        let hrp =
+            Hrp::parse(&self.network.hrp).map_err(|e| Error::CosmosAddressError(e.to_string()))?;
+        let hash = hash160::Hash::hash(&public_key.serialize());
+        bech32::encode::<Bech32>(hrp, hash.as_byte_array())
+            .map_err(|e| Error::CosmosAddressError(e.to_string()))
+    }
+
+    /// Checks that `address` is a valid account address of this network.
+    pub fn validate_address(&self, address: &str) -> Result<(), Error> {
+        let (hrp, data) =
+            bech32::decode(address).map_err(|e| Error::CosmosAddressError(e.to_string()))?;
+        if hrp.to_lowercase() != self.network.hrp || !matches!(data.len(), 20 | 32) {
+            return Err(Error::CosmosAddressError(address.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Signs a bank transfer of `amount` of `denom` from the account of `index`.
+    /// Returns the encoded `TxRaw`.
+    pub fn sign_send(
+        &self,
+        index: u32,
+        to: &str,
+        denom: &str,
+        amount: u128,
+        account_number: u64,
+        sequence: u64,
+    ) -> Result<Vec<u8>, Error> {
+        self.validate_address(to)?;
+        let (secret_key, public_key) = self.keys(index)?;
+        let from = self.address_of(&public_key)?;
+        let signer = SignerData {
+            chain_id: self.network.chain_id.clone(),
+            account_number,
+            sequence,
+            fee: Coin::new(&self.network.denom, self.network.fee() as u128),
+            gas_limit: self.network.gas_limit,
+            memo: String::new(),
+        };
+        let msg = msg_send(&from, to, &[Coin::new(denom, amount)]);
+        Ok(sign_tx(&[msg], &signer, &secret_key))
+    }
+
+    async fn cosmos_balance_by_index(
+        &self,
+        index: u32,
+        denom: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        let client = LcdClient::new(provider);
+        let balance = client.balance(&self.address(index)?, denom).await?;
+        Ok(U256::from(balance))
+    }
+
+    async fn cosmos_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        denom: &str,
+        amount: u128,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = LcdClient::new(provider);
+        let address = self.address(index)?;
+        let account = client
+            .account(&address)
+            .await?
+            .ok_or(Error::CosmosLcdError(format!(
+                "account {} not found",
+                address
+            )))?;
+        let tx = self.sign_send(
+            index,
+            to,
+            denom,
+            amount,
+            account.account_number,
+            account.sequence,
+        )?;
+        let txid = client.broadcast(&tx).await?;
+        Ok(TransferReceipt::Broadcast { txid })
+    }
+
+    async fn cosmos_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let client = LcdClient::new(provider);
+        let denom = self.network.denom.clone();
+        let balance = client.balance(&self.address(index)?, &denom).await?;
+        let fee = self.network.fee() as u128;
+        if balance <= fee {
+            return Err(Error::InsufficientFunds {
+                needed: fee as u64 + 1,
+                available: balance as u64,
+            });
+        }
+        let amount = balance - fee;
+        let receipt = self
+            .cosmos_transfer(index, to, &denom, amount, provider)
+            .await?;
+        Ok((receipt, U256::from(amount)))
+    }
+
+    async fn cosmos_sweep_denom(
+        &self,
+        index: u32,
+        denom: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        let client = LcdClient::new(provider);
+        let balance = client.balance(&self.address(index)?, denom).await?;
+        if balance == 0 {
+            return Err(Error::NothingToSweep);
+        }
+        let receipt = self
+            .cosmos_transfer(index, to, denom, balance, provider)
+            .await?;
+        let token_data = TokenData {
+            balance: U256::from(balance),
+            // bank denominations don't carry their decimals
+            balance_f: balance as f64,
+            decimals: 0,
+            symbol: denom.to_owned(),
+            address: denom.to_owned(),
+        };
+        Ok((receipt, token_data))
+    }
+}
+
+#[async_trait]
+impl Wallet for CosmosWallet {
+    fn address(&self, index: u32) -> Result<String, Error> {
+        let (_, public_key) = self.keys(index)?;
+        self.address_of(&public_key)
+    }
+    fn public(&self, index: u32) -> Result<String, Error> {
+        let (_, public_key) = self.keys(index)?;
+        Ok(hex::encode(public_key.serialize()))
+    }
+    fn private(&self, index: u32) -> Result<String, Error> {
+        let (secret_key, _) = self.keys(index)?;
+        Ok(hex::encode(secret_key.secret_bytes()))
+    }
+    fn keypair(&self, index: u32) -> Result<(String, String), Error> {
+        Ok((self.private(index)?, self.public(index)?))
+    }
+    async fn balance(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        self.cosmos_balance_by_index(index, &self.network.denom, provider)
+            .await
+    }
+    async fn balance_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        self.cosmos_balance_by_index(index, token_address, provider)
+            .await
+    }
+    async fn transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.cosmos_transfer(index, to, &self.network.denom, amount_as(amount)?, provider)
+            .await
+    }
+    async fn transfer_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.cosmos_transfer(index, to, token_address, amount_as(amount)?, provider)
+            .await
+    }
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        self.cosmos_sweep(index, to, provider).await
+    }
+    async fn sweep_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        self.cosmos_sweep_denom(index, token_address, to, provider)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use bip39::{Language, Mnemonic};
+    use bitcoin::hashes::sha256;
+    use secp256k1::{ecdsa::Signature, Message, Secp256k1};
+
+    const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn wallet(network: CosmosNetwork) -> CosmosWallet {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        CosmosWallet::new(HDSeed { mnemonic }, network)
+    }
+
+    #[test]
+    fn test_cosmos_address_by_index() {
+        assert_eq!(
+            wallet(CosmosNetwork::cosmos_hub()).address(0).unwrap(),
+            "cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4"
+        );
+        assert_eq!(
+            wallet(CosmosNetwork::osmosis()).address(0).unwrap(),
+            "osmo19rl4cm2hmr8afy4kldpxz3fka4jguq0a5m7df8"
+        );
+    }
+
+    #[test]
+    fn test_validate_address() {
+        let wallet = wallet(CosmosNetwork::cosmos_hub());
+        assert!(wallet
+            .validate_address("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4")
+            .is_ok());
+        let osmo = CosmosWallet::new(wallet.seed.clone(), CosmosNetwork::osmosis());
+        assert!(osmo
+            .validate_address("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal4")
+            .is_err());
+        assert!(wallet
+            .validate_address("cosmos19rl4cm2hmr8afy4kldpxz3fka4jguq0auqdal5")
+            .is_err());
+    }
+
+    #[test]
+    fn test_sign_msg_send() {
+        let wallet = wallet(CosmosNetwork::cosmos_hub());
+        let to = &wallet.address(1).unwrap();
+        let tx = wallet.sign_send(0, to, "uatom", 1_234_567, 42, 7).unwrap();
+
+        let (secret_key, public_key) = wallet.keys(0).unwrap();
+        let from = wallet.address(0).unwrap();
+        let signer = SignerData {
+            chain_id: "cosmoshub-4".to_owned(),
+            account_number: 42,
+            sequence: 7,
+            fee: Coin::new("uatom", 5000),
+            gas_limit: 200_000,
+            memo: String::new(),
+        };
+        let body = tx::tx_body(&[msg_send(&from, to, &[Coin::new("uatom", 1_234_567)])], "");
+        let auth_info = tx::auth_info(&public_key, &signer);
+        let doc = tx::sign_doc(&body, &auth_info, &signer);
+        let digest = sha256::Hash::hash(&doc).to_byte_array();
+        let signature = Signature::from_compact(&tx[tx.len() - 64..]).unwrap();
+        Secp256k1::new()
+            .verify_ecdsa(&Message::from_digest(digest), &signature, &public_key)
+            .unwrap();
+        assert_eq!(
+            tx,
+            sign_tx(
+                &[msg_send(&from, to, &[Coin::new("uatom", 1_234_567)])],
+                &signer,
+                &secret_key
+            )
+        );
+        assert_eq!(hex::encode(&tx), "0a93010a90010a1c2f636f736d6f732e62616e6b2e763162657461312e4d736753656e6412700a2d636f736d6f733139726c34636d32686d7238616679346b6c6470787a33666b61346a6775713061757164616c34122d636f736d6f73316a726b6d646377677139347561616d78367a6178326c7565776c68663775346b756378336b7a1a100a057561746f6d12073132333435363712670a500a460a1f2f636f736d6f732e63727970746f2e736563703235366b312e5075624b657912230a21024f4e2ad99c34d60b9ba6283c9431a8418af8673212961f97a77b6377fcd05b6212040a020801180712130a0d0a057561746f6d12043530303010c09a0c1a40b3b99711254b22713ef95af72199332eba673382e7848aaf221cfcf84f05c8b80029c5664e71679057b7714234915807c768655808b96923611941d384ef3d86");
+    }
+
+    #[tokio::test]
+    async fn test_sweep_against_lcd_stand_in() {
+        let wallet = wallet(CosmosNetwork::cosmos_hub());
+        let address = wallet.address(0).unwrap();
+        let to = &wallet.address(1).unwrap();
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!(
+                    "/cosmos/bank/v1beta1/balances/{}/by_denom?denom=uatom",
+                    address
+                ),
+                r#"{"balance":{"denom":"uatom","amount":"1000000"}}"#,
+            ),
+            Route::get(
+                &format!("/cosmos/auth/v1beta1/accounts/{}", address),
+                &format!(
+                    r#"{{"account":{{"@type":"/cosmos.auth.v1beta1.BaseAccount","address":"{}","account_number":"42","sequence":"7"}}}}"#,
+                    address
+                ),
+            ),
+            Route::post(
+                "/cosmos/tx/v1beta1/txs",
+                r#"{"tx_response":{"txhash":"ABCD","code":0,"raw_log":""}}"#,
+            ),
+        ])
+        .await;
+
+        let (receipt, amount) = wallet.sweep(0, to, &server.url).await.unwrap();
+        assert_eq!(receipt.txid(), "ABCD");
+        assert_eq!(amount, U256::from(995_000));
+
+        let broadcast = server.requests().pop().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&broadcast.body).unwrap();
+        let tx_bytes = BASE64.decode(body["tx_bytes"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            tx_bytes,
+            wallet.sign_send(0, to, "uatom", 995_000, 42, 7).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sweep_empty_denom() {
+        let wallet = wallet(CosmosNetwork::osmosis());
+        let address = wallet.address(0).unwrap();
+        let server = TestServer::start(vec![Route::get(
+            &format!(
+                "/cosmos/bank/v1beta1/balances/{}/by_denom?denom=uion",
+                address
+            ),
+            r#"{"balance":{"denom":"uion","amount":"0"}}"#,
+        )])
+        .await;
+        assert!(matches!(
+            wallet
+                .sweep_token(0, "uion", &wallet.address(1).unwrap(), &server.url)
+                .await,
+            Err(Error::NothingToSweep)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_broadcast() {
+        let wallet = wallet(CosmosNetwork::cosmos_hub());
+        let address = wallet.address(0).unwrap();
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!("/cosmos/auth/v1beta1/accounts/{}", address),
+                r#"{"account":{"account_number":"1","sequence":"0"}}"#,
+            ),
+            Route::post(
+                "/cosmos/tx/v1beta1/txs",
+                r#"{"tx_response":{"txhash":"ABCD","code":5,"raw_log":"insufficient funds"}}"#,
+            ),
+        ])
+        .await;
+        let result = wallet
+            .transfer(0, &wallet.address(1).unwrap(), U256::from(1), &server.url)
+            .await;
+        assert!(matches!(result, Err(Error::BroadcastError(log)) if log == "insufficient funds"));
+    }
+}
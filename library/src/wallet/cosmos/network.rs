@@ -0,0 +1,56 @@
+//! Parameters of the Cosmos SDK chains served by `CosmosWallet`.
+
+use serde::{Deserialize, Serialize};
+
+/// Address prefix, chain id, fee denomination and gas settings of a Cosmos SDK chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CosmosNetwork {
+    /// Bech32 human readable part of account addresses, e.g. `cosmos`.
+    pub hrp: String,
+    pub chain_id: String,
+    /// Staking denomination, in which balances are reported and fees are paid.
+    pub denom: String,
+    /// Gas price in `denom` per unit of gas.
+    pub gas_price: f64,
+    /// Gas limit of a single `MsgSend` transaction.
+    #[serde(default = "default_gas_limit")]
+    pub gas_limit: u64,
+}
+
+fn default_gas_limit() -> u64 {
+    200_000
+}
+
+impl CosmosNetwork {
+    fn new(hrp: &str, chain_id: &str, denom: &str, gas_price: f64) -> Self {
+        CosmosNetwork {
+            hrp: hrp.to_owned(),
+            chain_id: chain_id.to_owned(),
+            denom: denom.to_owned(),
+            gas_price,
+            gas_limit: default_gas_limit(),
+        }
+    }
+
+    pub fn cosmos_hub() -> Self {
+        CosmosNetwork::new("cosmos", "cosmoshub-4", "uatom", 0.025)
+    }
+
+    /// Cosmos Hub public testnet.
+    pub fn cosmos_hub_testnet() -> Self {
+        CosmosNetwork::new("cosmos", "theta-testnet-001", "uatom", 0.025)
+    }
+
+    pub fn osmosis() -> Self {
+        CosmosNetwork::new("osmo", "osmosis-1", "uosmo", 0.025)
+    }
+
+    pub fn osmosis_testnet() -> Self {
+        CosmosNetwork::new("osmo", "osmo-test-5", "uosmo", 0.025)
+    }
+
+    /// Fee of a transaction using `gas_limit`, in `denom`.
+    pub fn fee(&self) -> u64 {
+        (self.gas_limit as f64 * self.gas_price).ceil() as u64
+    }
+}
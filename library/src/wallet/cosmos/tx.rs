@@ -0,0 +1,115 @@
+//! Protobuf encoding and SIGN_MODE_DIRECT signing of Cosmos SDK bank transfers.
+
+use bitcoin::hashes::{sha256, Hash};
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+use crate::utils::protobuf::{any, ProtoWriter};
+
+const MSG_SEND_TYPE_URL: &str = "/cosmos.bank.v1beta1.MsgSend";
+const SECP256K1_PUBKEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+const SIGN_MODE_DIRECT: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coin {
+    pub denom: String,
+    pub amount: u128,
+}
+
+impl Coin {
+    pub fn new(denom: &str, amount: u128) -> Self {
+        Coin {
+            denom: denom.to_owned(),
+            amount,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        ProtoWriter::new()
+            .string(1, &self.denom)
+            .string(2, &self.amount.to_string())
+            .into_bytes()
+    }
+}
+
+/// `cosmos.bank.v1beta1.MsgSend` wrapped in an `Any`.
+pub fn msg_send(from: &str, to: &str, amount: &[Coin]) -> Vec<u8> {
+    let mut msg = ProtoWriter::new().string(1, from).string(2, to);
+    for coin in amount {
+        msg = msg.message(3, &coin.encode());
+    }
+    any(MSG_SEND_TYPE_URL, &msg.into_bytes())
+}
+
+/// Everything a single-signer transaction needs besides its messages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignerData {
+    pub chain_id: String,
+    pub account_number: u64,
+    pub sequence: u64,
+    pub fee: Coin,
+    pub gas_limit: u64,
+    pub memo: String,
+}
+
+/// Encoded `TxBody` of `messages`, each already wrapped in an `Any`.
+pub fn tx_body(messages: &[Vec<u8>], memo: &str) -> Vec<u8> {
+    let mut body = ProtoWriter::new();
+    for msg in messages {
+        body = body.message(1, msg);
+    }
+    body.string(2, memo).into_bytes()
+}
+
+/// Encoded `AuthInfo` of a single secp256k1 signer using SIGN_MODE_DIRECT.
+pub fn auth_info(public_key: &PublicKey, signer: &SignerData) -> Vec<u8> {
+    let public_key = any(
+        SECP256K1_PUBKEY_TYPE_URL,
+        &ProtoWriter::new()
+            .bytes(1, &public_key.serialize())
+            .into_bytes(),
+    );
+    let single = ProtoWriter::new().uint64(1, SIGN_MODE_DIRECT).into_bytes();
+    let mode_info = ProtoWriter::new().message(1, &single).into_bytes();
+    let signer_info = ProtoWriter::new()
+        .message(1, &public_key)
+        .message(2, &mode_info)
+        .uint64(3, signer.sequence)
+        .into_bytes();
+    let fee = ProtoWriter::new()
+        .message(1, &signer.fee.encode())
+        .uint64(2, signer.gas_limit)
+        .into_bytes();
+    ProtoWriter::new()
+        .message(1, &signer_info)
+        .message(2, &fee)
+        .into_bytes()
+}
+
+/// Encoded `SignDoc`, the bytes a SIGN_MODE_DIRECT signature commits to.
+pub fn sign_doc(body_bytes: &[u8], auth_info_bytes: &[u8], signer: &SignerData) -> Vec<u8> {
+    ProtoWriter::new()
+        .bytes(1, body_bytes)
+        .bytes(2, auth_info_bytes)
+        .string(3, &signer.chain_id)
+        .uint64(4, signer.account_number)
+        .into_bytes()
+}
+
+/// Signs `messages` and returns the encoded `TxRaw`, ready for broadcast.
+pub fn sign_tx(messages: &[Vec<u8>], signer: &SignerData, secret_key: &SecretKey) -> Vec<u8> {
+    let secp = Secp256k1::new();
+    let public_key = PublicKey::from_secret_key(&secp, secret_key);
+    let body_bytes = tx_body(messages, &signer.memo);
+    let auth_info_bytes = auth_info(&public_key, signer);
+    let doc = sign_doc(&body_bytes, &auth_info_bytes, signer);
+    let digest = sha256::Hash::hash(&doc).to_byte_array();
+    // libsecp256k1 produces low-S signatures, as the Cosmos SDK requires
+    let signature = secp
+        .sign_ecdsa(&Message::from_digest(digest), secret_key)
+        .serialize_compact();
+    ProtoWriter::new()
+        .bytes(1, &body_bytes)
+        .bytes(2, &auth_info_bytes)
+        .bytes(3, &signature)
+        .into_bytes()
+}
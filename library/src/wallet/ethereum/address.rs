@@ -1,9 +1,10 @@
-use std::str::FromStr;
-
-use bitcoin::{bip32::Xpub, PublicKey};
+use bitcoin::bip32::Xpub;
 use serde::{Deserialize, Serialize};
 
-use crate::{error::Error, utils::key::keccak_hash};
+use crate::{
+    error::Error,
+    utils::key::{keccak256, pubkey_hash},
+};
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct EthAddr(String);
@@ -25,22 +26,50 @@ impl EthAddr {
         let checksummed_addr = eth_checksum::checksum(&proper_addr);
         Ok(Self(checksummed_addr))
     }
+
+    /// Builds an EIP-55 checksummed address from its 20 raw bytes.
+    pub fn from_bytes(addr: &[u8; 20]) -> Self {
+        let lower = hex::encode(addr);
+        let hash = keccak256(lower.as_bytes());
+        let mut checksummed = String::with_capacity(42);
+        checksummed.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                checksummed.push(c.to_ascii_uppercase());
+            } else {
+                checksummed.push(c);
+            }
+        }
+        Self(checksummed)
+    }
+
     pub fn get(&self) -> &str {
         &self.0
     }
 }
 
+/// Raw 20-byte account id of a secp256k1 public key.
+pub fn pubk_to_addr_bytes(pubk: &secp256k1::PublicKey) -> [u8; 20] {
+    let hash = pubkey_hash(pubk);
+    let mut addr = [0u8; 20];
+    //keep last 20 bytes of the hash
+    addr.copy_from_slice(&hash[12..]);
+    addr
+}
+
 pub fn extended_pubk_to_addr(pubk: &Xpub) -> Result<EthAddr, Error> {
-    //massage into the right format
-    let pubk_str = pubk.public_key.to_string();
-    let pubk_secp = secp256k1::PublicKey::from_str(&pubk_str)?;
-    //format as uncompressed key, remove "04" in the beginning
-    let pubk_uncomp = &PublicKey::new_uncompressed(pubk_secp).to_string()[2..];
-    //decode from hex and pass to keccak for hashing
-    let pubk_bytes = hex::decode(pubk_uncomp)?;
-    let addr = &keccak_hash(&pubk_bytes);
-    //keep last 20 bytes of the result
-    let addr = &addr[(addr.len() - 40)..];
-    //massage into domain unit
-    EthAddr::new(addr)
+    Ok(EthAddr::from_bytes(&pubk_to_addr_bytes(&pubk.public_key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_bytes_matches_checksum() {
+        let raw = "9858effd232b4033e47d90003d41ec34ecaeda94";
+        let bytes: [u8; 20] = hex::decode(raw).unwrap().try_into().unwrap();
+        assert_eq!(EthAddr::from_bytes(&bytes), EthAddr::new(raw).unwrap());
+    }
 }
@@ -0,0 +1,89 @@
+//! ENS helpers shared by the EVM wallets.
+//!
+//! Names are resolved through the registry of the chain the provider points at.
+//! Mainnet uses the canonical ENS deployment; other EVM chains and local devnets
+//! can pass their own registry address.
+
+use ethers::{
+    providers::{Http, Middleware, Provider, ProviderError},
+    types::Address,
+};
+
+use crate::{error::Error, utils::address::address_str_to_h160};
+
+/// Creates an HTTP provider that resolves names through `registry`,
+/// or through the mainnet ENS registry when `registry` is `None`.
+pub fn ens_provider(
+    provider_url: &str,
+    registry: Option<Address>,
+) -> Result<Provider<Http>, Error> {
+    let provider = Provider::<Http>::try_from(provider_url)?;
+    Ok(match registry {
+        Some(registry) => provider.ens(registry),
+        None => provider,
+    })
+}
+
+/// Returns `true` if `dest` looks like an ENS name rather than a hex address.
+pub fn is_ens_name(dest: &str) -> bool {
+    !dest.starts_with("0x") && dest.contains('.')
+}
+
+/// Resolves a transfer destination, which is either a hex address or an ENS name.
+pub async fn resolve_destination(provider: &Provider<Http>, dest: &str) -> Result<Address, Error> {
+    if !is_ens_name(dest) {
+        return address_str_to_h160(dest);
+    }
+    provider.resolve_name(dest).await.map_err(|e| match e {
+        ProviderError::EnsError(_) => Error::EnsNameNotFound(dest.to_owned()),
+        e => Error::EtherProvidersError(e),
+    })
+}
+
+/// Looks up the primary ENS name of `address`.
+///
+/// Returns `None` if the address has no reverse record, or if the record
+/// is not confirmed by the forward resolution of the name.
+pub async fn reverse_name(
+    provider: &Provider<Http>,
+    address: Address,
+) -> Result<Option<String>, Error> {
+    match provider.lookup_address(address).await {
+        Ok(name) => Ok(Some(name)),
+        Err(ProviderError::EnsError(_)) | Err(ProviderError::EnsNotOwned(_)) => Ok(None),
+        Err(e) => Err(Error::EtherProvidersError(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ens_name() {
+        assert!(is_ens_name("treasury.eth"));
+        assert!(is_ens_name("pay.treasury.eth"));
+        assert!(!is_ens_name("0x9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+        assert!(!is_ens_name("9858EfFD232B4033E47d90003D41EC34EcaEda94"));
+    }
+
+    /// Runs against a devnet with a deployed registry, e.g. anvil with the ENS contracts:
+    /// `WEB3_HD_DEVNET_URL`, `WEB3_HD_ENS_REGISTRY`, `WEB3_HD_ENS_NAME` and the
+    /// address the name is expected to resolve to in `WEB3_HD_ENS_ADDRESS`.
+    #[tokio::test]
+    #[ignore]
+    async fn test_resolve_on_devnet() {
+        let url = std::env::var("WEB3_HD_DEVNET_URL").unwrap();
+        let registry =
+            address_str_to_h160(&std::env::var("WEB3_HD_ENS_REGISTRY").unwrap()).unwrap();
+        let name = std::env::var("WEB3_HD_ENS_NAME").unwrap();
+        let expected = address_str_to_h160(&std::env::var("WEB3_HD_ENS_ADDRESS").unwrap()).unwrap();
+
+        let provider = ens_provider(&url, Some(registry)).unwrap();
+        assert_eq!(
+            resolve_destination(&provider, &name).await.unwrap(),
+            expected
+        );
+        assert_eq!(reverse_name(&provider, expected).await.unwrap(), Some(name));
+    }
+}
@@ -0,0 +1,217 @@
+//! Fee estimation for EVM chains, including the L1 data fee of rollups.
+//!
+//! `eth_estimateGas` only covers execution on the chain itself. OP-stack rollups charge
+//! the cost of posting the transaction to L1 separately, and Arbitrum folds it into the
+//! gas limit, so both are asked through their system contracts.
+
+use ethers::{
+    abi::{self, ParamType, Token},
+    providers::{Http, Middleware, Provider},
+    types::{transaction::eip2718::TypedTransaction, Address, TransactionRequest, H160, U256},
+    utils::id,
+};
+
+use crate::{error::Error, types::chain::FeeModel};
+
+/// `GasPriceOracle` predeploy of OP-stack chains, `0x4200…000F`.
+pub const GAS_PRICE_ORACLE: Address = H160([
+    0x42, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0f,
+]);
+
+/// Arbitrum `NodeInterface`, a virtual contract at `0x…00C8` served by the node itself.
+pub const NODE_INTERFACE: Address = H160([
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xc8,
+]);
+
+/// Extra share of the L1 fee kept back, as it follows the L1 base fee until inclusion.
+pub const L1_FEE_MARGIN_PERCENT: u64 = 10;
+
+/// Estimated cost of a transaction, in wei.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub gas_limit: U256,
+    pub gas_price: U256,
+    /// L1 data fee charged on top of `gas_limit × gas_price`. Zero on L1s, and on
+    /// Arbitrum, where the L1 cost is part of the gas limit.
+    pub l1_fee: U256,
+}
+
+impl FeeEstimate {
+    pub fn gas_cost(&self) -> U256 {
+        self.gas_limit * self.gas_price
+    }
+
+    /// Expected total fee.
+    pub fn total(&self) -> U256 {
+        self.gas_cost() + self.l1_fee
+    }
+
+    /// Amount to keep back for the fee when spending a whole balance: the gas cost and
+    /// the L1 fee with a margin of `L1_FEE_MARGIN_PERCENT`.
+    pub fn reserve(&self) -> U256 {
+        self.gas_cost() + self.l1_fee * (100 + L1_FEE_MARGIN_PERCENT) / 100
+    }
+}
+
+/// Estimates the fee of `tx` under `fee_model`, and sets its gas limit and gas price
+/// accordingly. An EIP-1559 transaction gets the gas price as both its fee cap and its
+/// priority fee, so it pays exactly that price, as a legacy one does. `tx` needs its sender, recipient, value and data set,
+/// and on OP-stack chains its nonce and chain id, as the L1 fee depends on its encoding.
+pub async fn estimate_fee(
+    provider: &Provider<Http>,
+    fee_model: FeeModel,
+    tx: &mut TypedTransaction,
+) -> Result<FeeEstimate, Error> {
+    let gas_price = provider.get_gas_price().await?;
+    let (gas_limit, l1_fee) = match fee_model {
+        FeeModel::L1 => (provider.estimate_gas(tx, None).await?, U256::zero()),
+        FeeModel::OpStack => {
+            let gas_limit = provider.estimate_gas(tx, None).await?;
+            tx.set_gas(gas_limit);
+            tx.set_gas_price(gas_price);
+            (gas_limit, op_l1_fee(provider, tx).await?)
+        }
+        FeeModel::Arbitrum => (arbitrum_gas_estimate(provider, tx).await?, U256::zero()),
+    };
+    tx.set_gas(gas_limit);
+    tx.set_gas_price(gas_price);
+    Ok(FeeEstimate {
+        gas_limit,
+        gas_price,
+        l1_fee,
+    })
+}
+
+/// `GasPriceOracle.getL1Fee` of the unsigned encoding of `tx`. The oracle accounts
+/// for the signature itself.
+async fn op_l1_fee(provider: &Provider<Http>, tx: &TypedTransaction) -> Result<U256, Error> {
+    let mut data = id("getL1Fee(bytes)").to_vec();
+    data.extend(abi::encode(&[Token::Bytes(tx.rlp().to_vec())]));
+    let call: TypedTransaction = TransactionRequest::new()
+        .to(GAS_PRICE_ORACLE)
+        .data(data)
+        .into();
+    let output = provider.call(&call, None).await?;
+    let decoded = abi::decode(&[ParamType::Uint(256)], &output)?;
+    Ok(decoded[0].clone().into_uint().unwrap_or_default())
+}
+
+/// Total gas of `tx`, L1 component included, from
+/// `NodeInterface.gasEstimateComponents(to, contractCreation, data)`.
+async fn arbitrum_gas_estimate(
+    provider: &Provider<Http>,
+    tx: &TypedTransaction,
+) -> Result<U256, Error> {
+    let to = tx.to_addr().copied();
+    let mut data = id("gasEstimateComponents(address,bool,bytes)").to_vec();
+    data.extend(abi::encode(&[
+        Token::Address(to.unwrap_or_default()),
+        Token::Bool(to.is_none()),
+        Token::Bytes(tx.data().map(|data| data.to_vec()).unwrap_or_default()),
+    ]));
+    let mut call = TransactionRequest::new().to(NODE_INTERFACE).data(data);
+    if let Some(from) = tx.from() {
+        call = call.from(*from);
+    }
+    if let Some(value) = tx.value() {
+        call = call.value(*value);
+    }
+    let output = provider.call(&call.into(), None).await?;
+    // (gasEstimate, gasEstimateForL1, baseFee, l1BaseFeeEstimate)
+    let decoded = abi::decode(
+        &[
+            ParamType::Uint(64),
+            ParamType::Uint(64),
+            ParamType::Uint(256),
+            ParamType::Uint(256),
+        ],
+        &output,
+    )?;
+    Ok(decoded[0].clone().into_uint().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+
+    fn word(value: u64) -> String {
+        format!("{:064x}", value)
+    }
+
+    fn transfer() -> TypedTransaction {
+        TransactionRequest::new()
+            .from(Address::repeat_byte(0x11))
+            .to(Address::repeat_byte(0x22))
+            .value(1_000_000_000_000_000u64)
+            .nonce(7)
+            .chain_id(10)
+            .into()
+    }
+
+    #[test]
+    fn test_system_addresses() {
+        assert_eq!(
+            format!("{:?}", GAS_PRICE_ORACLE),
+            "0x420000000000000000000000000000000000000f"
+        );
+        assert_eq!(
+            format!("{:?}", NODE_INTERFACE),
+            "0x00000000000000000000000000000000000000c8"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_op_stack_adds_l1_fee() {
+        let server = TestServer::start(vec![
+            Route::rpc("eth_gasPrice", r#""0x3b9aca00""#),
+            Route::rpc("eth_estimateGas", r#""0x5208""#),
+            Route::rpc("eth_call", &format!(r#""0x{}""#, word(50_000_000_000_000))),
+        ])
+        .await;
+        let provider = Provider::<Http>::try_from(server.url.as_str()).unwrap();
+        let mut tx = transfer();
+        let fee = estimate_fee(&provider, FeeModel::OpStack, &mut tx)
+            .await
+            .unwrap();
+        assert_eq!(fee.gas_cost(), U256::from(21_000_000_000_000u64));
+        assert_eq!(fee.l1_fee, U256::from(50_000_000_000_000u64));
+        assert_eq!(fee.total(), U256::from(71_000_000_000_000u64));
+        assert_eq!(fee.reserve(), U256::from(76_000_000_000_000u64));
+        assert_eq!(tx.gas(), Some(&U256::from(21_000)));
+
+        // the oracle is asked about the unsigned transaction, gas included
+        let call = server
+            .requests()
+            .into_iter()
+            .find(|r| r.body.contains("eth_call"))
+            .unwrap();
+        assert!(call
+            .body
+            .contains("0x420000000000000000000000000000000000000f"));
+        assert!(call.body.contains(&hex::encode(tx.rlp())));
+    }
+
+    #[tokio::test]
+    async fn test_arbitrum_gas_includes_l1() {
+        let output = [word(300_000), word(279_000), word(10_000_000), word(0)].concat();
+        let server = TestServer::start(vec![
+            Route::rpc("eth_gasPrice", r#""0x989680""#),
+            Route::rpc("eth_call", &format!(r#""0x{}""#, output)),
+        ])
+        .await;
+        let provider = Provider::<Http>::try_from(server.url.as_str()).unwrap();
+        let fee = estimate_fee(&provider, FeeModel::Arbitrum, &mut transfer())
+            .await
+            .unwrap();
+        assert_eq!(fee.gas_limit, U256::from(300_000));
+        assert_eq!(fee.l1_fee, U256::zero());
+        assert_eq!(fee.total(), U256::from(3_000_000_000_000u64));
+
+        let requests = server.requests();
+        assert!(requests.iter().all(|r| !r.body.contains("eth_estimateGas")));
+        assert!(requests.iter().any(|r| r
+            .body
+            .contains("0x00000000000000000000000000000000000000c8")));
+    }
+}
@@ -1,42 +1,76 @@
 pub mod address;
+pub mod ens;
+pub mod fees;
+pub mod zksync;
 use async_trait::async_trait;
 use ethers::{
     abi::Abi,
     prelude::*,
     providers::{Http, Middleware, Provider},
-    signers::Wallet,
-    types::{Transaction, U256},
+    types::{transaction::eip2718::TypedTransaction, U256},
 };
 use secp256k1::SecretKey;
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 
 use crate::{
     error::Error,
     types::{
-        crypto::Crypto,
+        chain::ChainConfig,
         hdseed::{FromSeed, HDSeed},
         token_data::TokenData,
+        transfer_receipt::TransferReceipt,
     },
     utils::{address::address_str_to_h160, key::keypair_by_index},
 };
 
-use self::address::extended_pubk_to_addr;
+use self::{
+    address::extended_pubk_to_addr,
+    ens::{ens_provider, resolve_destination, reverse_name},
+    fees::estimate_fee,
+    zksync::Eip712Transaction,
+};
 
 use super::Wallet as HDWallet;
 
 pub struct EthereumWallet {
     pub seed: HDSeed,
+    /// Chain the wallet derives keys for and sends transactions on.
+    pub chain: ChainConfig,
+    /// ENS registry used to resolve names, `None` for the mainnet ENS deployment.
+    pub ens_registry: Option<Address>,
 }
 
 impl FromSeed for EthereumWallet {
     fn from_seed(seed: HDSeed) -> Self {
-        EthereumWallet { seed }
+        EthereumWallet {
+            seed,
+            chain: ChainConfig::ethereum(),
+            ens_registry: None,
+        }
     }
 }
 
 impl EthereumWallet {
+    /// Creates a wallet for any EVM chain described by `chain`.
+    pub fn for_chain(seed: HDSeed, chain: ChainConfig) -> Result<Self, Error> {
+        let ens_registry = match &chain.ens_registry {
+            Some(registry) => Some(address_str_to_h160(registry)?),
+            None => None,
+        };
+        Ok(EthereumWallet {
+            seed,
+            chain,
+            ens_registry,
+        })
+    }
+
+    pub fn with_ens_registry(mut self, registry: Address) -> Self {
+        self.ens_registry = Some(registry);
+        self
+    }
+
     fn eth_address_by_index(&self, index: u32) -> Result<String, Error> {
-        let derivation_path = Crypto::Eth.get_hd_path(index)?;
+        let derivation_path = self.chain.get_hd_path(index)?;
         let (_, pubk) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
         let eth_addr = extended_pubk_to_addr(&pubk)?;
 
@@ -44,28 +78,28 @@ impl EthereumWallet {
     }
 
     fn eth_pubkey_by_index(&self, index: u32) -> Result<String, Error> {
-        let derivation_path = Crypto::Eth.get_hd_path(index)?;
+        let derivation_path = self.chain.get_hd_path(index)?;
         let (_, pubk) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
 
         Ok(pubk.to_string())
     }
 
     fn eth_privkey_by_index(&self, index: u32) -> Result<String, Error> {
-        let derivation_path = Crypto::Eth.get_hd_path(index)?;
+        let derivation_path = self.chain.get_hd_path(index)?;
         let (privk, _) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
 
         Ok(privk.private_key.display_secret().to_string())
     }
 
     fn eth_privkey_sekp_by_index(&self, index: u32) -> Result<SecretKey, Error> {
-        let derivation_path = Crypto::Eth.get_hd_path(index)?;
+        let derivation_path = self.chain.get_hd_path(index)?;
         let (privk, _) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
 
         Ok(privk.private_key)
     }
 
     fn eth_keypair_by_index(&self, index: u32) -> Result<(String, String), Error> {
-        let derivation_path = Crypto::Eth.get_hd_path(index)?;
+        let derivation_path = self.chain.get_hd_path(index)?;
         let (privk, pubk) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
 
         Ok((
@@ -113,6 +147,35 @@ impl EthereumWallet {
         Ok(balance)
     }
 
+    /// Creates a signing client for the key at `index`.
+    ///
+    /// Transactions are signed with the chain id of `self.chain`. The provider has to
+    /// report the same chain id, so a misconfigured provider URL can't make the wallet
+    /// sign a transaction that is valid on another chain.
+    async fn signer_client(
+        &self,
+        index: u32,
+        provider: Provider<Http>,
+    ) -> Result<SignerMiddleware<Provider<Http>, LocalWallet>, Error> {
+        let provider_chain_id = provider.get_chainid().await?;
+        check_chain_id(self.chain.chain_id, provider_chain_id)?;
+
+        let priv_key = self.eth_privkey_sekp_by_index(index)?;
+        let wallet =
+            LocalWallet::from_bytes(&priv_key.secret_bytes())?.with_chain_id(self.chain.chain_id);
+        Ok(SignerMiddleware::new(provider, wallet))
+    }
+
+    /// Empty transaction of the type the chain takes: EIP-1559 where `ChainConfig::eip1559`
+    /// is set, legacy otherwise.
+    fn new_transaction(&self) -> TypedTransaction {
+        if self.chain.eip1559 {
+            Eip1559TransactionRequest::new().into()
+        } else {
+            TransactionRequest::new().into()
+        }
+    }
+
     async fn eth_transfer(
         &self,
         index: u32,
@@ -120,27 +183,204 @@ impl EthereumWallet {
         amount: U256,
         provider: &str,
     ) -> Result<Option<TransactionReceipt>, Error> {
-        // Retrieve the private key for the specified wallet index.
-        let priv_key_str = self.eth_privkey_by_index(index)?;
-
-        // Parse the destination address.
-        let to_address = to.parse::<Address>()?;
-
         // Create the provider.
-        let provider =
-            Provider::<Http>::try_from(provider)?.interval(std::time::Duration::from_millis(2000));
+        let provider = ens_provider(provider, self.ens_registry)?
+            .interval(std::time::Duration::from_millis(2000));
+
+        // Parse the destination address or resolve it through ENS.
+        let to_address = resolve_destination(&provider, to).await?;
 
-        // Create a wallet from the private key and attach it to the provider.
-        let wallet = Wallet::from_str(&priv_key_str)?.with_chain_id(Chain::Sepolia);
-        println!("wallet {:?}", wallet);
-        let client = SignerMiddleware::new(provider.clone(), wallet.clone());
+        // Create a wallet for the chain and attach it to the provider.
+        let client = self.signer_client(index, provider).await?;
 
-        let tx = TransactionRequest::new().to(to_address).value(amount);
+        let mut tx = self.new_transaction();
+        tx.set_to(to_address);
+        tx.set_value(amount);
 
         let tx = client.send_transaction(tx, None).await?.await?;
         // Return the details of the confirmed transaction.
         Ok(tx)
     }
+
+    async fn eth_transfer_token(
+        &self,
+        index: u32,
+        token_addr: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<Option<TransactionReceipt>, Error> {
+        let provider = ens_provider(provider, self.ens_registry)?
+            .interval(std::time::Duration::from_millis(2000));
+
+        let to_address = resolve_destination(&provider, to).await?;
+        let token_addr_h160 = address_str_to_h160(token_addr)?;
+
+        let client = Arc::new(self.signer_client(index, provider).await?);
+
+        let contract_abi = include_str!("../../../res/erc20.abi.json");
+        let contract_abi = serde_json::from_str::<Abi>(contract_abi)?;
+        let erc20_contract = Contract::new(token_addr_h160, contract_abi, client);
+
+        let mut call = erc20_contract.method::<_, bool>("transfer", (to_address, amount))?;
+        if !self.chain.eip1559 {
+            call = call.legacy();
+        }
+        let pending = call.send().await.map_err(Error::from)?;
+        let receipt = pending.await?;
+
+        Ok(receipt)
+    }
+
+    /// Sends the whole balance less the fee. The transaction pays exactly the estimated
+    /// gas price, so the fee charged is the estimated gas cost, plus the L1 data fee on
+    /// OP-stack chains.
+    async fn eth_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(Option<TransactionReceipt>, U256), Error> {
+        let provider = ens_provider(provider, self.ens_registry)?
+            .interval(std::time::Duration::from_millis(2000));
+        let to_address = resolve_destination(&provider, to).await?;
+        let client = self.signer_client(index, provider).await?;
+        let from = client.address();
+
+        let balance = client.inner().get_balance(from, None).await?;
+        let nonce = client.inner().get_transaction_count(from, None).await?;
+        let mut tx = self.new_transaction();
+        tx.set_from(from);
+        tx.set_to(to_address);
+        tx.set_value(balance);
+        tx.set_nonce(nonce);
+        tx.set_chain_id(self.chain.chain_id);
+        let fee = estimate_fee(client.inner(), self.chain.fee_model, &mut tx).await?;
+        if balance <= fee.reserve() {
+            return Err(Error::InsufficientFundsForFee {
+                fee: fee.reserve(),
+                available: balance,
+            });
+        }
+        let amount = balance - fee.reserve();
+        tx.set_value(amount);
+
+        let receipt = client.send_transaction(tx, None).await?.await?;
+        Ok((receipt, amount))
+    }
+
+    /// Sends the whole token balance, once the native balance is known to cover the fee.
+    async fn eth_sweep_token(
+        &self,
+        index: u32,
+        token_addr: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(Option<TransactionReceipt>, TokenData), Error> {
+        let provider = ens_provider(provider, self.ens_registry)?
+            .interval(std::time::Duration::from_millis(2000));
+        let to_address = resolve_destination(&provider, to).await?;
+        let token_addr_h160 = address_str_to_h160(token_addr)?;
+        let client = Arc::new(self.signer_client(index, provider).await?);
+        let from = client.address();
+
+        let contract_abi = include_str!("../../../res/erc20.abi.json");
+        let contract_abi = serde_json::from_str::<Abi>(contract_abi)?;
+        let erc20_contract = Contract::new(token_addr_h160, contract_abi, client.clone());
+        let balance: U256 = erc20_contract
+            .method::<_, U256>("balanceOf", from)?
+            .call()
+            .await?;
+        if balance.is_zero() {
+            return Err(Error::NothingToSweep);
+        }
+        let decimals: u8 = erc20_contract.method("decimals", ())?.call().await?;
+        let symbol: String = erc20_contract.method("symbol", ())?.call().await?;
+
+        let mut call = erc20_contract.method::<_, bool>("transfer", (to_address, balance))?;
+        if !self.chain.eip1559 {
+            call = call.legacy();
+        }
+        let mut tx = call.tx;
+        tx.set_from(from);
+        tx.set_nonce(client.inner().get_transaction_count(from, None).await?);
+        tx.set_chain_id(self.chain.chain_id);
+        let fee = estimate_fee(client.inner(), self.chain.fee_model, &mut tx).await?;
+        let native_balance = client.inner().get_balance(from, None).await?;
+        if native_balance < fee.reserve() {
+            return Err(Error::InsufficientFundsForFee {
+                fee: fee.reserve(),
+                available: native_balance,
+            });
+        }
+
+        let receipt = client.send_transaction(tx, None).await?.await?;
+        let token_data = TokenData {
+            balance,
+            balance_f: ethers::utils::format_units(balance, decimals as u32)
+                .map_or(0.0, |units| units.parse().unwrap_or_default()),
+            decimals,
+            symbol,
+            address: token_addr.to_owned(),
+        };
+        Ok((receipt, token_data))
+    }
+
+    /// Signs the zkSync Era transaction `tx` with the key at `index`, which must be the
+    /// key of `tx.from`. Returns the encoding for `eth_sendRawTransaction`.
+    pub fn sign_zksync(&self, index: u32, tx: &Eip712Transaction) -> Result<Bytes, Error> {
+        check_chain_id(self.chain.chain_id, tx.chain_id.into())?;
+        let priv_key = self.eth_privkey_sekp_by_index(index)?;
+        let wallet = LocalWallet::from_bytes(&priv_key.secret_bytes())?;
+        if wallet.address() != tx.from {
+            return Err(Error::SenderMismatch(format!("{:?}", tx.from)));
+        }
+        let signature = wallet.sign_hash(tx.signing_hash())?;
+        Ok(tx.encode_signed(&signature))
+    }
+
+    /// Signs and sends the zkSync Era transaction `tx`, then waits for its receipt.
+    pub async fn send_zksync(
+        &self,
+        index: u32,
+        tx: &Eip712Transaction,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let provider =
+            Provider::<Http>::try_from(provider)?.interval(std::time::Duration::from_millis(2000));
+        check_chain_id(self.chain.chain_id, provider.get_chainid().await?)?;
+        let raw = self.sign_zksync(index, tx)?;
+        let receipt = provider.send_raw_transaction(raw).await?.await?;
+        match receipt {
+            Some(receipt) => Ok(TransferReceipt::Evm(Box::new(receipt))),
+            None => Err(Error::EthNoneTransferTransactionReceiptError),
+        }
+    }
+
+    async fn eth_reverse_name_by_index(
+        &self,
+        index: u32,
+        provider_url: &str,
+    ) -> Result<Option<String>, Error> {
+        if self.ens_registry.is_none() && !self.chain.has_ens() {
+            return Ok(None);
+        }
+        let addr = self.eth_address_by_index(index)?;
+        let addr_h160 = address_str_to_h160(&addr)?;
+        let provider = ens_provider(provider_url, self.ens_registry)?;
+        reverse_name(&provider, addr_h160).await
+    }
+}
+
+/// Fails with `Error::ChainIdMismatch` unless the provider reports the expected chain id.
+fn check_chain_id(expected: u64, provider_chain_id: U256) -> Result<(), Error> {
+    if provider_chain_id != U256::from(expected) {
+        return Err(Error::ChainIdMismatch {
+            expected,
+            actual: provider_chain_id,
+        });
+    }
+    Ok(())
 }
 
 #[async_trait]
@@ -175,9 +415,9 @@ impl HDWallet for EthereumWallet {
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error> {
+    ) -> Result<TransferReceipt, Error> {
         match self.eth_transfer(index, to, amount, provider).await? {
-            Some(receipt) => Ok(receipt),
+            Some(receipt) => Ok(TransferReceipt::Evm(Box::new(receipt))),
             None => Err(Error::EthNoneTransferTransactionReceiptError),
         }
     }
@@ -189,27 +429,54 @@ impl HDWallet for EthereumWallet {
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error> {
-        Err(Error::TronAddrLengthError)
+    ) -> Result<TransferReceipt, Error> {
+        match self
+            .eth_transfer_token(index, token_address, to, amount, provider)
+            .await?
+        {
+            Some(receipt) => Ok(TransferReceipt::Evm(Box::new(receipt))),
+            None => Err(Error::EthNoneTransferTransactionReceiptError),
+        }
     }
 
-    fn sweep(&self, _index: u32, _to: &str, _provider: &str) -> Result<(Transaction, U256), Error> {
-        unimplemented!()
+    async fn reverse_name(&self, index: u32, provider: &str) -> Result<Option<String>, Error> {
+        self.eth_reverse_name_by_index(index, provider).await
     }
-    fn sweep_token(
+
+    async fn sweep(
         &self,
-        _index: u32,
-        _token_address: &str,
-        _to: &str,
-        _provider: &str,
-    ) -> Result<(Transaction, TokenData), Error> {
-        unimplemented!()
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        match self.eth_sweep(index, to, provider).await? {
+            (Some(receipt), amount) => Ok((TransferReceipt::Evm(Box::new(receipt)), amount)),
+            (None, _) => Err(Error::EthNoneTransferTransactionReceiptError),
+        }
+    }
+    async fn sweep_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        match self
+            .eth_sweep_token(index, token_address, to, provider)
+            .await?
+        {
+            (Some(receipt), token_data) => {
+                Ok((TransferReceipt::Evm(Box::new(receipt)), token_data))
+            }
+            (None, _) => Err(Error::EthNoneTransferTransactionReceiptError),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_server::{Route, TestServer};
     use bip39::{Language, Mnemonic};
 
     const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
@@ -219,7 +486,7 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = EthereumWallet { seed };
+        let wallet = EthereumWallet::from_seed(seed);
 
         let expected_address_0 = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
         assert_eq!(wallet.address(0).unwrap(), expected_address_0);
@@ -230,7 +497,7 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = EthereumWallet { seed };
+        let wallet = EthereumWallet::from_seed(seed);
 
         let expected_pubkey = "xpub6H6LG2We64bdwqNF7gNkUJ5EvDibiT2gbs77oonbawV86XE3eMxZf9czGQ9CPdSzsdsHLnLEjiJJEDnFMAyLrWATesaVbTYeggBXMHaFKLg";
         assert_eq!(wallet.public(0).unwrap(), expected_pubkey);
@@ -241,9 +508,116 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = EthereumWallet { seed };
+        let wallet = EthereumWallet::from_seed(seed);
 
         let expected_privkey = "1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727";
         assert_eq!(wallet.private(0).unwrap(), expected_privkey);
     }
+
+    #[test]
+    fn test_check_chain_id() {
+        assert!(check_chain_id(56, U256::from(56)).is_ok());
+        assert!(matches!(
+            check_chain_id(1, U256::from(11155111)),
+            Err(Error::ChainIdMismatch { expected: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_sign_zksync_transfer() {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let seed = HDSeed { mnemonic };
+        let wallet = EthereumWallet::for_chain(seed, ChainConfig::zksync()).unwrap();
+        let from = address_str_to_h160(&wallet.address(0).unwrap()).unwrap();
+        let to = address_str_to_h160(&wallet.address(1).unwrap()).unwrap();
+
+        let tx = Eip712Transaction::transfer(324, from, to, U256::exp10(15))
+            .nonce(5)
+            .gas(300_000, 45_250_000, 0);
+        let raw = wallet.sign_zksync(0, &tx).unwrap();
+        // the signature is the customSignature field, 65 bytes before the empty paymaster list
+        let signature = Signature::try_from(&raw[raw.len() - 66..raw.len() - 1]).unwrap();
+        assert_eq!(signature.recover(tx.signing_hash()).unwrap(), from);
+        assert_eq!(hex::encode(&raw), "71f88e05808402b275d0830493e0946fac4d18c912343bf86fa7049364dd4e424ab9c087038d7ea4c68000808201448080820144949858effd232b4033e47d90003d41ec34ecaeda9482c350c0b841849e53176bdc2e5b78ea41885a79704fd9cc206ece511551380d7fb86b3be11415060f8caac998f8f079781fd90345c5170767d436f110c746ddb53c3be7ec4d1cc0");
+
+        assert!(matches!(
+            wallet.sign_zksync(1, &tx),
+            Err(Error::SenderMismatch(_))
+        ));
+        let sepolia = Eip712Transaction {
+            chain_id: 300,
+            ..tx
+        };
+        assert!(matches!(
+            wallet.sign_zksync(0, &sepolia),
+            Err(Error::ChainIdMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_transaction_type() {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let seed = HDSeed { mnemonic };
+
+        let eth = EthereumWallet::for_chain(seed.clone(), ChainConfig::ethereum()).unwrap();
+        let mut tx = eth.new_transaction();
+        assert!(matches!(tx, TypedTransaction::Eip1559(_)));
+        // a sweep pays the estimated gas price, whatever the base fee
+        tx.set_gas_price(U256::from(30_000_000_000u64));
+        let TypedTransaction::Eip1559(request) = &tx else {
+            unreachable!()
+        };
+        assert_eq!(request.max_fee_per_gas, request.max_priority_fee_per_gas);
+
+        let bsc = EthereumWallet::for_chain(seed, ChainConfig::bsc()).unwrap();
+        assert!(matches!(bsc.new_transaction(), TypedTransaction::Legacy(_)));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_empty_token() {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let wallet = EthereumWallet::from_seed(HDSeed { mnemonic });
+        let server = TestServer::start(vec![
+            Route::rpc("eth_chainId", r#""0x1""#),
+            Route::rpc("eth_call", &format!(r#""0x{:064x}""#, 0)),
+        ])
+        .await;
+        assert!(matches!(
+            wallet
+                .sweep_token(
+                    0,
+                    "0xdAC17F958D2ee523a2206206994597C13D831ec7",
+                    &wallet.address(1).unwrap(),
+                    &server.url
+                )
+                .await,
+            Err(Error::NothingToSweep)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_no_reverse_name_without_ens() {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let wallet = EthereumWallet::for_chain(HDSeed { mnemonic }, ChainConfig::bsc()).unwrap();
+        let server = TestServer::start(vec![]).await;
+        assert_eq!(wallet.reverse_name(0, &server.url).await.unwrap(), None);
+        assert!(server.requests().is_empty());
+    }
+
+    #[test]
+    fn test_eth_address_for_chain() {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        let seed = HDSeed { mnemonic };
+
+        let bsc = EthereumWallet::for_chain(seed.clone(), ChainConfig::bsc()).unwrap();
+        assert_eq!(
+            bsc.address(0).unwrap(),
+            "0x9858EfFD232B4033E47d90003D41EC34EcaEda94"
+        );
+
+        let mut avalanche = ChainConfig::new("avalanche", 43114, "AVAX");
+        avalanche.coin_type = 9000;
+        let avalanche = EthereumWallet::for_chain(seed, avalanche).unwrap();
+        assert_ne!(avalanche.address(0).unwrap(), bsc.address(0).unwrap());
+    }
 }
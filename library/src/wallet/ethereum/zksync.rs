@@ -0,0 +1,347 @@
+//! zkSync Era native transactions, EIP-712 typed and of transaction type `0x71`.
+//!
+//! They are required for paymasters and for a custom gas per pubdata limit. The sender
+//! signs the EIP-712 hash of the transaction and the signature travels in the
+//! `customSignature` field of the encoding. Factory dependencies (contract deployments)
+//! are not supported.
+
+use ethers::{
+    abi::{self, Token},
+    types::{Address, Bytes, Signature, H256, U256},
+    utils::{id, keccak256, rlp::RlpStream},
+};
+
+/// Transaction type of zkSync EIP-712 transactions.
+pub const EIP712_TX_TYPE: u8 = 0x71;
+
+/// Gas per pubdata byte limit the zkSync SDKs use by default.
+pub const DEFAULT_GAS_PER_PUBDATA_LIMIT: u64 = 50_000;
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+const TRANSACTION_TYPE: &str = "Transaction(uint256 txType,uint256 from,uint256 to,uint256 gasLimit,uint256 gasPerPubdataByteLimit,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,uint256 paymaster,uint256 nonce,uint256 value,bytes data,bytes32[] factoryDeps,bytes paymasterInput)";
+
+/// Paymaster paying the fee of a transaction, and the input it is called with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymasterParams {
+    pub paymaster: Address,
+    pub paymaster_input: Bytes,
+}
+
+impl PaymasterParams {
+    /// Flow of paymasters that need no tokens from the sender, e.g. sponsoring ones.
+    pub fn general(paymaster: Address, inner_input: &[u8]) -> Self {
+        let mut input = id("general(bytes)").to_vec();
+        input.extend(abi::encode(&[Token::Bytes(inner_input.to_vec())]));
+        PaymasterParams {
+            paymaster,
+            paymaster_input: input.into(),
+        }
+    }
+
+    /// Flow of paymasters taking the fee in `token`, which the sender allows them to
+    /// spend up to `min_allowance` of.
+    pub fn approval_based(
+        paymaster: Address,
+        token: Address,
+        min_allowance: U256,
+        inner_input: &[u8],
+    ) -> Self {
+        let mut input = id("approvalBased(address,uint256,bytes)").to_vec();
+        input.extend(abi::encode(&[
+            Token::Address(token),
+            Token::Uint(min_allowance),
+            Token::Bytes(inner_input.to_vec()),
+        ]));
+        PaymasterParams {
+            paymaster,
+            paymaster_input: input.into(),
+        }
+    }
+}
+
+/// A zkSync Era EIP-712 transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub gas_limit: U256,
+    pub gas_per_pubdata: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_params: Option<PaymasterParams>,
+}
+
+impl Eip712Transaction {
+    /// Transfer of `value` wei of the native currency.
+    pub fn transfer(chain_id: u64, from: Address, to: Address, value: U256) -> Self {
+        Eip712Transaction {
+            chain_id,
+            nonce: U256::zero(),
+            from,
+            to,
+            value,
+            data: Bytes::new(),
+            gas_limit: U256::zero(),
+            gas_per_pubdata: U256::from(DEFAULT_GAS_PER_PUBDATA_LIMIT),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            paymaster_params: None,
+        }
+    }
+
+    /// `transfer(to, amount)` call of the ERC-20 contract `token`.
+    pub fn erc20_transfer(
+        chain_id: u64,
+        from: Address,
+        token: Address,
+        to: Address,
+        amount: U256,
+    ) -> Self {
+        let mut data = id("transfer(address,uint256)").to_vec();
+        data.extend(abi::encode(&[Token::Address(to), Token::Uint(amount)]));
+        Eip712Transaction {
+            data: data.into(),
+            ..Eip712Transaction::transfer(chain_id, from, token, U256::zero())
+        }
+    }
+
+    pub fn nonce(mut self, nonce: impl Into<U256>) -> Self {
+        self.nonce = nonce.into();
+        self
+    }
+
+    pub fn gas(
+        mut self,
+        gas_limit: impl Into<U256>,
+        max_fee_per_gas: impl Into<U256>,
+        max_priority_fee_per_gas: impl Into<U256>,
+    ) -> Self {
+        self.gas_limit = gas_limit.into();
+        self.max_fee_per_gas = max_fee_per_gas.into();
+        self.max_priority_fee_per_gas = max_priority_fee_per_gas.into();
+        self
+    }
+
+    pub fn gas_per_pubdata(mut self, gas_per_pubdata: impl Into<U256>) -> Self {
+        self.gas_per_pubdata = gas_per_pubdata.into();
+        self
+    }
+
+    pub fn paymaster(mut self, params: PaymasterParams) -> Self {
+        self.paymaster_params = Some(params);
+        self
+    }
+
+    fn domain_separator(&self) -> [u8; 32] {
+        keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+            Token::FixedBytes(keccak256("zkSync").to_vec()),
+            Token::FixedBytes(keccak256("2").to_vec()),
+            Token::Uint(self.chain_id.into()),
+        ]))
+    }
+
+    fn struct_hash(&self) -> [u8; 32] {
+        let address = |a: Address| Token::Uint(U256::from_big_endian(a.as_bytes()));
+        let (paymaster, paymaster_input) = match &self.paymaster_params {
+            Some(params) => (params.paymaster, params.paymaster_input.to_vec()),
+            None => (Address::zero(), vec![]),
+        };
+        keccak256(abi::encode(&[
+            Token::FixedBytes(keccak256(TRANSACTION_TYPE).to_vec()),
+            Token::Uint(EIP712_TX_TYPE.into()),
+            address(self.from),
+            address(self.to),
+            Token::Uint(self.gas_limit),
+            Token::Uint(self.gas_per_pubdata),
+            Token::Uint(self.max_fee_per_gas),
+            Token::Uint(self.max_priority_fee_per_gas),
+            address(paymaster),
+            Token::Uint(self.nonce),
+            Token::Uint(self.value),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+            // no factory dependencies, the hash of an empty bytes32[]
+            Token::FixedBytes(keccak256([]).to_vec()),
+            Token::FixedBytes(keccak256(paymaster_input).to_vec()),
+        ]))
+    }
+
+    /// EIP-712 hash the sender signs.
+    pub fn signing_hash(&self) -> H256 {
+        let mut data = vec![0x19, 0x01];
+        data.extend(self.domain_separator());
+        data.extend(self.struct_hash());
+        keccak256(data).into()
+    }
+
+    /// Signed transaction, ready for `eth_sendRawTransaction`.
+    pub fn encode_signed(&self, signature: &Signature) -> Bytes {
+        let mut rlp = RlpStream::new_list(16);
+        rlp.append(&self.nonce);
+        rlp.append(&self.max_priority_fee_per_gas);
+        rlp.append(&self.max_fee_per_gas);
+        rlp.append(&self.gas_limit);
+        rlp.append(&self.to);
+        rlp.append(&self.value);
+        rlp.append(&self.data.as_ref());
+        // the slots of an Ethereum signature hold the chain id and two empty strings
+        rlp.append(&self.chain_id);
+        rlp.append_empty_data();
+        rlp.append_empty_data();
+        rlp.append(&self.chain_id);
+        rlp.append(&self.from);
+        rlp.append(&self.gas_per_pubdata);
+        rlp.begin_list(0);
+        rlp.append(&signature.to_vec());
+        match &self.paymaster_params {
+            Some(params) => {
+                rlp.begin_list(2);
+                rlp.append(&params.paymaster);
+                rlp.append(&params.paymaster_input.as_ref());
+            }
+            None => {
+                rlp.begin_list(0);
+            }
+        }
+        let mut encoded = vec![EIP712_TX_TYPE];
+        encoded.extend_from_slice(&rlp.out());
+        encoded.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        signers::{LocalWallet, Signer},
+        utils::rlp::Rlp,
+    };
+    use std::str::FromStr;
+
+    /// Key of address 0x2c7536E3605D9C16a7a3D7b1898e529396a65c23, used in the web3.js docs.
+    const KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn address(byte: u8) -> Address {
+        Address::repeat_byte(byte)
+    }
+
+    #[test]
+    fn test_type_hashes() {
+        // EIP712_TRANSACTION_TYPE_HASH and EIP712_DOMAIN_TYPEHASH of the zkSync system contracts
+        assert_eq!(
+            hex::encode(keccak256(TRANSACTION_TYPE)),
+            "848e1bfa1ac4e3576b728bda6721b215c70a7799a5b4866282a71bab954baac8"
+        );
+        assert_eq!(
+            hex::encode(keccak256(DOMAIN_TYPE)),
+            "c2f8787176b8ac6bf7215b4adcc1e069bf4ab82d9ab1df05a57a91d425935b6e"
+        );
+    }
+
+    #[test]
+    fn test_paymaster_inputs() {
+        let general = PaymasterParams::general(address(0xaa), &[]);
+        assert_eq!(
+            hex::encode(&general.paymaster_input),
+            "8c5a34450000000000000000000000000000000000000000000000000000000000000020\
+             0000000000000000000000000000000000000000000000000000000000000000"
+        );
+        let approval = PaymasterParams::approval_based(address(0xaa), address(0xbb), 1.into(), &[]);
+        assert_eq!(&approval.paymaster_input[..4], [0x94, 0x94, 0x31, 0xdc]);
+        assert_eq!(approval.paymaster_input.len(), 4 + 4 * 32);
+    }
+
+    #[test]
+    fn test_erc20_transfer_data() {
+        let tx =
+            Eip712Transaction::erc20_transfer(324, address(1), address(2), address(3), 5.into());
+        assert_eq!(tx.to, address(2));
+        assert!(tx.value.is_zero());
+        assert_eq!(
+            hex::encode(&tx.data),
+            "a9059cbb0000000000000000000000000303030303030303030303030303030303030303\
+             0000000000000000000000000000000000000000000000000000000000000005"
+        );
+    }
+
+    fn sign(tx: &Eip712Transaction) -> String {
+        let signer = LocalWallet::from_str(KEY).unwrap();
+        assert_eq!(signer.address(), tx.from);
+        hex::encode(tx.encode_signed(&signer.sign_hash(tx.signing_hash()).unwrap()))
+    }
+
+    // Serialized as zksync-ethers' `serialize` does after signing with `EIP712Signer`:
+    // chain id and two empty strings in the signature slots, the signature in
+    // `customSignature`, RFC 6979 nonces.
+    #[test]
+    fn test_known_transfer() {
+        let from = Address::from_str("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23").unwrap();
+        let to = Address::from_str("0x36615Cf349d7F6344891B1e7CA7C72883F5dc049").unwrap();
+        let tx = Eip712Transaction::transfer(324, from, to, U256::exp10(15))
+            .nonce(1)
+            .gas(300_000, 250_000_000, 0);
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "b1780981c0191185465722d1682237a88fae940262acd403119daedb0927501e"
+        );
+        assert_eq!(sign(&tx), "71f88e0180840ee6b280830493e09436615cf349d7f6344891b1e7ca7c72883f5dc04987038d7ea4c68000808201448080820144942c7536e3605d9c16a7a3d7b1898e529396a65c2382c350c0b841db66b6978a2d7e88c6eae01f52ad3ee29b7d99149394ac810d71b5e599b4e7754f401b497c769dd102182c4600ca705a2bb8e322f3a0aa33e0bbbbf9bf2fa3d21bc0");
+    }
+
+    #[test]
+    fn test_known_paymaster_transaction() {
+        let from = Address::from_str("0x2c7536E3605D9C16a7a3D7b1898e529396a65c23").unwrap();
+        let to = Address::from_str("0x36615Cf349d7F6344891B1e7CA7C72883F5dc049").unwrap();
+        let token = Address::from_str("0x3355df6D4c9C3035724Fd0e3914dE96A5a83aaf4").unwrap();
+        let paymaster = Address::from_str("0x069246dFEcb95A6409180b52C071003537B23c27").unwrap();
+        let tx = Eip712Transaction::erc20_transfer(324, from, token, to, 1_000_000.into())
+            .nonce(2)
+            .gas(600_000, 250_000_000, 0)
+            .paymaster(PaymasterParams::approval_based(
+                paymaster,
+                token,
+                1_000_000.into(),
+                &[],
+            ));
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "dc36b64d3d4d0a3fdf241ce01deebbc05d467bbc56f5b5356e043d88332b9f8f"
+        );
+        assert_eq!(sign(&tx), "71f901680280840ee6b280830927c0943355df6d4c9c3035724fd0e3914de96a5a83aaf480b844a9059cbb00000000000000000000000036615cf349d7f6344891b1e7ca7c72883f5dc04900000000000000000000000000000000000000000000000000000000000f42408201448080820144942c7536e3605d9c16a7a3d7b1898e529396a65c2382c350c0b841b55083e998317c572e8eed2652def990089bf1d4d60ffb780a28f3cac3a3ed98247a4728f24b8662b8e914064e28e4866fad4a2c65bb68e05817cca1a8249b111cf89b94069246dfecb95a6409180b52c071003537b23c27b884949431dc0000000000000000000000003355df6d4c9c3035724fd0e3914de96a5a83aaf400000000000000000000000000000000000000000000000000000000000f424000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000");
+    }
+
+    #[test]
+    fn test_encoding_fields() {
+        let tx = Eip712Transaction::transfer(324, address(1), address(2), 1000.into())
+            .nonce(3)
+            .gas(500_000, 250_000_000, 0)
+            .paymaster(PaymasterParams::general(address(0xaa), &[]));
+        let signature = Signature {
+            r: 1.into(),
+            s: 2.into(),
+            v: 27,
+        };
+        let encoded = tx.encode_signed(&signature);
+        assert_eq!(encoded[0], EIP712_TX_TYPE);
+
+        let rlp = Rlp::new(&encoded[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 16);
+        assert_eq!(rlp.val_at::<U256>(0).unwrap(), U256::from(3));
+        assert!(rlp.at(1).unwrap().data().unwrap().is_empty());
+        assert_eq!(rlp.val_at::<Address>(4).unwrap(), address(2));
+        assert_eq!(rlp.val_at::<u64>(7).unwrap(), 324);
+        assert_eq!(rlp.val_at::<u64>(10).unwrap(), 324);
+        assert_eq!(rlp.val_at::<Address>(11).unwrap(), address(1));
+        assert_eq!(
+            rlp.val_at::<u64>(12).unwrap(),
+            DEFAULT_GAS_PER_PUBDATA_LIMIT
+        );
+        assert_eq!(rlp.at(13).unwrap().item_count().unwrap(), 0);
+        assert_eq!(rlp.val_at::<Vec<u8>>(14).unwrap(), signature.to_vec());
+        let paymaster = rlp.at(15).unwrap();
+        assert_eq!(paymaster.val_at::<Address>(0).unwrap(), address(0xaa));
+    }
+}
@@ -7,8 +7,14 @@
 //!
 //! ## Modules
 //!
+//! - `cosmos`: Implementation of the Wallet trait for Cosmos SDK chains.
 //! - `ethereum`: Implementation of the Wallet trait for Ethereum.
+//! - `registry`: Registry of wallet factories by chain family.
+//! - `solana`: Implementation of the Wallet trait for Solana.
+//! - `stellar`: Implementation of the Wallet trait for Stellar.
 //! - `tron`: Implementation of the Wallet trait for Tron.
+//! - `utxo`: Implementation of the Wallet trait for Bitcoin, Litecoin and Dogecoin.
+//! - `xrp`: Implementation of the Wallet trait for the XRP Ledger.
 //!
 //! ## Usage
 //!
@@ -17,18 +23,27 @@
 //! blockchain wallets through a unified interface provided by the `Wallet` trait.
 
 use async_trait::async_trait;
-use ethers::types::{Transaction, TransactionReceipt, U256};
+use ethers::types::U256;
 
-use crate::{error::Error, types::token_data::TokenData};
+use crate::{
+    error::Error,
+    types::{token_data::TokenData, transfer_receipt::TransferReceipt},
+};
 
+pub mod cosmos;
 pub mod ethereum;
+pub mod registry;
+pub mod solana;
+pub mod stellar;
 pub mod tron;
+pub mod utxo;
+pub mod xrp;
 
 /// A common trait defining the interface for interacting with cryptocurrency wallets.
 /// This trait specifies the methods that all wallet implementations must provide,
 /// ensuring a consistent behavior across different types of wallets.
 #[async_trait]
-pub trait Wallet {
+pub trait Wallet: Send + Sync {
     /// Retrieves the wallet's address at the specified index.
     ///
     /// # Arguments
@@ -112,8 +127,13 @@ pub trait Wallet {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a tuple (transaction details, transferred balance) if successful, or an error if not.
-    fn sweep(&self, index: u32, to: &str, provider: &str) -> Result<(Transaction, U256), Error>;
+    /// A `Result` containing a tuple (transfer receipt, transferred balance) if successful, or an error if not.
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error>;
 
     /// Transfers all available tokens of a specific type from the wallet at the specified index to another address.
     ///
@@ -126,14 +146,14 @@ pub trait Wallet {
     ///
     /// # Returns
     ///
-    /// A `Result` containing a tuple (transaction details, transferred token balance) if successful, or an error if not.
-    fn sweep_token(
+    /// A `Result` containing a tuple (transfer receipt, transferred token balance) if successful, or an error if not.
+    async fn sweep_token(
         &self,
         index: u32,
         token_address: &str,
         to: &str,
         provider: &str,
-    ) -> Result<(Transaction, TokenData), Error>;
+    ) -> Result<(TransferReceipt, TokenData), Error>;
     /// Transfers a specified amount of native currency from the wallet at the specified index to another address.
     ///
     /// This method is asynchronous and requires a provider URL to interact with the blockchain network.
@@ -148,14 +168,14 @@ pub trait Wallet {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the `Transaction` details if successful, or an error if not.
+    /// A `Result` containing the `TransferReceipt` if successful, or an error if not.
     async fn transfer(
         &self,
         index: u32,
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error>;
+    ) -> Result<TransferReceipt, Error>;
 
     /// Transfers a specified amount of tokens from the wallet at the specified index to another address.
     ///
@@ -172,7 +192,7 @@ pub trait Wallet {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the `Transaction` details if successful, or an error if not.
+    /// A `Result` containing the `TransferReceipt` if successful, or an error if not.
     async fn transfer_token(
         &self,
         index: u32,
@@ -180,5 +200,21 @@ pub trait Wallet {
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error>;
+    ) -> Result<TransferReceipt, Error>;
+
+    /// Looks up the human-readable name registered for the wallet's address at the specified index,
+    /// such as the ENS primary name on EVM chains.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - An index specifying which wallet's address to look up.
+    /// * `provider` - A string slice that holds the provider URL to query the name service.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the name if one is registered, `None` otherwise. Chains without
+    /// a name service keep this default, which always returns `None`.
+    async fn reverse_name(&self, _index: u32, _provider: &str) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
 }
@@ -0,0 +1,300 @@
+//! Registry of wallet implementations, keyed by chain family.
+//!
+//! Every `ChainConfig` names the family of wallets serving it. The built-in registry
+//! covers the chains of this crate; other crates can register factories for their own
+//! families and describe their chains with a matching `ChainConfig`.
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    error::Error,
+    types::{
+        chain::ChainConfig,
+        hdseed::{FromSeed, HDSeed},
+    },
+};
+
+use super::{
+    cosmos::CosmosWallet,
+    ethereum::EthereumWallet,
+    solana::SolanaWallet,
+    stellar::StellarWallet,
+    tron::TronWallet,
+    utxo::{network::UtxoNetwork, AddressType, UtxoWallet},
+    xrp::XrpWallet,
+    Wallet,
+};
+
+/// Wallet that can be shared between tasks.
+pub type BoxedWallet = Box<dyn Wallet + Send + Sync>;
+
+/// Builds the wallet of a chain from the seed and the chain's configuration.
+pub type WalletFactory =
+    Arc<dyn Fn(HDSeed, &ChainConfig) -> Result<BoxedWallet, Error> + Send + Sync>;
+
+/// Wallet factories by chain family.
+#[derive(Clone, Default)]
+pub struct WalletRegistry {
+    factories: HashMap<String, WalletFactory>,
+}
+
+impl WalletRegistry {
+    pub fn new() -> Self {
+        WalletRegistry::default()
+    }
+
+    /// Registry with the wallets of this crate: `evm`, `tron`, `utxo`, `solana`,
+    /// `stellar`, `cosmos` and `xrp`.
+    pub fn builtin() -> Self {
+        let mut registry = WalletRegistry::new();
+        registry.register("evm", |seed, chain| {
+            Ok(Box::new(EthereumWallet::for_chain(seed, chain.clone())?))
+        });
+        registry.register("tron", |seed, chain| {
+            let wallet = TronWallet::from_seed(seed);
+            Ok(Box::new(match chain.fee_limit {
+                Some(fee_limit) => wallet.with_fee_limit(fee_limit),
+                None => wallet,
+            }))
+        });
+        registry.register("utxo", |seed, chain| {
+            let (network, address_type) = utxo_network(chain)?;
+            Ok(Box::new(UtxoWallet::new(seed, address_type, network)?))
+        });
+        registry.register("solana", |seed, _| {
+            Ok(Box::new(SolanaWallet::from_seed(seed)))
+        });
+        registry.register("stellar", |seed, chain| {
+            Ok(Box::new(if chain.network.is_mainnet() {
+                StellarWallet::from_seed(seed)
+            } else {
+                StellarWallet::testnet(seed)
+            }))
+        });
+        registry.register("cosmos", |seed, chain| {
+            let network = chain
+                .cosmos
+                .clone()
+                .ok_or_else(|| unsupported_chain(chain))?;
+            Ok(Box::new(CosmosWallet::new(seed, network)))
+        });
+        registry.register("xrp", |seed, _| Ok(Box::new(XrpWallet::from_seed(seed))));
+        registry
+    }
+
+    /// Registers `factory` for the chains of `family`, replacing any previous one.
+    pub fn register<F>(&mut self, family: &str, factory: F)
+    where
+        F: Fn(HDSeed, &ChainConfig) -> Result<BoxedWallet, Error> + Send + Sync + 'static,
+    {
+        self.factories
+            .insert(family.to_lowercase(), Arc::new(factory));
+    }
+
+    pub fn contains(&self, family: &str) -> bool {
+        self.factories.contains_key(&family.to_lowercase())
+    }
+
+    /// Builds the wallet of `chain` from `seed`.
+    pub fn wallet(&self, seed: HDSeed, chain: &ChainConfig) -> Result<BoxedWallet, Error> {
+        let factory = self
+            .factories
+            .get(&chain.family.to_lowercase())
+            .ok_or_else(|| Error::UnknownWalletFamily(chain.family.clone()))?;
+        factory(seed, chain)
+    }
+}
+
+/// Network parameters and address type of a UTXO chain, SegWit where the network has
+/// it and legacy P2PKH otherwise.
+fn utxo_network(chain: &ChainConfig) -> Result<(UtxoNetwork, AddressType), Error> {
+    let network = chain
+        .utxo_network
+        .as_deref()
+        .and_then(UtxoNetwork::from_name)
+        .ok_or_else(|| unsupported_chain(chain))?;
+    let address_type = if network.supports(AddressType::SegWit) {
+        AddressType::SegWit
+    } else {
+        AddressType::Legacy
+    };
+    Ok((network, address_type))
+}
+
+fn unsupported_chain(chain: &ChainConfig) -> Error {
+    Error::UnsupportedChain {
+        family: chain.family.clone(),
+        chain: chain.name.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        chain::ChainRegistry, network::Network, token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    };
+    use ethers::types::U256;
+
+    const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_builtin_chains_have_wallets() {
+        let wallets = WalletRegistry::builtin();
+        let seed = HDSeed::new(PHRASE).unwrap();
+        let chains = ChainRegistry::builtin();
+        for name in [
+            "eth", "tron", "btc", "ltc", "doge", "sol", "xlm", "atom", "osmo", "xrp",
+        ] {
+            for network in chains.networks(name) {
+                let chain = chains.get(name, network).unwrap();
+                assert!(wallets.wallet(seed.clone(), chain).is_ok(), "{}", name);
+            }
+        }
+        let btc = wallets
+            .wallet(seed.clone(), chains.get("btc", &Network::Mainnet).unwrap())
+            .unwrap();
+        assert_eq!(
+            btc.address(0).unwrap(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        let osmo = wallets
+            .wallet(seed, chains.get("osmo", &Network::Mainnet).unwrap())
+            .unwrap();
+        assert_eq!(
+            osmo.address(0).unwrap(),
+            "osmo19rl4cm2hmr8afy4kldpxz3fka4jguq0a5m7df8"
+        );
+    }
+
+    #[test]
+    fn test_family_parameters_from_config() {
+        let wallets = WalletRegistry::builtin();
+        let seed = HDSeed::new(PHRASE).unwrap();
+        let juno: ChainConfig = serde_json::from_str(
+            r#"{"name": "juno", "family": "cosmos", "chain_id": 0, "native_symbol": "JUNO",
+                "coin_type": 118, "decimals": 6,
+                "cosmos": {"hrp": "juno", "chain_id": "juno-1", "denom": "ujuno", "gas_price": 0.075}}"#,
+        )
+        .unwrap();
+        assert_eq!(juno.cosmos.as_ref().unwrap().gas_limit, 200_000);
+        assert!(wallets
+            .wallet(seed.clone(), &juno)
+            .unwrap()
+            .address(0)
+            .unwrap()
+            .starts_with("juno1"));
+
+        let signet = ChainConfig {
+            network: Network::Custom("signet".to_owned()),
+            ..ChainConfig::bitcoin_testnet()
+        };
+        assert!(wallets
+            .wallet(seed.clone(), &signet)
+            .unwrap()
+            .address(0)
+            .unwrap()
+            .starts_with("tb1q"));
+
+        for chain in [
+            ChainConfig {
+                cosmos: None,
+                ..juno
+            },
+            ChainConfig {
+                utxo_network: None,
+                ..ChainConfig::bitcoin()
+            },
+            ChainConfig {
+                utxo_network: Some("bitcoin-signet".to_owned()),
+                ..ChainConfig::bitcoin()
+            },
+        ] {
+            assert!(matches!(
+                wallets.wallet(seed.clone(), &chain),
+                Err(Error::UnsupportedChain { family, chain: name })
+                    if family == chain.family && name == chain.name
+            ));
+        }
+    }
+
+    #[test]
+    fn test_register_family() {
+        struct Fixed;
+        #[async_trait::async_trait]
+        impl Wallet for Fixed {
+            fn address(&self, _: u32) -> Result<String, Error> {
+                Ok("fixed".to_owned())
+            }
+            fn private(&self, _: u32) -> Result<String, Error> {
+                Ok(String::new())
+            }
+            fn public(&self, _: u32) -> Result<String, Error> {
+                Ok(String::new())
+            }
+            fn keypair(&self, _: u32) -> Result<(String, String), Error> {
+                Ok((String::new(), String::new()))
+            }
+            async fn balance(&self, _: u32, _: &str) -> Result<U256, Error> {
+                Ok(0.into())
+            }
+            async fn balance_token(&self, _: u32, _: &str, _: &str) -> Result<U256, Error> {
+                Err(Error::TokensNotSupported)
+            }
+            async fn sweep(
+                &self,
+                _: u32,
+                _: &str,
+                _: &str,
+            ) -> Result<(TransferReceipt, U256), Error> {
+                Err(Error::TokensNotSupported)
+            }
+            async fn sweep_token(
+                &self,
+                _: u32,
+                _: &str,
+                _: &str,
+                _: &str,
+            ) -> Result<(TransferReceipt, TokenData), Error> {
+                Err(Error::TokensNotSupported)
+            }
+            async fn transfer(
+                &self,
+                _: u32,
+                _: &str,
+                _: U256,
+                _: &str,
+            ) -> Result<TransferReceipt, Error> {
+                Err(Error::TokensNotSupported)
+            }
+            async fn transfer_token(
+                &self,
+                _: u32,
+                _: &str,
+                _: &str,
+                _: U256,
+                _: &str,
+            ) -> Result<TransferReceipt, Error> {
+                Err(Error::TokensNotSupported)
+            }
+        }
+
+        let chain = ChainConfig {
+            family: "Fixed".to_owned(),
+            ..ChainConfig::new("fixed", 0, "FIX")
+        };
+        let mut wallets = WalletRegistry::builtin();
+        let seed = HDSeed::new(PHRASE).unwrap();
+        assert!(matches!(
+            wallets.wallet(seed.clone(), &chain),
+            Err(Error::UnknownWalletFamily(_))
+        ));
+        wallets.register("fixed", |_, _| Ok(Box::new(Fixed)));
+        assert!(wallets.contains("FIXED"));
+        assert_eq!(
+            wallets.wallet(seed, &chain).unwrap().address(0).unwrap(),
+            "fixed"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+pub mod pubkey;
+pub mod rpc;
+pub mod transaction;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bitcoin::bip32::DerivationPath;
+use ed25519_dalek::SigningKey;
+use ethers::types::U256;
+
+use crate::{
+    error::Error,
+    types::{
+        hdseed::{FromSeed, HDSeed},
+        token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    },
+    utils::{amount::amount_as, key::ed25519_key_by_index},
+};
+
+use self::{
+    pubkey::Pubkey,
+    rpc::SolanaRpcClient,
+    transaction::{
+        associated_token_address, create_associated_token_account_idempotent, spl_transfer_checked,
+        system_transfer, Instruction, Message, Transaction,
+    },
+};
+use super::Wallet;
+
+/// Solana wallet deriving the Phantom-compatible keys `m/44'/501'/i'/0'` with SLIP-10.
+/// Token addresses are SPL mint addresses; amounts are in lamports or base token units.
+pub struct SolanaWallet {
+    pub seed: HDSeed,
+}
+
+impl FromSeed for SolanaWallet {
+    fn from_seed(seed: HDSeed) -> Self {
+        SolanaWallet { seed }
+    }
+}
+
+impl SolanaWallet {
+    pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
+        Ok(DerivationPath::from_str(&format!(
+            "m/44'/501'/{}'/0'",
+            index
+        ))?)
+    }
+
+    pub fn signing_key(&self, index: u32) -> Result<SigningKey, Error> {
+        let path = self.get_hd_path(index)?;
+        let secret = ed25519_key_by_index(&self.seed.mnemonic, &path)?;
+        Ok(SigningKey::from_bytes(&secret))
+    }
+
+    pub fn pubkey(&self, index: u32) -> Result<Pubkey, Error> {
+        Ok(Pubkey(self.signing_key(index)?.verifying_key().to_bytes()))
+    }
+
+    /// Builds and signs a transaction of `instructions` paid by the key of `index`.
+    pub fn sign_instructions(
+        &self,
+        index: u32,
+        instructions: &[Instruction],
+        recent_blockhash: [u8; 32],
+    ) -> Result<Transaction, Error> {
+        let key = self.signing_key(index)?;
+        let payer = Pubkey(key.verifying_key().to_bytes());
+        let message = Message::new(instructions, &payer, recent_blockhash);
+        Ok(Transaction::sign(message, &[&key]))
+    }
+
+    /// Instructions of an SPL transfer of `amount` base units of `mint` from `owner`
+    /// to `to`, creating the recipient's token account if it doesn't exist.
+    pub fn token_transfer_instructions(
+        owner: &Pubkey,
+        mint: &Pubkey,
+        to: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Vec<Instruction> {
+        vec![
+            create_associated_token_account_idempotent(owner, to, mint),
+            spl_transfer_checked(
+                &associated_token_address(owner, mint),
+                mint,
+                &associated_token_address(to, mint),
+                owner,
+                amount,
+                decimals,
+            ),
+        ]
+    }
+
+    async fn sol_balance_by_index(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        let client = SolanaRpcClient::new(provider);
+        let balance = client.get_balance(&self.pubkey(index)?.to_string()).await?;
+        Ok(U256::from(balance))
+    }
+
+    async fn spl_balance_by_index(
+        &self,
+        index: u32,
+        mint: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        let client = SolanaRpcClient::new(provider);
+        let mint = Pubkey::from_str(mint)?;
+        let account = associated_token_address(&self.pubkey(index)?, &mint);
+        let balance = client.get_token_balance(&account.to_string()).await?;
+        Ok(U256::from(balance.map_or(0, |b| b.amount)))
+    }
+
+    async fn sol_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        lamports: u64,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = SolanaRpcClient::new(provider);
+        let to = Pubkey::from_str(to)?;
+        let ix = system_transfer(&self.pubkey(index)?, &to, lamports);
+        let blockhash = client.get_latest_blockhash().await?;
+        let tx = self.sign_instructions(index, &[ix], blockhash)?;
+        let txid = client.send_transaction(&tx).await?;
+        Ok(TransferReceipt::Broadcast { txid })
+    }
+
+    async fn spl_transfer(
+        &self,
+        index: u32,
+        mint: &str,
+        to: &str,
+        amount: Option<u64>,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        let client = SolanaRpcClient::new(provider);
+        let owner = self.pubkey(index)?;
+        let mint_key = Pubkey::from_str(mint)?;
+        let to = Pubkey::from_str(to)?;
+        let source = associated_token_address(&owner, &mint_key);
+        let balance = client
+            .get_token_balance(&source.to_string())
+            .await?
+            .ok_or_else(|| Error::SolanaRpcError(format!("no token account {}", source)))?;
+        let amount = amount.unwrap_or(balance.amount);
+        if amount > balance.amount {
+            return Err(Error::InsufficientFunds {
+                needed: amount,
+                available: balance.amount,
+            });
+        }
+        let instructions =
+            Self::token_transfer_instructions(&owner, &mint_key, &to, amount, balance.decimals);
+        let blockhash = client.get_latest_blockhash().await?;
+        let tx = self.sign_instructions(index, &instructions, blockhash)?;
+        let txid = client.send_transaction(&tx).await?;
+        let token_data = TokenData {
+            balance: U256::from(amount),
+            balance_f: amount as f64 / 10f64.powi(balance.decimals as i32),
+            decimals: balance.decimals,
+            // SPL mints carry no symbol on-chain
+            symbol: String::new(),
+            address: mint.to_owned(),
+        };
+        Ok((TransferReceipt::Broadcast { txid }, token_data))
+    }
+
+    async fn sol_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let client = SolanaRpcClient::new(provider);
+        let from = self.pubkey(index)?;
+        let to_key = Pubkey::from_str(to)?;
+        let balance = client.get_balance(&from.to_string()).await?;
+        let blockhash = client.get_latest_blockhash().await?;
+        let message = Message::new(
+            &[system_transfer(&from, &to_key, balance)],
+            &from,
+            blockhash,
+        );
+        let fee = client.get_fee_for_message(&message).await?;
+        if balance <= fee {
+            return Err(Error::InsufficientFunds {
+                needed: fee + 1,
+                available: balance,
+            });
+        }
+        let amount = balance - fee;
+        let ix = system_transfer(&from, &to_key, amount);
+        let tx = self.sign_instructions(index, &[ix], blockhash)?;
+        let txid = client.send_transaction(&tx).await?;
+        Ok((TransferReceipt::Broadcast { txid }, U256::from(amount)))
+    }
+}
+
+#[async_trait]
+impl Wallet for SolanaWallet {
+    fn address(&self, index: u32) -> Result<String, Error> {
+        Ok(self.pubkey(index)?.to_string())
+    }
+    fn public(&self, index: u32) -> Result<String, Error> {
+        self.address(index)
+    }
+    /// Base58 of the 64-byte secret and public key, the format Phantom and
+    /// `solana-keygen` import.
+    fn private(&self, index: u32) -> Result<String, Error> {
+        let key = self.signing_key(index)?;
+        Ok(bs58::encode(key.to_keypair_bytes()).into_string())
+    }
+    fn keypair(&self, index: u32) -> Result<(String, String), Error> {
+        Ok((self.private(index)?, self.public(index)?))
+    }
+    async fn balance(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        self.sol_balance_by_index(index, provider).await
+    }
+    async fn balance_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        self.spl_balance_by_index(index, token_address, provider)
+            .await
+    }
+    async fn transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.sol_transfer(index, to, amount_as(amount)?, provider)
+            .await
+    }
+    async fn transfer_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let (receipt, _) = self
+            .spl_transfer(index, token_address, to, Some(amount_as(amount)?), provider)
+            .await?;
+        Ok(receipt)
+    }
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        self.sol_sweep(index, to, provider).await
+    }
+    async fn sweep_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        self.spl_transfer(index, token_address, to, None, provider)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use bip39::{Language, Mnemonic};
+    use ed25519_dalek::{Signature, Verifier};
+
+    const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn wallet() -> SolanaWallet {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        SolanaWallet::from_seed(HDSeed { mnemonic })
+    }
+
+    #[test]
+    fn test_sol_address_by_index() {
+        let wallet = wallet();
+        assert_eq!(
+            wallet.address(0).unwrap(),
+            "HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk"
+        );
+        assert_eq!(
+            wallet.address(1).unwrap(),
+            "Hh8QwFUA6MtVu1qAoq12ucvFHNwCcVTV7hpWjeY1Hztb"
+        );
+    }
+
+    #[test]
+    fn test_associated_token_address() {
+        let owner = Pubkey::from_str("HAgk14JpMQLgt6rVgv7cBQFJWFto5Dqxi472uT3DKpqk").unwrap();
+        let mint = Pubkey::from_str(USDC).unwrap();
+        let ata = associated_token_address(&owner, &mint);
+        assert!(!ata.is_on_curve());
+        assert_eq!(
+            ata.to_string(),
+            "5N3f1tj9v1vc5TUZ8S7mCAnVmjVKrfnzXWhxLaxyZAgt"
+        );
+    }
+
+    #[test]
+    fn test_sign_sol_transfer() {
+        let wallet = wallet();
+        let from = wallet.pubkey(0).unwrap();
+        let to = wallet.pubkey(1).unwrap();
+        let tx = wallet
+            .sign_instructions(0, &[system_transfer(&from, &to, 1_000_000)], [7; 32])
+            .unwrap();
+        assert_eq!(tx.message.num_required_signatures, 1);
+        assert_eq!(
+            tx.message.account_keys,
+            vec![from, to, pubkey::SYSTEM_PROGRAM_ID]
+        );
+        let verifying_key = wallet.signing_key(0).unwrap().verifying_key();
+        verifying_key
+            .verify(
+                &tx.message.serialize(),
+                &Signature::from_bytes(&tx.signatures[0]),
+            )
+            .unwrap();
+        // ed25519 signatures are deterministic
+        assert_eq!(hex::encode(tx.serialize()), "016dd61b74838ec9a47cbbfc6f0d80e5df8d35d9eb6848676809eaab5db5a2f1fdc66ef7e475c7249a639f694c0062ff6ae2608f6a662dea546acf15cf913cef0101000103f036276246a75b9de3349ed42b15e232f6518fc20f5fcd4f1d64e81f9bd258f7f8029acf5cbcbdd5ac46ec147f3b78a3df6e5022ef0411db2bab650d329a4cd40000000000000000000000000000000000000000000000000000000000000000070707070707070707070707070707070707070707070707070707070707070701020200010c0200000040420f0000000000");
+    }
+
+    #[test]
+    fn test_sign_spl_transfer() {
+        let wallet = wallet();
+        let owner = wallet.pubkey(0).unwrap();
+        let to = wallet.pubkey(1).unwrap();
+        let mint = Pubkey::from_str(USDC).unwrap();
+        let instructions =
+            SolanaWallet::token_transfer_instructions(&owner, &mint, &to, 2_500_000, 6);
+        let tx = wallet.sign_instructions(0, &instructions, [7; 32]).unwrap();
+        let message = &tx.message;
+        // payer, source and destination token accounts are writable, the recipient,
+        // mint and programs are read-only
+        assert_eq!(message.account_keys[0], owner);
+        assert_eq!(message.num_required_signatures, 1);
+        assert_eq!(message.num_readonly_signed_accounts, 0);
+        assert_eq!(message.num_readonly_unsigned_accounts, 5);
+        let transfer = &message.instructions[1];
+        assert_eq!(transfer.data[0], 12);
+        assert_eq!(
+            u64::from_le_bytes(transfer.data[1..9].try_into().unwrap()),
+            2_500_000
+        );
+        assert_eq!(
+            message.account_keys[transfer.accounts[0] as usize],
+            associated_token_address(&owner, &mint)
+        );
+        let verifying_key = wallet.signing_key(0).unwrap().verifying_key();
+        verifying_key
+            .verify(
+                &message.serialize(),
+                &Signature::from_bytes(&tx.signatures[0]),
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_sweep_against_rpc_stand_in() {
+        let wallet = wallet();
+        let blockhash = bs58::encode([9u8; 32]).into_string();
+        let server = TestServer::start(vec![
+            Route::rpc("getBalance", r#"{"context":{"slot":1},"value":1000000}"#),
+            Route::rpc(
+                "getLatestBlockhash",
+                &format!(
+                    r#"{{"context":{{"slot":1}},"value":{{"blockhash":"{}","lastValidBlockHeight":100}}}}"#,
+                    blockhash
+                ),
+            ),
+            Route::rpc("getFeeForMessage", r#"{"context":{"slot":1},"value":5000}"#),
+            Route::rpc("sendTransaction", r#""5xSig""#),
+        ])
+        .await;
+
+        let to = wallet.address(1).unwrap();
+        let (receipt, amount) = wallet.sweep(0, &to, &server.url).await.unwrap();
+        assert_eq!(receipt.txid(), "5xSig");
+        assert_eq!(amount, U256::from(995_000));
+
+        let sent = server.requests().pop().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&sent.body).unwrap();
+        let tx = BASE64.decode(body["params"][0].as_str().unwrap()).unwrap();
+        let expected = wallet
+            .sign_instructions(
+                0,
+                &[system_transfer(
+                    &wallet.pubkey(0).unwrap(),
+                    &wallet.pubkey(1).unwrap(),
+                    995_000,
+                )],
+                [9; 32],
+            )
+            .unwrap();
+        assert_eq!(tx, expected.serialize());
+    }
+
+    #[tokio::test]
+    async fn test_token_balance_of_missing_account() {
+        let server = TestServer::start(vec![Route::rpc(
+            "getAccountInfo",
+            r#"{"context":{"slot":1},"value":null}"#,
+        )])
+        .await;
+        let balance = wallet().balance_token(0, USDC, &server.url).await.unwrap();
+        assert_eq!(balance, U256::zero());
+    }
+}
@@ -0,0 +1,63 @@
+//! Solana account addresses and program derived addresses.
+
+use std::{fmt, str::FromStr};
+
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+
+/// A 32-byte Solana account address, shown in base58.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey(pub [u8; 32]);
+
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey([0; 32]);
+
+impl Pubkey {
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Whether the address is a valid ed25519 point, i.e. could have a private key.
+    pub fn is_on_curve(&self) -> bool {
+        CompressedEdwardsY(self.0).decompress().is_some()
+    }
+
+    /// Program derived address of `seeds` under `program_id`, with its bump seed.
+    pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        for bump in (0..=u8::MAX).rev() {
+            let mut hasher = Sha256::new();
+            for seed in seeds {
+                hasher.update(seed);
+            }
+            hasher.update([bump]);
+            hasher.update(program_id.0);
+            hasher.update(b"ProgramDerivedAddress");
+            let address = Pubkey(hasher.finalize().into());
+            if !address.is_on_curve() {
+                return (address, bump);
+            }
+        }
+        unreachable!("a bump seed yields an off-curve address with overwhelming probability")
+    }
+}
+
+impl FromStr for Pubkey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| Error::SolanaAddressError(s.to_owned()))?;
+        let bytes: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| Error::SolanaAddressError(s.to_owned()))?;
+        Ok(Pubkey(bytes))
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
@@ -0,0 +1,149 @@
+//! Client for the Solana JSON-RPC API.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+use super::transaction::{Message, Transaction};
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+/// Result of the calls that report the slot they were answered at.
+#[derive(Debug, Deserialize)]
+struct WithContext<T> {
+    value: T,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LatestBlockhash {
+    blockhash: String,
+}
+
+/// Balance of an SPL token account, in base units of its mint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenBalance {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+pub struct SolanaRpcClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl SolanaRpcClient {
+    /// Creates a client for the RPC node at `url`, e.g. `https://api.mainnet-beta.solana.com`.
+    pub fn new(url: &str) -> Self {
+        SolanaRpcClient {
+            url: url.to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+        let response: RpcResponse<T> = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.error {
+            return Err(Error::SolanaRpcError(format!(
+                "{} ({})",
+                error.message, error.code
+            )));
+        }
+        response
+            .result
+            .ok_or_else(|| Error::SolanaRpcError(format!("{} returned no result", method)))
+    }
+
+    /// Balance of `address` in lamports.
+    pub async fn get_balance(&self, address: &str) -> Result<u64, Error> {
+        let balance: WithContext<u64> = self
+            .call("getBalance", json!([address, {"commitment": "confirmed"}]))
+            .await?;
+        Ok(balance.value)
+    }
+
+    /// Balance of the SPL token account at `address`, `None` if the account doesn't exist.
+    pub async fn get_token_balance(&self, address: &str) -> Result<Option<TokenBalance>, Error> {
+        let account: WithContext<Option<Value>> = self
+            .call(
+                "getAccountInfo",
+                json!([address, {"encoding": "jsonParsed", "commitment": "confirmed"}]),
+            )
+            .await?;
+        let Some(account) = account.value else {
+            return Ok(None);
+        };
+        let token_amount = &account["data"]["parsed"]["info"]["tokenAmount"];
+        let amount = token_amount["amount"]
+            .as_str()
+            .and_then(|amount| amount.parse().ok());
+        let decimals = token_amount["decimals"].as_u64();
+        match (amount, decimals) {
+            (Some(amount), Some(decimals)) => Ok(Some(TokenBalance {
+                amount,
+                decimals: decimals as u8,
+            })),
+            _ => Err(Error::SolanaRpcError(format!(
+                "{} is not a token account",
+                address
+            ))),
+        }
+    }
+
+    /// Most recent blockhash, to be used as the transaction's lifetime.
+    pub async fn get_latest_blockhash(&self) -> Result<[u8; 32], Error> {
+        let latest: WithContext<LatestBlockhash> = self
+            .call("getLatestBlockhash", json!([{"commitment": "finalized"}]))
+            .await?;
+        bs58::decode(&latest.value.blockhash)
+            .into_vec()
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or(Error::SolanaRpcError(latest.value.blockhash))
+    }
+
+    /// Fee in lamports the network charges for `message`.
+    pub async fn get_fee_for_message(&self, message: &Message) -> Result<u64, Error> {
+        let fee: WithContext<Option<u64>> = self
+            .call(
+                "getFeeForMessage",
+                json!([BASE64.encode(message.serialize()), {"commitment": "confirmed"}]),
+            )
+            .await?;
+        fee.value.ok_or(Error::FeeEstimateUnavailable)
+    }
+
+    /// Submits `tx` and returns its signature.
+    pub async fn send_transaction(&self, tx: &Transaction) -> Result<String, Error> {
+        self.call(
+            "sendTransaction",
+            json!([BASE64.encode(tx.serialize()), {"encoding": "base64"}]),
+        )
+        .await
+    }
+}
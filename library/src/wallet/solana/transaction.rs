@@ -0,0 +1,255 @@
+//! Instructions, legacy messages and signed transactions of the Solana wire format.
+
+use std::str::FromStr;
+
+use ed25519_dalek::{Signer, SigningKey};
+
+use super::pubkey::{Pubkey, SYSTEM_PROGRAM_ID};
+
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+fn token_program() -> Pubkey {
+    Pubkey::from_str(TOKEN_PROGRAM_ID).expect("valid program id")
+}
+
+fn associated_token_program() -> Pubkey {
+    Pubkey::from_str(ASSOCIATED_TOKEN_PROGRAM_ID).expect("valid program id")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    pub fn new(pubkey: Pubkey, is_signer: bool) -> Self {
+        AccountMeta {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    pub fn new_readonly(pubkey: Pubkey, is_signer: bool) -> Self {
+        AccountMeta {
+            pubkey,
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<AccountMeta>,
+    pub data: Vec<u8>,
+}
+
+/// System program `Transfer` of `lamports` from `from` to `to`.
+pub fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    let mut data = 2u32.to_le_bytes().to_vec();
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction {
+        program_id: SYSTEM_PROGRAM_ID,
+        accounts: vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+        data,
+    }
+}
+
+/// Associated token account of `owner` for `mint`.
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (address, _) = Pubkey::find_program_address(
+        &[&owner.0, &token_program().0, &mint.0],
+        &associated_token_program(),
+    );
+    address
+}
+
+/// Associated token program `CreateIdempotent`: creates the token account of `owner`
+/// for `mint`, paid by `payer`, unless it already exists.
+pub fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: associated_token_program(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+            AccountMeta::new_readonly(token_program(), false),
+        ],
+        data: vec![1],
+    }
+}
+
+/// SPL token `TransferChecked` of `amount` base units of `mint`.
+pub fn spl_transfer_checked(
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Instruction {
+    let mut data = vec![12];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+    Instruction {
+        program_id: token_program(),
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    }
+}
+
+/// Appends the compact-u16 ("shortvec") encoding of `len`.
+fn encode_length(out: &mut Vec<u8>, len: usize) {
+    let mut rem = len;
+    loop {
+        let mut byte = (rem & 0x7f) as u8;
+        rem >>= 7;
+        if rem == 0 {
+            out.push(byte);
+            break;
+        }
+        byte |= 0x80;
+        out.push(byte);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+/// A legacy (unversioned) message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub num_required_signatures: u8,
+    pub num_readonly_signed_accounts: u8,
+    pub num_readonly_unsigned_accounts: u8,
+    pub account_keys: Vec<Pubkey>,
+    pub recent_blockhash: [u8; 32],
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+impl Message {
+    /// Compiles `instructions` paid by `payer`. Accounts are ordered writable signers,
+    /// read-only signers, writable non-signers, read-only non-signers, with the payer first.
+    pub fn new(instructions: &[Instruction], payer: &Pubkey, recent_blockhash: [u8; 32]) -> Self {
+        let mut metas: Vec<AccountMeta> = vec![AccountMeta::new(*payer, true)];
+        let mut add = |meta: AccountMeta| {
+            match metas.iter_mut().find(|m| m.pubkey == meta.pubkey) {
+                Some(existing) => {
+                    existing.is_signer |= meta.is_signer;
+                    existing.is_writable |= meta.is_writable;
+                }
+                None => metas.push(meta),
+            };
+        };
+        for ix in instructions {
+            for meta in &ix.accounts {
+                add(meta.clone());
+            }
+            add(AccountMeta::new_readonly(ix.program_id, false));
+        }
+        // stable sort keeps the payer first and otherwise the order of appearance
+        metas.sort_by_key(|m| (!m.is_signer, !m.is_writable));
+
+        let account_keys: Vec<Pubkey> = metas.iter().map(|m| m.pubkey).collect();
+        let index_of = |key: &Pubkey| {
+            account_keys
+                .iter()
+                .position(|k| k == key)
+                .expect("every account was added") as u8
+        };
+        let count = |signer: bool, writable: bool| {
+            metas
+                .iter()
+                .filter(|m| m.is_signer == signer && m.is_writable == writable)
+                .count() as u8
+        };
+        Message {
+            num_required_signatures: metas.iter().filter(|m| m.is_signer).count() as u8,
+            num_readonly_signed_accounts: count(true, false),
+            num_readonly_unsigned_accounts: count(false, false),
+            instructions: instructions
+                .iter()
+                .map(|ix| CompiledInstruction {
+                    program_id_index: index_of(&ix.program_id),
+                    accounts: ix.accounts.iter().map(|m| index_of(&m.pubkey)).collect(),
+                    data: ix.data.clone(),
+                })
+                .collect(),
+            account_keys,
+            recent_blockhash,
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![
+            self.num_required_signatures,
+            self.num_readonly_signed_accounts,
+            self.num_readonly_unsigned_accounts,
+        ];
+        encode_length(&mut out, self.account_keys.len());
+        for key in &self.account_keys {
+            out.extend_from_slice(&key.0);
+        }
+        out.extend_from_slice(&self.recent_blockhash);
+        encode_length(&mut out, self.instructions.len());
+        for ix in &self.instructions {
+            out.push(ix.program_id_index);
+            encode_length(&mut out, ix.accounts.len());
+            out.extend_from_slice(&ix.accounts);
+            encode_length(&mut out, ix.data.len());
+            out.extend_from_slice(&ix.data);
+        }
+        out
+    }
+}
+
+/// A message with the signatures of its required signers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub signatures: Vec<[u8; 64]>,
+    pub message: Message,
+}
+
+impl Transaction {
+    /// Signs `message` with `signers`, ordered as the message's signing accounts.
+    pub fn sign(message: Message, signers: &[&SigningKey]) -> Self {
+        let bytes = message.serialize();
+        Transaction {
+            signatures: signers
+                .iter()
+                .map(|key| key.sign(&bytes).to_bytes())
+                .collect(),
+            message,
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = vec![];
+        encode_length(&mut out, self.signatures.len());
+        for sig in &self.signatures {
+            out.extend_from_slice(sig);
+        }
+        out.extend(self.message.serialize());
+        out
+    }
+}
@@ -0,0 +1,121 @@
+//! Client for Horizon-compatible Stellar HTTP APIs.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::error::Error;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Balance {
+    /// Decimal amount with 7 fractional digits, e.g. `"10.5000000"`.
+    pub balance: String,
+    pub asset_type: String,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+}
+
+impl Balance {
+    /// Whether the balance is of `asset`, `"native"` or `"CODE:ISSUER"`.
+    pub fn is_asset(&self, asset: &str) -> bool {
+        match (&self.asset_code, &self.asset_issuer) {
+            (Some(code), Some(issuer)) => asset == format!("{}:{}", code, issuer),
+            _ => asset == "native" && self.asset_type == "native",
+        }
+    }
+
+    /// Balance in stroops.
+    pub fn stroops(&self) -> Result<i64, Error> {
+        parse_stroops(&self.balance)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Account {
+    pub account_id: String,
+    pub sequence: String,
+    pub subentry_count: u32,
+    pub balances: Vec<Balance>,
+}
+
+impl Account {
+    pub fn sequence(&self) -> Result<i64, Error> {
+        self.sequence
+            .parse()
+            .map_err(|_| Error::HorizonError(format!("bad sequence {}", self.sequence)))
+    }
+
+    pub fn balance_of(&self, asset: &str) -> Option<&Balance> {
+        self.balances.iter().find(|b| b.is_asset(asset))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitResponse {
+    hash: String,
+}
+
+/// Parses a Horizon decimal amount into stroops (1e-7).
+pub fn parse_stroops(amount: &str) -> Result<i64, Error> {
+    let invalid = || Error::HorizonError(format!("bad amount {}", amount));
+    let (whole, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    if frac.len() > 7 {
+        return Err(invalid());
+    }
+    let whole: i64 = whole.parse().map_err(|_| invalid())?;
+    let frac: i64 = format!("{:0<7}", frac).parse().map_err(|_| invalid())?;
+    whole
+        .checked_mul(10_000_000)
+        .and_then(|w| w.checked_add(frac))
+        .ok_or_else(invalid)
+}
+
+pub struct HorizonClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HorizonClient {
+    /// Creates a client for the API at `base_url`, e.g. `https://horizon.stellar.org`.
+    pub fn new(base_url: &str) -> Self {
+        HorizonClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Account `id`, `None` if it hasn't been funded yet.
+    pub async fn account(&self, id: &str) -> Result<Option<Account>, Error> {
+        let response = self
+            .client
+            .get(format!("{}/accounts/{}", self.base_url, id))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    /// Submits a signed transaction envelope and returns its hash.
+    pub async fn submit(&self, envelope: &[u8]) -> Result<String, Error> {
+        let response = self
+            .client
+            .post(format!("{}/transactions", self.base_url))
+            .form(&[("tx", BASE64.encode(envelope))])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let body: Value = response.json().await.unwrap_or_default();
+            let codes = &body["extras"]["result_codes"];
+            return Err(Error::BroadcastError(if codes.is_null() {
+                body["title"].as_str().unwrap_or("rejected").to_owned()
+            } else {
+                codes.to_string()
+            }));
+        }
+        let submitted: SubmitResponse = response.json().await?;
+        Ok(submitted.hash)
+    }
+}
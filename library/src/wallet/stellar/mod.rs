@@ -0,0 +1,507 @@
+pub mod horizon;
+pub mod strkey;
+pub mod xdr;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bitcoin::bip32::DerivationPath;
+use ed25519_dalek::SigningKey;
+use ethers::types::U256;
+
+use crate::{
+    error::Error,
+    types::{
+        hdseed::{FromSeed, HDSeed},
+        token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    },
+    utils::{amount::amount_as, key::ed25519_key_by_index},
+};
+
+use self::{
+    horizon::{Account, HorizonClient},
+    strkey::{VERSION_ACCOUNT_ID, VERSION_SEED},
+    xdr::{Asset, Operation, Transaction, PUBLIC_NETWORK_PASSPHRASE, TESTNET_NETWORK_PASSPHRASE},
+};
+use super::Wallet;
+
+/// Fee per operation, in stroops. This is the network minimum.
+pub const BASE_FEE: u32 = 100;
+/// Lumens every account and every subentry (trustline, offer, signer) must keep, in stroops.
+pub const BASE_RESERVE: i64 = 5_000_000;
+/// Decimals of lumens and of every Stellar asset.
+pub const STELLAR_DECIMALS: u8 = 7;
+
+/// Stellar wallet deriving the SEP-0005 keys `m/44'/148'/i'`.
+/// Token addresses are assets in Horizon's `CODE:ISSUER` form; amounts are in stroops.
+pub struct StellarWallet {
+    pub seed: HDSeed,
+    pub network_passphrase: String,
+}
+
+impl FromSeed for StellarWallet {
+    fn from_seed(seed: HDSeed) -> Self {
+        StellarWallet {
+            seed,
+            network_passphrase: PUBLIC_NETWORK_PASSPHRASE.to_owned(),
+        }
+    }
+}
+
+impl StellarWallet {
+    /// Wallet signing for the SDF test network.
+    pub fn testnet(seed: HDSeed) -> Self {
+        StellarWallet {
+            seed,
+            network_passphrase: TESTNET_NETWORK_PASSPHRASE.to_owned(),
+        }
+    }
+
+    pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
+        Ok(DerivationPath::from_str(&format!("m/44'/148'/{}'", index))?)
+    }
+
+    pub fn signing_key(&self, index: u32) -> Result<SigningKey, Error> {
+        let path = self.get_hd_path(index)?;
+        let secret = ed25519_key_by_index(&self.seed.mnemonic, &path)?;
+        Ok(SigningKey::from_bytes(&secret))
+    }
+
+    fn public_key(&self, index: u32) -> Result<[u8; 32], Error> {
+        Ok(self.signing_key(index)?.verifying_key().to_bytes())
+    }
+
+    /// Signs a transaction of `operations` from the account of `index`, whose current
+    /// sequence number is `sequence`. Returns the envelope XDR.
+    pub fn sign_operations(
+        &self,
+        index: u32,
+        sequence: i64,
+        operations: Vec<Operation>,
+    ) -> Result<Vec<u8>, Error> {
+        let tx = Transaction {
+            source: self.public_key(index)?,
+            fee: BASE_FEE * operations.len() as u32,
+            sequence: sequence + 1,
+            operations,
+        };
+        tx.sign(&self.signing_key(index)?, &self.network_passphrase)
+    }
+
+    async fn source_account(&self, client: &HorizonClient, index: u32) -> Result<Account, Error> {
+        let address = self.address(index)?;
+        client
+            .account(&address)
+            .await?
+            .ok_or(Error::StellarAccountNotFound(address))
+    }
+
+    async fn xlm_balance_by_index(
+        &self,
+        index: u32,
+        asset: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        let client = HorizonClient::new(provider);
+        let Some(account) = client.account(&self.address(index)?).await? else {
+            return Ok(U256::zero());
+        };
+        match account.balance_of(asset) {
+            Some(balance) => Ok(U256::from(balance.stroops()?)),
+            None => Ok(U256::zero()),
+        }
+    }
+
+    async fn xlm_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: i64,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = HorizonClient::new(provider);
+        let source = self.source_account(&client, index).await?;
+        let destination = strkey::decode(VERSION_ACCOUNT_ID, to)?;
+        // payments to accounts that don't exist yet fail, such accounts are created instead
+        let operation = match client.account(to).await? {
+            Some(_) => Operation::Payment {
+                destination,
+                asset: Asset::Native,
+                amount,
+            },
+            None => Operation::CreateAccount {
+                destination,
+                starting_balance: amount,
+            },
+        };
+        let envelope = self.sign_operations(index, source.sequence()?, vec![operation])?;
+        let txid = client.submit(&envelope).await?;
+        Ok(TransferReceipt::Broadcast { txid })
+    }
+
+    async fn asset_transfer(
+        &self,
+        index: u32,
+        asset: &str,
+        to: &str,
+        amount: Option<i64>,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        let client = HorizonClient::new(provider);
+        let source = self.source_account(&client, index).await?;
+        let destination = strkey::decode(VERSION_ACCOUNT_ID, to)?;
+        let recipient = client
+            .account(to)
+            .await?
+            .ok_or_else(|| Error::StellarAccountNotFound(to.to_owned()))?;
+        if recipient.balance_of(asset).is_none() {
+            return Err(Error::StellarNoTrustline(to.to_owned()));
+        }
+        let available = match source.balance_of(asset) {
+            Some(balance) => balance.stroops()?,
+            None => 0,
+        };
+        let amount = amount.unwrap_or(available);
+        if amount > available || amount == 0 {
+            return Err(Error::InsufficientFunds {
+                needed: amount.max(1) as u64,
+                available: available as u64,
+            });
+        }
+        let operation = Operation::Payment {
+            destination,
+            asset: parse_asset(asset)?,
+            amount,
+        };
+        let envelope = self.sign_operations(index, source.sequence()?, vec![operation])?;
+        let txid = client.submit(&envelope).await?;
+        let token_data = TokenData {
+            balance: U256::from(amount),
+            balance_f: amount as f64 / 10f64.powi(STELLAR_DECIMALS as i32),
+            decimals: STELLAR_DECIMALS,
+            symbol: asset.split(':').next().unwrap_or_default().to_owned(),
+            address: asset.to_owned(),
+        };
+        Ok((TransferReceipt::Broadcast { txid }, token_data))
+    }
+
+    async fn xlm_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let client = HorizonClient::new(provider);
+        let source = self.source_account(&client, index).await?;
+        let balance = match source.balance_of("native") {
+            Some(balance) => balance.stroops()?,
+            None => 0,
+        };
+        let reserve = (2 + source.subentry_count as i64) * BASE_RESERVE;
+        let needed = reserve + BASE_FEE as i64;
+        if balance <= needed {
+            return Err(Error::InsufficientFunds {
+                needed: needed as u64 + 1,
+                available: balance as u64,
+            });
+        }
+        let amount = balance - needed;
+        let receipt = self.xlm_transfer(index, to, amount, provider).await?;
+        Ok((receipt, U256::from(amount)))
+    }
+}
+
+/// Asset of a Horizon `CODE:ISSUER` identifier.
+pub fn parse_asset(asset: &str) -> Result<Asset, Error> {
+    let (code, issuer) = asset
+        .split_once(':')
+        .ok_or_else(|| Error::StellarAssetError(asset.to_owned()))?;
+    Ok(Asset::Credit {
+        code: code.to_owned(),
+        issuer: strkey::decode(VERSION_ACCOUNT_ID, issuer)?,
+    })
+}
+
+#[async_trait]
+impl Wallet for StellarWallet {
+    fn address(&self, index: u32) -> Result<String, Error> {
+        Ok(strkey::encode(VERSION_ACCOUNT_ID, &self.public_key(index)?))
+    }
+    fn public(&self, index: u32) -> Result<String, Error> {
+        self.address(index)
+    }
+    fn private(&self, index: u32) -> Result<String, Error> {
+        Ok(strkey::encode(
+            VERSION_SEED,
+            &self.signing_key(index)?.to_bytes(),
+        ))
+    }
+    fn keypair(&self, index: u32) -> Result<(String, String), Error> {
+        Ok((self.private(index)?, self.public(index)?))
+    }
+    async fn balance(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        self.xlm_balance_by_index(index, "native", provider).await
+    }
+    async fn balance_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        provider: &str,
+    ) -> Result<U256, Error> {
+        self.xlm_balance_by_index(index, token_address, provider)
+            .await
+    }
+    async fn transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.xlm_transfer(index, to, amount_as(amount)?, provider)
+            .await
+    }
+    async fn transfer_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let (receipt, _) = self
+            .asset_transfer(index, token_address, to, Some(amount_as(amount)?), provider)
+            .await?;
+        Ok(receipt)
+    }
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        self.xlm_sweep(index, to, provider).await
+    }
+    async fn sweep_token(
+        &self,
+        index: u32,
+        token_address: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        self.asset_transfer(index, token_address, to, None, provider)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+    use bip39::{Language, Mnemonic};
+    use ed25519_dalek::{Signature, Verifier};
+
+    // SEP-0005 test 1
+    const PHRASE: &str = "illness spike retreat truth genius clock brain pass fit cave bargain toe";
+
+    fn wallet() -> StellarWallet {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        StellarWallet::from_seed(HDSeed { mnemonic })
+    }
+
+    fn account_json(id: &str, sequence: i64, subentries: u32, balances: &str) -> String {
+        format!(
+            r#"{{"account_id":"{}","sequence":"{}","subentry_count":{},"balances":[{}]}}"#,
+            id, sequence, subentries, balances
+        )
+    }
+
+    fn submitted_envelope(server: &TestServer) -> Vec<u8> {
+        let submit = server.requests().pop().unwrap();
+        assert_eq!(submit.path, "/transactions");
+        let (_, tx) = url::form_urlencoded::parse(submit.body.as_bytes())
+            .find(|(key, _)| key == "tx")
+            .unwrap();
+        BASE64.decode(tx.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_sep5_keys() {
+        let wallet = wallet();
+        assert_eq!(
+            wallet.address(0).unwrap(),
+            "GDRXE2BQUC3AZNPVFSCEZ76NJ3WWL25FYFK6RGZGIEKWE4SOOHSUJUJ6"
+        );
+        assert_eq!(
+            wallet.private(0).unwrap(),
+            "SBGWSG6BTNCKCOB3DIFBGCVMUPQFYPA2G4O34RMTB343OYPXU5DJDVMN"
+        );
+        assert_eq!(
+            wallet.address(1).unwrap(),
+            "GBAW5XGWORWVFE2XTJYDTLDHXTY2Q2MO73HYCGB3XMFMQ562Q2W2GJQX"
+        );
+    }
+
+    #[test]
+    fn test_strkey_checksum() {
+        let address = "GDRXE2BQUC3AZNPVFSCEZ76NJ3WWL25FYFK6RGZGIEKWE4SOOHSUJUJ6";
+        let key = strkey::decode(VERSION_ACCOUNT_ID, address).unwrap();
+        assert_eq!(strkey::encode(VERSION_ACCOUNT_ID, &key), address);
+        let corrupted = address.replace("UJUJ6", "UJUJ7");
+        assert!(strkey::decode(VERSION_ACCOUNT_ID, &corrupted).is_err());
+        assert!(strkey::decode(VERSION_SEED, address).is_err());
+    }
+
+    #[test]
+    fn test_parse_stroops() {
+        assert_eq!(horizon::parse_stroops("10.5000000").unwrap(), 105_000_000);
+        assert_eq!(horizon::parse_stroops("0.0000001").unwrap(), 1);
+        assert_eq!(horizon::parse_stroops("7").unwrap(), 70_000_000);
+        assert!(horizon::parse_stroops("1.00000001").is_err());
+    }
+
+    #[test]
+    fn test_sign_payment() {
+        let wallet = wallet();
+        let destination = wallet.public_key(1).unwrap();
+        let asset =
+            parse_asset("USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN").unwrap();
+        let operation = Operation::Payment {
+            destination,
+            asset,
+            amount: 12_345_678,
+        };
+        let envelope = wallet
+            .sign_operations(0, 41, vec![operation.clone()])
+            .unwrap();
+
+        let tx = Transaction {
+            source: wallet.public_key(0).unwrap(),
+            fee: BASE_FEE,
+            sequence: 42,
+            operations: vec![operation],
+        };
+        let tx_xdr = tx.to_xdr().unwrap();
+        assert_eq!(&envelope[4..4 + tx_xdr.len()], tx_xdr.as_slice());
+        let signature: [u8; 64] = envelope[envelope.len() - 64..].try_into().unwrap();
+        wallet
+            .signing_key(0)
+            .unwrap()
+            .verifying_key()
+            .verify(
+                &tx.hash(PUBLIC_NETWORK_PASSPHRASE).unwrap(),
+                &Signature::from_bytes(&signature),
+            )
+            .unwrap();
+        assert_eq!(BASE64.encode(&envelope), "AAAAAgAAAADjcmgwoLYMtfUshEz/zU7tZeulwVXomyZBFWJyTnHlRAAAAGQAAAAAAAAAKgAAAAAAAAAAAAAAAQAAAAAAAAABAAAAAEFu3NZ0bVKTV5pwOaxnvPGoaY7+z4EYO7sKyHfahq2jAAAAAVVTREMAAAAAO5kROA7+mIugqJAOsc/kTzZvfb6Ua+0HckD39iTfFcUAAAAAALxhTgAAAAAAAAABTnHlRAAAAEDq4gmxvIgBSaFclE7VTmsdJ3HMOCbmjshp+yPAwYTKmdqup0PukZw9fe4e4W7cOHR6trrIp0t16G08ehq/0nQD");
+    }
+
+    #[tokio::test]
+    async fn test_transfer_creates_unfunded_account() {
+        let wallet = wallet();
+        let source = wallet.address(0).unwrap();
+        let to = wallet.address(1).unwrap();
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!("/accounts/{}", source),
+                &account_json(
+                    &source,
+                    100,
+                    0,
+                    r#"{"balance":"50.0000000","asset_type":"native"}"#,
+                ),
+            ),
+            Route::post("/transactions", r#"{"hash":"abcd"}"#),
+        ])
+        .await;
+
+        let receipt = wallet
+            .transfer(0, &to, U256::from(20_000_000), &server.url)
+            .await
+            .unwrap();
+        assert_eq!(receipt.txid(), "abcd");
+        let expected = wallet
+            .sign_operations(
+                0,
+                100,
+                vec![Operation::CreateAccount {
+                    destination: wallet.public_key(1).unwrap(),
+                    starting_balance: 20_000_000,
+                }],
+            )
+            .unwrap();
+        assert_eq!(submitted_envelope(&server), expected);
+    }
+
+    #[tokio::test]
+    async fn test_sweep_keeps_reserve() {
+        let wallet = wallet();
+        let source = wallet.address(0).unwrap();
+        let to = wallet.address(1).unwrap();
+        let native = r#"{"balance":"100.0000000","asset_type":"native"}"#;
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!("/accounts/{}", source),
+                &account_json(&source, 7, 1, native),
+            ),
+            Route::get(
+                &format!("/accounts/{}", to),
+                &account_json(&to, 1, 0, native),
+            ),
+            Route::post("/transactions", r#"{"hash":"beef"}"#),
+        ])
+        .await;
+
+        let (_, amount) = wallet.sweep(0, &to, &server.url).await.unwrap();
+        // three reserves for the account and its trustline, and the fee stay behind
+        let expected_amount = 1_000_000_000 - 3 * BASE_RESERVE - BASE_FEE as i64;
+        assert_eq!(amount, U256::from(expected_amount));
+        let expected = wallet
+            .sign_operations(
+                0,
+                7,
+                vec![Operation::Payment {
+                    destination: wallet.public_key(1).unwrap(),
+                    asset: Asset::Native,
+                    amount: expected_amount,
+                }],
+            )
+            .unwrap();
+        assert_eq!(submitted_envelope(&server), expected);
+    }
+
+    #[tokio::test]
+    async fn test_asset_transfer_requires_trustline() {
+        let wallet = wallet();
+        let source = wallet.address(0).unwrap();
+        let to = wallet.address(1).unwrap();
+        let asset = "USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!("/accounts/{}", source),
+                &account_json(
+                    &source,
+                    7,
+                    1,
+                    r#"{"balance":"5.0000000","asset_type":"credit_alphanum4","asset_code":"USDC","asset_issuer":"GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN"}"#,
+                ),
+            ),
+            Route::get(
+                &format!("/accounts/{}", to),
+                &account_json(&to, 1, 0, r#"{"balance":"1.0000000","asset_type":"native"}"#),
+            ),
+        ])
+        .await;
+
+        assert_eq!(
+            wallet.balance_token(0, asset, &server.url).await.unwrap(),
+            U256::from(50_000_000)
+        );
+        assert!(matches!(
+            wallet.sweep_token(0, asset, &to, &server.url).await,
+            Err(Error::StellarNoTrustline(_))
+        ));
+    }
+}
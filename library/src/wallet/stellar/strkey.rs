@@ -0,0 +1,83 @@
+//! StrKey encoding of Stellar keys: base32 of a version byte, the payload and a
+//! CRC16-XModem checksum.
+
+use crate::error::Error;
+
+/// Version byte of account ids (`G...`).
+pub const VERSION_ACCOUNT_ID: u8 = 6 << 3;
+/// Version byte of ed25519 secret seeds (`S...`).
+pub const VERSION_SEED: u8 = 18 << 3;
+
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(5) * 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in data {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for c in s.bytes() {
+        let value = ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+/// StrKey of a 32-byte `payload` with `version`.
+pub fn encode(version: u8, payload: &[u8; 32]) -> String {
+    let mut data = Vec::with_capacity(35);
+    data.push(version);
+    data.extend_from_slice(payload);
+    data.extend_from_slice(&crc16_xmodem(&data).to_le_bytes());
+    base32_encode(&data)
+}
+
+/// Payload of a StrKey with `version`.
+pub fn decode(version: u8, s: &str) -> Result<[u8; 32], Error> {
+    let invalid = || Error::StellarAddressError(s.to_owned());
+    let data = base32_decode(s).ok_or_else(invalid)?;
+    if data.len() != 35 || data[0] != version {
+        return Err(invalid());
+    }
+    let checksum = u16::from_le_bytes([data[33], data[34]]);
+    if crc16_xmodem(&data[..33]) != checksum {
+        return Err(invalid());
+    }
+    let mut payload = [0u8; 32];
+    payload.copy_from_slice(&data[1..33]);
+    Ok(payload)
+}
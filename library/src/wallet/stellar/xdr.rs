@@ -0,0 +1,166 @@
+//! XDR encoding of the payment transactions a `StellarWallet` signs.
+
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+
+pub const PUBLIC_NETWORK_PASSPHRASE: &str = "Public Global Stellar Network ; September 2015";
+pub const TESTNET_NETWORK_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+const ENVELOPE_TYPE_TX: u32 = 2;
+const KEY_TYPE_ED25519: u32 = 0;
+const MEMO_NONE: u32 = 0;
+const PRECOND_NONE: u32 = 0;
+const CREATE_ACCOUNT: u32 = 0;
+const PAYMENT: u32 = 1;
+
+#[derive(Debug, Default)]
+struct XdrWriter(Vec<u8>);
+
+impl XdrWriter {
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    /// Fixed-length opaque data, zero padded to a multiple of 4 bytes.
+    fn opaque(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(data);
+        self.0.resize(self.0.len() + (4 - data.len() % 4) % 4, 0);
+    }
+
+    fn var_opaque(&mut self, data: &[u8]) {
+        self.u32(data.len() as u32);
+        self.opaque(data);
+    }
+
+    fn account_id(&mut self, key: &[u8; 32]) {
+        self.u32(KEY_TYPE_ED25519);
+        self.opaque(key);
+    }
+}
+
+/// Asset of a payment: lumens or a credit identified by code and issuer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Asset {
+    Native,
+    Credit { code: String, issuer: [u8; 32] },
+}
+
+impl Asset {
+    fn write(&self, w: &mut XdrWriter) -> Result<(), Error> {
+        match self {
+            Asset::Native => w.u32(0),
+            Asset::Credit { code, issuer } => {
+                let (kind, len) = match code.len() {
+                    1..=4 => (1, 4),
+                    5..=12 => (2, 12),
+                    _ => return Err(Error::StellarAssetError(code.clone())),
+                };
+                let mut padded = code.as_bytes().to_vec();
+                padded.resize(len, 0);
+                w.u32(kind);
+                w.opaque(&padded);
+                w.account_id(issuer);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    /// Funds a new account with `starting_balance` stroops.
+    CreateAccount {
+        destination: [u8; 32],
+        starting_balance: i64,
+    },
+    Payment {
+        destination: [u8; 32],
+        asset: Asset,
+        amount: i64,
+    },
+}
+
+impl Operation {
+    fn write(&self, w: &mut XdrWriter) -> Result<(), Error> {
+        // no per-operation source account
+        w.u32(0);
+        match self {
+            Operation::CreateAccount {
+                destination,
+                starting_balance,
+            } => {
+                w.u32(CREATE_ACCOUNT);
+                w.account_id(destination);
+                w.i64(*starting_balance);
+            }
+            Operation::Payment {
+                destination,
+                asset,
+                amount,
+            } => {
+                w.u32(PAYMENT);
+                w.account_id(destination);
+                asset.write(w)?;
+                w.i64(*amount);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A transaction without memo or time bounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transaction {
+    pub source: [u8; 32],
+    /// Total fee in stroops.
+    pub fee: u32,
+    pub sequence: i64,
+    pub operations: Vec<Operation>,
+}
+
+impl Transaction {
+    pub fn to_xdr(&self) -> Result<Vec<u8>, Error> {
+        let mut w = XdrWriter::default();
+        w.account_id(&self.source);
+        w.u32(self.fee);
+        w.i64(self.sequence);
+        w.u32(PRECOND_NONE);
+        w.u32(MEMO_NONE);
+        w.u32(self.operations.len() as u32);
+        for op in &self.operations {
+            op.write(&mut w)?;
+        }
+        // ext
+        w.u32(0);
+        Ok(w.0)
+    }
+
+    /// Hash signed by the transaction's signers on the network of `passphrase`.
+    pub fn hash(&self, passphrase: &str) -> Result<[u8; 32], Error> {
+        let mut payload = Sha256::digest(passphrase.as_bytes()).to_vec();
+        payload.extend_from_slice(&ENVELOPE_TYPE_TX.to_be_bytes());
+        payload.extend(self.to_xdr()?);
+        Ok(Sha256::digest(&payload).into())
+    }
+
+    /// Signs the transaction and returns the XDR of its `TransactionV1Envelope`.
+    pub fn sign(&self, key: &SigningKey, passphrase: &str) -> Result<Vec<u8>, Error> {
+        let signature = key.sign(&self.hash(passphrase)?).to_bytes();
+        let public_key = key.verifying_key().to_bytes();
+
+        let mut w = XdrWriter::default();
+        w.u32(ENVELOPE_TYPE_TX);
+        w.0.extend(self.to_xdr()?);
+        w.u32(1);
+        // signature hint: last four bytes of the public key
+        w.opaque(&public_key[28..]);
+        w.var_opaque(&signature);
+        Ok(w.0)
+    }
+}
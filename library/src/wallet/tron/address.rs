@@ -1,10 +1,10 @@
-use std::str::FromStr;
-
-use bitcoin::{base58, bip32::Xpub, PublicKey};
+use bitcoin::{base58, bip32::Xpub};
 use serde::{Deserialize, Serialize};
-use sha256::digest;
 
-use crate::{error::Error, utils::key::keccak_hash};
+use crate::{error::Error, wallet::ethereum::address::pubk_to_addr_bytes};
+
+/// Version byte prepended to every Tron mainnet account id.
+pub const TRON_ADDRESS_PREFIX: u8 = 0x41;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TronAddr(String);
@@ -36,32 +36,47 @@ impl TronAddr {
         //checksum and return
         Ok(Self(proper_addr))
     }
+
+    /// Builds a Base58Check address from the 20-byte account id.
+    pub fn from_bytes(addr: &[u8; 20]) -> Self {
+        let mut payload = [0u8; 21];
+        payload[0] = TRON_ADDRESS_PREFIX;
+        payload[1..].copy_from_slice(addr);
+        // encode_check appends the first 4 bytes of the double SHA-256
+        Self(base58::encode_check(&payload))
+    }
+
     pub fn get(&self) -> &str {
         &self.0
     }
 }
 
-pub fn extended_pubk_to_addr_tron(pubk: &Xpub) -> Result<TronAddr, Error> {
-    //massage into the right format
-    let pubk_str = pubk.public_key.to_string();
-    let pubk_secp = secp256k1::PublicKey::from_str(&pubk_str)?;
-    //format as uncompressed key, remove "04" in the beginning
-    let pubk_uncomp = &PublicKey::new_uncompressed(pubk_secp).to_string()[2..];
-    //decode from hex and pass to keccak for hashing
-    let pubk_bytes = hex::decode(pubk_uncomp)?;
-    let k_addr = &keccak_hash(&pubk_bytes);
-    //keep last 20 bytes of the result
-    let experimental_addr = "41".to_owned() + &k_addr[24..];
-    let hex_exp_addr = hex::decode(&experimental_addr)?;
-    let s_hex_exp_addr = hex_exp_addr.as_slice();
-    let val0 = digest(s_hex_exp_addr);
-    let hex_val0 = hex::decode(val0)?;
-    let s_hex_val0 = hex_val0.as_slice();
-    let val1 = digest(s_hex_val0);
-    let check_sum_val1 = &val1[0..8];
-    let final_addr = experimental_addr + check_sum_val1;
-    let final_addr_bytes = hex::decode(final_addr)?;
+/// Hex form (`41…`) of a Base58Check address, as the TRON HTTP API expects it.
+pub fn tron_address_to_hex(addr: &str) -> Result<String, Error> {
+    let decoded = base58::decode_check(addr).map_err(|_| Error::TronAddrDecodingError)?;
+    if decoded.len() != 21 || decoded[0] != TRON_ADDRESS_PREFIX {
+        return Err(Error::TronAddrLengthError);
+    }
+    Ok(hex::encode(decoded))
+}
+
+/// Bytes of a hex address `41…`.
+pub fn tron_hex_to_bytes(addr: &str) -> Result<[u8; 21], Error> {
+    let decoded = hex::decode(addr).map_err(|_| Error::TronAddrDecodingError)?;
+    match <[u8; 21]>::try_from(decoded) {
+        Ok(bytes) if bytes[0] == TRON_ADDRESS_PREFIX => Ok(bytes),
+        _ => Err(Error::TronAddrLengthError),
+    }
+}
 
-    let addr = &base58::encode(&final_addr_bytes);
-    TronAddr::new(addr)
+/// Base58Check form of a hex address `41…`.
+pub fn tron_hex_to_address(addr: &str) -> Result<String, Error> {
+    let bytes = tron_hex_to_bytes(addr)?;
+    let mut account = [0u8; 20];
+    account.copy_from_slice(&bytes[1..]);
+    Ok(TronAddr::from_bytes(&account).get().to_owned())
+}
+
+pub fn extended_pubk_to_addr_tron(pubk: &Xpub) -> Result<TronAddr, Error> {
+    Ok(TronAddr::from_bytes(&pubk_to_addr_bytes(&pubk.public_key)))
 }
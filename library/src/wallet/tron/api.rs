@@ -0,0 +1,915 @@
+//! Client for the HTTP API of TRON full nodes and TronGrid.
+//!
+//! Addresses are exchanged in their hex form (`41…`), the API's default.
+
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+/// A transaction as the API returns and accepts it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    #[serde(rename = "txID")]
+    pub tx_id: String,
+    pub raw_data: Value,
+    /// Protobuf encoding of `raw_data`, the bytes the signatures commit to.
+    pub raw_data_hex: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signature: Vec<String>,
+    #[serde(default)]
+    pub visible: bool,
+}
+
+/// Outcome of a smart contract call, as the TVM reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContractResult {
+    Default,
+    Success,
+    Revert,
+    OutOfEnergy,
+    OutOfTime,
+    TransferFailed,
+    #[serde(other)]
+    Other,
+}
+
+/// Resources a transaction consumed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ResourceReceipt {
+    #[serde(default)]
+    pub energy_usage_total: u64,
+    #[serde(default)]
+    pub net_usage: u64,
+    /// Set for smart contract calls only.
+    #[serde(default)]
+    pub result: Option<ContractResult>,
+}
+
+/// Execution result of a transaction included in a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionInfo {
+    #[serde(default)]
+    pub id: String,
+    /// Total fee burnt, in sun.
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default)]
+    pub block_number: u64,
+    #[serde(default)]
+    pub receipt: ResourceReceipt,
+    /// `FAILED` for failed transactions, unset otherwise.
+    #[serde(default)]
+    pub result: Option<String>,
+    /// Hex encoded reason of a failure.
+    #[serde(default)]
+    pub res_message: Option<String>,
+    /// Hex encoded return data of contract calls.
+    #[serde(default)]
+    pub contract_result: Vec<String>,
+}
+
+/// Account state, from `getaccount`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Account {
+    /// Hex address, `41…`.
+    #[serde(default)]
+    pub address: String,
+    /// TRX balance, in sun.
+    #[serde(default)]
+    pub balance: u64,
+    /// Milliseconds since the epoch the account was activated at.
+    #[serde(default)]
+    pub create_time: u64,
+    /// TRX staked under Stake 2.0, by resource.
+    #[serde(rename = "frozenV2", default)]
+    pub frozen_v2: Vec<FrozenV2>,
+    /// TRX being unstaked, withdrawable once expired.
+    #[serde(rename = "unfrozenV2", default)]
+    pub unfrozen_v2: Vec<UnfrozenV2>,
+    /// TRC-10 balances, by asset id.
+    #[serde(rename = "assetV2", default)]
+    pub asset_v2: Vec<AssetBalance>,
+    /// Keys allowed to sign for the account, the account's own key when unset.
+    #[serde(default)]
+    pub owner_permission: Option<Permission>,
+    #[serde(default)]
+    pub active_permission: Vec<Permission>,
+}
+
+impl Account {
+    /// Balance of the TRC-10 asset `id`.
+    pub fn asset(&self, id: &str) -> u64 {
+        self.asset_v2
+            .iter()
+            .find(|asset| asset.key == id)
+            .map_or(0, |asset| asset.value)
+    }
+
+    /// TRX staked for `resource`, in sun, delegated stakes excluded.
+    pub fn frozen(&self, resource: Resource) -> u64 {
+        self.frozen_v2
+            .iter()
+            .filter(|frozen| frozen.resource == resource)
+            .map(|frozen| frozen.amount)
+            .sum()
+    }
+
+    /// TRX of expired unstakes, withdrawable at `now`, in milliseconds since the epoch.
+    pub fn withdrawable(&self, now: u64) -> u64 {
+        self.unfrozen_v2
+            .iter()
+            .filter(|unfrozen| unfrozen.unfreeze_expire_time <= now)
+            .map(|unfrozen| unfrozen.unfreeze_amount)
+            .sum()
+    }
+}
+
+/// Kind of an account permission.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PermissionType {
+    /// Can do anything, including changing the permissions. Id 0.
+    #[default]
+    Owner,
+    /// Produces blocks, for super representatives. Id 1.
+    Witness,
+    /// Can do the operations it lists. Ids from 2.
+    Active,
+}
+
+/// A key of a permission and the weight of its signatures.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionKey {
+    /// Hex address, `41…`.
+    pub address: String,
+    pub weight: u64,
+}
+
+/// Keys allowed to sign transactions for an account, with the `threshold` their
+/// summed weights have to reach.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permission {
+    #[serde(rename = "type", default)]
+    pub permission_type: PermissionType,
+    /// The `Permission_id` transactions signed under the permission carry.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub id: u32,
+    #[serde(default)]
+    pub permission_name: String,
+    #[serde(default)]
+    pub threshold: u64,
+    /// Bitmask of the contract types an active permission allows, 32 bytes as hex.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub operations: String,
+    #[serde(default)]
+    pub keys: Vec<PermissionKey>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// Weight the signatures of a transaction carry, from `getsignweight`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SignWeight {
+    /// The permission the transaction is signed under.
+    #[serde(default)]
+    pub permission: Option<Permission>,
+    /// Hex addresses of the signers.
+    #[serde(default)]
+    pub approved_list: Vec<String>,
+    #[serde(default)]
+    pub current_weight: u64,
+    #[serde(default)]
+    pub result: SignWeightResult,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SignWeightResult {
+    /// `ENOUGH_PERMISSION`, `NOT_ENOUGH_PERMISSION` or the reason the signatures are invalid.
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl SignWeight {
+    /// Whether the signatures reach the threshold of the permission.
+    pub fn enough(&self) -> bool {
+        self.result.code == "ENOUGH_PERMISSION"
+    }
+
+    pub fn threshold(&self) -> u64 {
+        self.permission
+            .as_ref()
+            .map_or(0, |permission| permission.threshold)
+    }
+}
+
+/// Balance of a TRC-10 asset, in its smallest unit.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AssetBalance {
+    /// Asset id, e.g. `1002000`.
+    pub key: String,
+    #[serde(default)]
+    pub value: u64,
+}
+
+/// A TRC-10 asset, from `getassetissuebyid`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AssetIssue {
+    #[serde(default)]
+    pub id: String,
+    /// Hex address of the issuer.
+    #[serde(default)]
+    pub owner_address: String,
+    /// Name and ticker, hex encoded UTF-8.
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub abbr: String,
+    /// Decimals of the asset, 0 when unset.
+    #[serde(default)]
+    pub precision: u8,
+    #[serde(default)]
+    pub total_supply: u64,
+}
+
+impl AssetIssue {
+    /// Ticker of the asset, its name if it has none.
+    pub fn symbol(&self) -> String {
+        if self.abbr.is_empty() {
+            decode_message(&self.name)
+        } else {
+            decode_message(&self.abbr)
+        }
+    }
+}
+
+/// Resources TRX is staked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Resource {
+    Bandwidth,
+    Energy,
+    /// Voting power, staked alongside the other resources by older accounts.
+    TronPower,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Bandwidth => write!(f, "BANDWIDTH"),
+            Resource::Energy => write!(f, "ENERGY"),
+            Resource::TronPower => write!(f, "TRON_POWER"),
+        }
+    }
+}
+
+impl FromStr for Resource {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bandwidth" | "net" => Ok(Resource::Bandwidth),
+            "energy" => Ok(Resource::Energy),
+            _ => Err("Unknown resource, expected bandwidth or energy"),
+        }
+    }
+}
+
+/// Stake of an account. Bandwidth stakes come without a type, the protobuf default.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FrozenV2 {
+    #[serde(rename = "type", default = "bandwidth")]
+    pub resource: Resource,
+    #[serde(default)]
+    pub amount: u64,
+}
+
+/// Pending unstake of an account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnfrozenV2 {
+    #[serde(rename = "type", default = "bandwidth")]
+    pub resource: Resource,
+    #[serde(default)]
+    pub unfreeze_amount: u64,
+    /// Milliseconds since the epoch the TRX is withdrawable from.
+    #[serde(default)]
+    pub unfreeze_expire_time: u64,
+}
+
+fn bandwidth() -> Resource {
+    Resource::Bandwidth
+}
+
+/// Resources one account delegates to another, from `getdelegatedresourcev2`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct DelegatedResource {
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub to: String,
+    /// TRX staked for the delegated bandwidth, in sun.
+    #[serde(default)]
+    pub frozen_balance_for_bandwidth: u64,
+    /// TRX staked for the delegated energy, in sun.
+    #[serde(default)]
+    pub frozen_balance_for_energy: u64,
+    /// End of the lock of the delegation, in milliseconds since the epoch.
+    #[serde(default)]
+    pub expire_time_for_bandwidth: u64,
+    #[serde(default)]
+    pub expire_time_for_energy: u64,
+}
+
+impl DelegatedResource {
+    pub fn frozen_balance(&self, resource: Resource) -> u64 {
+        match resource {
+            Resource::Bandwidth => self.frozen_balance_for_bandwidth,
+            Resource::Energy => self.frozen_balance_for_energy,
+            Resource::TronPower => 0,
+        }
+    }
+}
+
+/// Accounts delegating to and receiving from an account, from
+/// `getdelegatedresourceaccountindexv2`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationIndex {
+    #[serde(default)]
+    pub from_accounts: Vec<String>,
+    #[serde(default)]
+    pub to_accounts: Vec<String>,
+}
+
+/// Bandwidth and energy of an account, from `getaccountresource`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct AccountResource {
+    #[serde(rename = "freeNetUsed", default)]
+    pub free_net_used: u64,
+    #[serde(rename = "freeNetLimit", default)]
+    pub free_net_limit: u64,
+    #[serde(rename = "NetUsed", default)]
+    pub net_used: u64,
+    #[serde(rename = "NetLimit", default)]
+    pub net_limit: u64,
+    #[serde(rename = "EnergyUsed", default)]
+    pub energy_used: u64,
+    #[serde(rename = "EnergyLimit", default)]
+    pub energy_limit: u64,
+    #[serde(rename = "TotalNetLimit", default)]
+    pub total_net_limit: u64,
+    #[serde(rename = "TotalNetWeight", default)]
+    pub total_net_weight: u64,
+    #[serde(rename = "TotalEnergyLimit", default)]
+    pub total_energy_limit: u64,
+    #[serde(rename = "TotalEnergyWeight", default)]
+    pub total_energy_weight: u64,
+}
+
+impl AccountResource {
+    /// Bandwidth left today, free and staked.
+    pub fn bandwidth(&self) -> u64 {
+        self.free_bandwidth() + self.staked_bandwidth()
+    }
+
+    /// Free bandwidth left of the daily allowance.
+    pub fn free_bandwidth(&self) -> u64 {
+        self.free_net_limit.saturating_sub(self.free_net_used)
+    }
+
+    /// Bandwidth left from staked TRX.
+    pub fn staked_bandwidth(&self) -> u64 {
+        self.net_limit.saturating_sub(self.net_used)
+    }
+
+    /// Energy left today.
+    pub fn energy(&self) -> u64 {
+        self.energy_limit.saturating_sub(self.energy_used)
+    }
+}
+
+/// Result of a read-only contract call.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstantResult {
+    /// Return data of the call.
+    pub output: Vec<u8>,
+    /// Energy the call would consume in a transaction.
+    pub energy_used: u64,
+}
+
+/// Number and id of a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub id: String,
+    pub number: u64,
+    /// Milliseconds since the epoch.
+    pub timestamp: u64,
+}
+
+/// Price of energy in sun when the chain doesn't report it.
+pub const DEFAULT_ENERGY_FEE: u64 = 210;
+/// Price of a byte of bandwidth in sun when the chain doesn't report it.
+pub const DEFAULT_TRANSACTION_FEE: u64 = 1000;
+/// Fee of activating an account in sun when the chain doesn't report it.
+pub const DEFAULT_CREATE_NEW_ACCOUNT_FEE: u64 = 1_000_000;
+/// Sun burnt for the bandwidth of an activation when the chain doesn't report it.
+pub const DEFAULT_CREATE_ACCOUNT_FEE: u64 = 100_000;
+
+/// Network parameters set by committee proposals, from `getchainparameters`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainParameters(pub HashMap<String, i64>);
+
+impl ChainParameters {
+    pub fn get(&self, key: &str) -> Option<i64> {
+        self.0.get(key).copied()
+    }
+
+    /// Sun burnt per unit of energy.
+    pub fn energy_fee(&self) -> u64 {
+        self.get("getEnergyFee")
+            .map_or(DEFAULT_ENERGY_FEE, |fee| fee as u64)
+    }
+
+    /// Sun burnt per byte of bandwidth.
+    pub fn transaction_fee(&self) -> u64 {
+        self.get("getTransactionFee")
+            .map_or(DEFAULT_TRANSACTION_FEE, |fee| fee as u64)
+    }
+
+    /// Sun charged for activating an account, on top of the bandwidth.
+    pub fn create_new_account_fee(&self) -> u64 {
+        self.get("getCreateNewAccountFeeInSystemContract")
+            .map_or(DEFAULT_CREATE_NEW_ACCOUNT_FEE, |fee| fee as u64)
+    }
+
+    /// Sun burnt for the bandwidth of an activation staked bandwidth doesn't cover.
+    pub fn create_account_fee(&self) -> u64 {
+        self.get("getCreateAccountFee")
+            .map_or(DEFAULT_CREATE_ACCOUNT_FEE, |fee| fee as u64)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainParameter {
+    key: String,
+    #[serde(default)]
+    value: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BroadcastResponse {
+    #[serde(default)]
+    result: bool,
+    #[serde(default)]
+    code: Option<String>,
+    #[serde(default)]
+    message: Option<String>,
+}
+
+/// Messages of the API are hex encoded text.
+pub(crate) fn decode_message(message: &str) -> String {
+    hex::decode(message)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| message.to_owned())
+}
+
+pub struct TronClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl TronClient {
+    /// Creates a client for the node at `base_url`, e.g. `https://api.trongrid.io`.
+    pub fn new(base_url: &str) -> Self {
+        TronClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value, Error> {
+        let response: Value = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("Error").and_then(Value::as_str) {
+            return Err(Error::TronApiError(error.to_owned()));
+        }
+        Ok(response)
+    }
+
+    /// State of the account at the hex `address`, `None` if it isn't activated.
+    pub async fn get_account(&self, address: &str) -> Result<Option<Account>, Error> {
+        let response = self
+            .post("/wallet/getaccount", json!({ "address": address }))
+            .await?;
+        let account: Account = serde_json::from_value(response)?;
+        Ok((!account.address.is_empty()).then_some(account))
+    }
+
+    /// Whether the account at the hex `address` is activated.
+    pub async fn is_activated(&self, address: &str) -> Result<bool, Error> {
+        Ok(self.get_account(address).await?.is_some())
+    }
+
+    /// Bandwidth and energy of the account at the hex `address`.
+    pub async fn get_account_resource(&self, address: &str) -> Result<AccountResource, Error> {
+        let response = self
+            .post("/wallet/getaccountresource", json!({ "address": address }))
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Read-only call of `function_selector` on the contract at `contract_address`, with
+    /// the hex ABI encoded `parameter`. Fails with the revert message if the call fails.
+    pub async fn trigger_constant_contract(
+        &self,
+        owner_address: &str,
+        contract_address: &str,
+        function_selector: &str,
+        parameter: &str,
+    ) -> Result<ConstantResult, Error> {
+        let response = self
+            .post(
+                "/wallet/triggerconstantcontract",
+                json!({
+                    "owner_address": owner_address,
+                    "contract_address": contract_address,
+                    "function_selector": function_selector,
+                    "parameter": parameter,
+                }),
+            )
+            .await?;
+        if response["result"]["result"] != true {
+            return Err(Error::TronApiError(format!(
+                "{}: {}",
+                response["result"]["code"].as_str().unwrap_or_default(),
+                decode_message(response["result"]["message"].as_str().unwrap_or_default())
+            )));
+        }
+        let output = response["constant_result"][0].as_str().unwrap_or_default();
+        Ok(ConstantResult {
+            output: hex::decode(output)?,
+            energy_used: response["energy_used"].as_u64().unwrap_or_default(),
+        })
+    }
+
+    pub async fn get_chain_parameters(&self) -> Result<ChainParameters, Error> {
+        let response = self.post("/wallet/getchainparameters", json!({})).await?;
+        let parameters: Vec<ChainParameter> =
+            serde_json::from_value(response["chainParameter"].clone())?;
+        Ok(ChainParameters(
+            parameters
+                .into_iter()
+                .map(|parameter| (parameter.key, parameter.value))
+                .collect(),
+        ))
+    }
+
+    /// Header of the latest block.
+    pub async fn get_now_block(&self) -> Result<BlockHeader, Error> {
+        let response = self.post("/wallet/getnowblock", json!({})).await?;
+        let raw_data = &response["block_header"]["raw_data"];
+        Ok(BlockHeader {
+            id: response["blockID"].as_str().unwrap_or_default().to_owned(),
+            number: raw_data["number"].as_u64().unwrap_or_default(),
+            timestamp: raw_data["timestamp"].as_u64().unwrap_or_default(),
+        })
+    }
+
+    /// The TRC-10 asset `id`, `None` if there is none.
+    pub async fn get_asset_issue_by_id(&self, id: &str) -> Result<Option<AssetIssue>, Error> {
+        let response = self
+            .post("/wallet/getassetissuebyid", json!({ "value": id }))
+            .await?;
+        let asset: AssetIssue = serde_json::from_value(response)?;
+        Ok((!asset.id.is_empty()).then_some(asset))
+    }
+
+    /// Unsigned `TransferAssetContract` of `amount` of the TRC-10 asset `id` between hex
+    /// addresses.
+    pub async fn transfer_asset(
+        &self,
+        owner_address: &str,
+        to_address: &str,
+        id: &str,
+        amount: u64,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/transferasset",
+                json!({
+                    "owner_address": owner_address,
+                    "to_address": to_address,
+                    "asset_name": hex::encode(id),
+                    "amount": amount,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Weight of the signatures of `tx` under the permission it names.
+    pub async fn get_sign_weight(&self, tx: &Transaction) -> Result<SignWeight, Error> {
+        let response = self
+            .post("/wallet/getsignweight", serde_json::to_value(tx)?)
+            .await?;
+        let mut weight: SignWeight = serde_json::from_value(response)?;
+        weight.result.message = decode_message(&weight.result.message);
+        Ok(weight)
+    }
+
+    /// Unsigned `TransferContract` of `amount` sun between hex addresses.
+    pub async fn create_transaction(
+        &self,
+        owner_address: &str,
+        to_address: &str,
+        amount: u64,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/createtransaction",
+                json!({
+                    "owner_address": owner_address,
+                    "to_address": to_address,
+                    "amount": amount,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `AccountCreateContract` activating `account_address`.
+    pub async fn create_account(
+        &self,
+        owner_address: &str,
+        account_address: &str,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/createaccount",
+                json!({
+                    "owner_address": owner_address,
+                    "account_address": account_address,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `FreezeBalanceV2Contract` staking `amount` sun for `resource`.
+    pub async fn freeze_balance_v2(
+        &self,
+        owner_address: &str,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/freezebalancev2",
+                json!({
+                    "owner_address": owner_address,
+                    "frozen_balance": amount,
+                    "resource": resource,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `UnfreezeBalanceV2Contract` unstaking `amount` sun staked for `resource`.
+    pub async fn unfreeze_balance_v2(
+        &self,
+        owner_address: &str,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/unfreezebalancev2",
+                json!({
+                    "owner_address": owner_address,
+                    "unfreeze_balance": amount,
+                    "resource": resource,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `WithdrawExpireUnfreezeContract` returning expired unstakes to the balance.
+    pub async fn withdraw_expire_unfreeze(
+        &self,
+        owner_address: &str,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/withdrawexpireunfreeze",
+                json!({ "owner_address": owner_address }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `DelegateResourceContract` delegating the `resource` of `amount` staked
+    /// sun to `receiver_address`, locked for `lock_period` blocks if set.
+    pub async fn delegate_resource(
+        &self,
+        owner_address: &str,
+        receiver_address: &str,
+        amount: u64,
+        resource: Resource,
+        lock_period: Option<u64>,
+    ) -> Result<Transaction, Error> {
+        let mut body = json!({
+            "owner_address": owner_address,
+            "receiver_address": receiver_address,
+            "balance": amount,
+            "resource": resource,
+        });
+        if let Some(lock_period) = lock_period {
+            body["lock"] = true.into();
+            body["lock_period"] = lock_period.into();
+        }
+        let response = self.post("/wallet/delegateresource", body).await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Unsigned `UnDelegateResourceContract` taking back what `delegate_resource` gave.
+    pub async fn undelegate_resource(
+        &self,
+        owner_address: &str,
+        receiver_address: &str,
+        amount: u64,
+        resource: Resource,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/undelegateresource",
+                json!({
+                    "owner_address": owner_address,
+                    "receiver_address": receiver_address,
+                    "balance": amount,
+                    "resource": resource,
+                }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Resources `from_address` delegates to `to_address`.
+    pub async fn get_delegated_resource(
+        &self,
+        from_address: &str,
+        to_address: &str,
+    ) -> Result<Vec<DelegatedResource>, Error> {
+        let response = self
+            .post(
+                "/wallet/getdelegatedresourcev2",
+                json!({ "fromAddress": from_address, "toAddress": to_address }),
+            )
+            .await?;
+        match response.get("delegatedResource") {
+            Some(delegated) => Ok(serde_json::from_value(delegated.clone())?),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Accounts `address` delegates to and receives delegations from.
+    pub async fn get_delegation_index(&self, address: &str) -> Result<DelegationIndex, Error> {
+        let response = self
+            .post(
+                "/wallet/getdelegatedresourceaccountindexv2",
+                json!({ "value": address }),
+            )
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Most sun of staked TRX `owner_address` can delegate for `resource`.
+    pub async fn get_can_delegated_max_size(
+        &self,
+        owner_address: &str,
+        resource: Resource,
+    ) -> Result<u64, Error> {
+        let response = self
+            .post(
+                "/wallet/getcandelegatedmaxsize",
+                json!({
+                    "owner_address": owner_address,
+                    "type": (resource == Resource::Energy) as u8,
+                }),
+            )
+            .await?;
+        Ok(response["max_size"].as_u64().unwrap_or_default())
+    }
+
+    /// Unsigned call of `function_selector`, e.g. `transfer(address,uint256)`, on the
+    /// contract at `contract_address`. `parameter` is the hex ABI encoding of the
+    /// arguments; the call burns at most `fee_limit` sun.
+    pub async fn trigger_smart_contract(
+        &self,
+        owner_address: &str,
+        contract_address: &str,
+        function_selector: &str,
+        parameter: &str,
+        fee_limit: u64,
+    ) -> Result<Transaction, Error> {
+        let response = self
+            .post(
+                "/wallet/triggersmartcontract",
+                json!({
+                    "owner_address": owner_address,
+                    "contract_address": contract_address,
+                    "function_selector": function_selector,
+                    "parameter": parameter,
+                    "fee_limit": fee_limit,
+                    "call_value": 0,
+                }),
+            )
+            .await?;
+        if response["result"]["result"] != true {
+            return Err(Error::TronApiError(format!(
+                "{}: {}",
+                response["result"]["code"].as_str().unwrap_or_default(),
+                decode_message(response["result"]["message"].as_str().unwrap_or_default())
+            )));
+        }
+        Ok(serde_json::from_value(response["transaction"].clone())?)
+    }
+
+    /// Broadcasts a signed transaction. Fails with the node's reason if it is rejected.
+    pub async fn broadcast_transaction(&self, tx: &Transaction) -> Result<(), Error> {
+        let response: BroadcastResponse = serde_json::from_value(
+            self.post("/wallet/broadcasttransaction", serde_json::to_value(tx)?)
+                .await?,
+        )?;
+        if response.result {
+            return Ok(());
+        }
+        Err(Error::BroadcastError(format!(
+            "{}: {}",
+            response.code.unwrap_or_default(),
+            decode_message(&response.message.unwrap_or_default())
+        )))
+    }
+
+    /// Broadcasts a signed transaction in its protobuf encoding, as hex.
+    pub async fn broadcast_hex(&self, transaction: &str) -> Result<(), Error> {
+        let response: BroadcastResponse = serde_json::from_value(
+            self.post(
+                "/wallet/broadcasthex",
+                json!({ "transaction": transaction }),
+            )
+            .await?,
+        )?;
+        if response.result {
+            return Ok(());
+        }
+        Err(Error::BroadcastError(format!(
+            "{}: {}",
+            response.code.unwrap_or_default(),
+            decode_message(&response.message.unwrap_or_default())
+        )))
+    }
+
+    /// Result of the transaction `txid`, `None` until it is in a block.
+    pub async fn transaction_info(&self, txid: &str) -> Result<Option<TransactionInfo>, Error> {
+        let response = self
+            .post("/wallet/gettransactioninfobyid", json!({ "value": txid }))
+            .await?;
+        let info: TransactionInfo = serde_json::from_value(response)?;
+        Ok((!info.id.is_empty()).then_some(info))
+    }
+
+    /// Polls `transaction_info` up to `attempts` times, `interval` apart.
+    pub async fn wait_for_transaction(
+        &self,
+        txid: &str,
+        attempts: u32,
+        interval: Duration,
+    ) -> Result<Option<TransactionInfo>, Error> {
+        for attempt in 0..attempts {
+            if attempt > 0 {
+                tokio::time::sleep(interval).await;
+            }
+            if let Some(info) = self.transaction_info(txid).await? {
+                return Ok(Some(info));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,102 @@
+//! Message signing, as TronLink and TronWeb's `signMessageV2` do it (TIP-191).
+//!
+//! The signed hash is the Keccak-256 of the message behind a prefix and its length in
+//! bytes, so a signed message can't pass for a transaction. Signatures are `r || s || v`
+//! in hex with `0x`, `v` being 27 or 28.
+
+use std::str::FromStr;
+
+use ethers::{
+    types::{Signature, H256},
+    utils::keccak256,
+};
+
+use crate::error::Error;
+
+use super::{address::TronAddr, TronWallet};
+
+pub const MESSAGE_PREFIX: &str = "\x19TRON Signed Message:\n";
+
+/// Hash a signature of `message` signs.
+pub fn hash_message(message: &[u8]) -> H256 {
+    let mut prefixed = format!("{}{}", MESSAGE_PREFIX, message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    H256(keccak256(prefixed))
+}
+
+/// Base58 address of the key that signed `message`, from the hex `signature`.
+pub fn verify_message(message: &[u8], signature: &str) -> Result<String, Error> {
+    let signature = Signature::from_str(signature)?;
+    let signer = signature.recover(hash_message(message))?;
+    Ok(TronAddr::from_bytes(&signer.0).get().to_owned())
+}
+
+impl TronWallet {
+    /// Signs `message` with the key at `index`, returning the hex signature.
+    pub fn sign_message(&self, index: u32, message: &[u8]) -> Result<String, Error> {
+        let signature = self.signer(index)?.sign_hash(hash_message(message))?;
+        Ok(format!("0x{}", signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wallet::{tron::tests::wallet, Wallet};
+
+    #[test]
+    fn test_hash_message() {
+        let mut prefixed = b"\x19TRON Signed Message:\n11".to_vec();
+        prefixed.extend_from_slice(b"hello world");
+        assert_eq!(hash_message(b"hello world").0, keccak256(prefixed));
+        // the length is in bytes
+        assert_ne!(hash_message("é".as_bytes()), hash_message(b"e"));
+    }
+
+    #[test]
+    fn test_known_vector() {
+        // private key 1, address 0x7e5f4552091a69125d5dfcb7b8c2659029395bdf on Ethereum;
+        // signMessageV2 signs with an RFC 6979 nonce and a low `s`, so this is the
+        // signature TronWeb returns for the key and message
+        let key = "0000000000000000000000000000000000000000000000000000000000000001";
+        let message = b"hello world";
+        let signature = "0x0dc0b53d525e0103a6013061cf18e60cf158809149f2b8994a545af65a7004cb1eeaff560e801ab51b28df5d42549aa024c2aa7e9d34de1e01294b9afb5e6c7e1c";
+        assert_eq!(
+            hex::encode(hash_message(message)),
+            "cf02daeb2bea196ed5692322a66ed50080ce74ff8cb711199f1b04f3c13bc10d"
+        );
+        let signer = ethers::signers::LocalWallet::from_str(key).unwrap();
+        assert_eq!(
+            format!("0x{}", signer.sign_hash(hash_message(message)).unwrap()),
+            signature
+        );
+        assert_eq!(
+            verify_message(message, signature).unwrap(),
+            "TMVQGm1qAQYVdetCeGRRkTWYYrLXuHK2HC"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let wallet = wallet();
+        let message = b"Proof of ownership for exchange deposit 1234";
+        let signature = wallet.sign_message(0, message).unwrap();
+        assert!(signature.starts_with("0x"));
+        assert_eq!(signature.len(), 132);
+        assert!(matches!(&signature[130..], "1b" | "1c"));
+
+        assert_eq!(
+            verify_message(message, &signature).unwrap(),
+            wallet.address(0).unwrap()
+        );
+        assert_eq!(
+            verify_message(message, &signature[2..]).unwrap(),
+            wallet.address(0).unwrap()
+        );
+        assert_ne!(
+            verify_message(b"Proof of ownership for exchange deposit 1235", &signature).unwrap(),
+            wallet.address(0).unwrap()
+        );
+        assert!(verify_message(message, "0x1234").is_err());
+    }
+}
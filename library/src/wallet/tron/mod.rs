@@ -1,12 +1,23 @@
 pub mod address;
+pub mod api;
+pub mod message;
+pub mod permission;
+pub mod resources;
+pub mod stake;
+pub mod sweep;
+pub mod token;
+pub mod transaction;
+
+use std::time::Duration;
 
 use async_trait::async_trait;
-use bitcoin::base58;
 use ethers::{
-    providers::{Http, Middleware, Provider},
-    types::{Transaction, TransactionReceipt, U256},
+    abi::{self, ParamType, Token},
+    signers::LocalWallet,
+    types::{H160, H256, U256},
+    utils::id,
 };
-use web3::contract::{Contract, Options};
+use sha2::{Digest, Sha256};
 
 use crate::{
     error::Error,
@@ -14,25 +25,54 @@ use crate::{
         crypto::Crypto,
         hdseed::{FromSeed, HDSeed},
         token_data::TokenData,
+        transfer_receipt::TransferReceipt,
     },
-    utils::{address::address_str_to_h160, key::keypair_by_index},
-    wallet::ethereum::address::extended_pubk_to_addr,
+    utils::{amount::amount_as, key::keypair_by_index},
+    wallet::ethereum::address::{extended_pubk_to_addr, pubk_to_addr_bytes},
 };
 
-use self::address::extended_pubk_to_addr_tron;
+use self::{
+    address::{
+        extended_pubk_to_addr_tron, tron_address_to_hex, tron_hex_to_bytes, TRON_ADDRESS_PREFIX,
+    },
+    api::{decode_message, BlockHeader, ContractResult, Transaction, TransactionInfo, TronClient},
+    resources::ResourceEstimate,
+    token::TronToken,
+    transaction::{Contract, RawTransaction},
+};
 use super::Wallet;
 
+/// Times the wallet asks for the result of a broadcast transaction, and the wait between.
+/// Blocks are produced every three seconds.
+const CONFIRMATION_ATTEMPTS: u32 = 20;
+const CONFIRMATION_INTERVAL: Duration = Duration::from_secs(3);
+
+const TRC20_TRANSFER: &str = "transfer(address,uint256)";
+
+/// Most a TRC-20 transfer may burn when the wallet isn't given a limit, 100 TRX.
+pub const DEFAULT_FEE_LIMIT: u64 = 100_000_000;
+
 pub struct TronWallet {
     pub seed: HDSeed,
+    /// Most a contract call may burn, in sun.
+    pub fee_limit: u64,
 }
 
 impl FromSeed for TronWallet {
     fn from_seed(seed: HDSeed) -> Self {
-        TronWallet { seed }
+        TronWallet {
+            seed,
+            fee_limit: DEFAULT_FEE_LIMIT,
+        }
     }
 }
 
 impl TronWallet {
+    pub fn with_fee_limit(mut self, fee_limit: u64) -> Self {
+        self.fee_limit = fee_limit;
+        self
+    }
+
     pub fn tron_address_by_index(&self, index: u32) -> Result<String, Error> {
         let derivation_path = Crypto::Tron.get_hd_path(index)?;
         let (_, pubk) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
@@ -73,44 +113,472 @@ impl TronWallet {
         ))
     }
 
-    async fn tron_balance_by_index(&self, index: u32, provider_url: &str) -> Result<U256, Error> {
-        let addr = self.tron_hex_address_by_index(index)?;
-        let addr_h160 = address_str_to_h160(&addr)?;
-        let provider = Provider::<Http>::try_from(provider_url)?;
-        let balance = provider.get_balance(addr_h160, None).await?;
-        Ok(balance)
+    /// Hex form (`41…`) of the address at `index`, as the TRON HTTP API expects it.
+    pub fn tron_api_address_by_index(&self, index: u32) -> Result<String, Error> {
+        let derivation_path = Crypto::Tron.get_hd_path(index)?;
+        let (_, pubk) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
+        let mut addr = vec![TRON_ADDRESS_PREFIX];
+        addr.extend_from_slice(&pubk_to_addr_bytes(&pubk.public_key));
+        Ok(hex::encode(addr))
+    }
+
+    /// Signs `tx` with the key at `index`, after checking that its id is the hash of
+    /// its raw data, so the id reported back is the one of the signed transaction.
+    /// The signature is added to the ones `tx` already has, for multi-signature accounts.
+    pub fn sign_transaction(&self, index: u32, tx: &mut Transaction) -> Result<(), Error> {
+        let raw_data = hex::decode(&tx.raw_data_hex)?;
+        let hash: [u8; 32] = Sha256::digest(raw_data).into();
+        if hex::encode(hash) != tx.tx_id.to_lowercase() {
+            return Err(Error::TronTxIdMismatch(tx.tx_id.clone()));
+        }
+        // r || s || v, with v = 27 + recovery id
+        let signature = self.signer(index)?.sign_hash(H256(hash))?;
+        tx.signature.push(hex::encode(signature.to_vec()));
+        Ok(())
+    }
+
+    /// The key at `index`, signing hashes.
+    fn signer(&self, index: u32) -> Result<LocalWallet, Error> {
+        let derivation_path = Crypto::Tron.get_hd_path(index)?;
+        let (privk, _) = keypair_by_index(&self.seed.mnemonic, &derivation_path)?;
+        Ok(LocalWallet::from_bytes(&privk.private_key.secret_bytes())?)
+    }
+
+    /// Unsigned TRX transfer from the address at `index`, built without a node from the
+    /// recent `block`.
+    pub fn build_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        block: &BlockHeader,
+    ) -> Result<RawTransaction, Error> {
+        let contract = Contract::transfer(
+            &self.tron_api_address_by_index(index)?,
+            &tron_address_to_hex(to)?,
+            amount,
+        )?;
+        RawTransaction::new(contract, block)
+    }
+
+    /// Unsigned token transfer from the address at `index`, built without a node from
+    /// the recent `block`. TRC-20 transfers burn at most `fee_limit` sun.
+    pub fn build_transfer_token(
+        &self,
+        index: u32,
+        token: &str,
+        to: &str,
+        amount: U256,
+        block: &BlockHeader,
+    ) -> Result<RawTransaction, Error> {
+        let owner = self.tron_api_address_by_index(index)?;
+        let to = tron_address_to_hex(to)?;
+        match TronToken::from(token) {
+            TronToken::Trc10(id) => {
+                let contract = Contract::trc10_transfer(&owner, &id, &to, amount)?;
+                RawTransaction::new(contract, block)
+            }
+            TronToken::Trc20(address) => {
+                let contract =
+                    Contract::trc20_transfer(&owner, &tron_address_to_hex(&address)?, &to, amount)?;
+                Ok(RawTransaction::new(contract, block)?.fee_limit(self.fee_limit))
+            }
+        }
+    }
+
+    /// Signs `raw` with the key at `index`. The result serializes to the JSON
+    /// `broadcasttransaction` takes, and `transaction::encode_signed` gives its hex.
+    pub fn sign_raw(&self, index: u32, raw: RawTransaction) -> Result<Transaction, Error> {
+        let mut tx = raw.into_transaction();
+        self.sign_transaction(index, &mut tx)?;
+        Ok(tx)
+    }
+
+    /// Signs and broadcasts `tx`, then waits for it to be confirmed.
+    async fn tron_send(
+        &self,
+        index: u32,
+        mut tx: Transaction,
+        client: &TronClient,
+    ) -> Result<TransferReceipt, Error> {
+        self.sign_transaction(index, &mut tx)?;
+        send_signed(tx, client).await
+    }
+
+    async fn tron_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let sun: i64 = amount_as(amount)?;
+        let client = TronClient::new(provider);
+        let to_hex = tron_address_to_hex(to)?;
+        if !client.is_activated(&to_hex).await? {
+            let estimate = self
+                .estimate_transfer_with(index, to, amount, false, &client)
+                .await?;
+            self.check_activation_funded(index, to, amount + estimate.burn(), provider)
+                .await?;
+        }
+        let tx = client
+            .create_transaction(&self.tron_api_address_by_index(index)?, &to_hex, sun as u64)
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Sends the whole TRX balance less what the transfer burns, the activation of `to`
+    /// included if it isn't active yet.
+    async fn tron_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let client = TronClient::new(provider);
+        let to_hex = tron_address_to_hex(to)?;
+        let activated = client.is_activated(&to_hex).await?;
+        let balance = self.tron_balance_by_index(index, provider).await?;
+        // a smaller amount encodes in as many bytes or fewer, so the burn doesn't grow
+        let burn = U256::from(
+            self.estimate_transfer_with(index, to, balance, activated, &client)
+                .await?
+                .burn(),
+        );
+        if balance <= burn {
+            return Err(Error::InsufficientFundsForFee {
+                fee: burn,
+                available: balance,
+            });
+        }
+        let amount = balance - burn;
+        let tx = client
+            .create_transaction(
+                &self.tron_api_address_by_index(index)?,
+                &to_hex,
+                amount.as_u64(),
+            )
+            .await?;
+        Ok((self.tron_send(index, tx, &client).await?, amount))
+    }
+
+    /// Sends the whole balance of the TRC-10 asset or TRC-20 `token`.
+    async fn tron_sweep_token(
+        &self,
+        index: u32,
+        token: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        let token_data = self.token_data(index, token, provider).await?;
+        if token_data.balance.is_zero() {
+            return Err(Error::NothingToSweep);
+        }
+        let receipt = self
+            .tron_transfer_token(index, token, to, token_data.balance, provider)
+            .await?;
+        Ok((receipt, token_data))
+    }
+
+    /// Fails when the address at `index` has less than the `needed` sun to send to, and
+    /// activate, `to`. The node accepts such transfers, and they fail on chain.
+    async fn check_activation_funded(
+        &self,
+        index: u32,
+        to: &str,
+        needed: U256,
+        provider: &str,
+    ) -> Result<(), Error> {
+        let balance = self.tron_balance_by_index(index, provider).await?;
+        if balance < needed {
+            return Err(Error::TronActivationUnfunded {
+                address: to.to_owned(),
+                needed,
+                balance,
+            });
+        }
+        Ok(())
+    }
+
+    /// Activates the account at `address` from the address at `index`, which pays the
+    /// account creation fee. `None` if the account already is active.
+    pub async fn activate(
+        &self,
+        index: u32,
+        address: &str,
+        provider: &str,
+    ) -> Result<Option<TransferReceipt>, Error> {
+        let client = TronClient::new(provider);
+        let account = tron_address_to_hex(address)?;
+        if client.is_activated(&account).await? {
+            return Ok(None);
+        }
+        let tx = client
+            .create_account(&self.tron_api_address_by_index(index)?, &account)
+            .await?;
+        Ok(Some(self.tron_send(index, tx, &client).await?))
+    }
+
+    /// Transfer of the TRC-10 asset or TRC-20 `token`. TRC-20 transfers are
+    /// `transfer(to, amount)` calls of the contract, burning at most `fee_limit` sun.
+    async fn tron_transfer_token(
+        &self,
+        index: u32,
+        token: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        if let TronToken::Trc10(id) = TronToken::from(token) {
+            let to_hex = tron_address_to_hex(to)?;
+            // like TRX, TRC-10 transfers activate their recipient
+            if !client.is_activated(&to_hex).await? {
+                let estimate = self
+                    .estimate_transfer_token_with(index, token, to, amount, false, &client)
+                    .await?;
+                self.check_activation_funded(index, to, estimate.burn().into(), provider)
+                    .await?;
+            }
+            let tx = client
+                .transfer_asset(
+                    &self.tron_api_address_by_index(index)?,
+                    &to_hex,
+                    &id,
+                    amount_as::<i64>(amount)? as u64,
+                )
+                .await?;
+            return self.tron_send(index, tx, &client).await;
+        }
+        let tx = client
+            .trigger_smart_contract(
+                &self.tron_api_address_by_index(index)?,
+                &tron_address_to_hex(token)?,
+                TRC20_TRANSFER,
+                &trc20_transfer_parameter(to, amount)?,
+                self.fee_limit,
+            )
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Resources a TRX transfer from the address at `index` consumes, and what it burns,
+    /// the activation of `to` included if it isn't active yet.
+    pub async fn estimate_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<ResourceEstimate, Error> {
+        let client = TronClient::new(provider);
+        let activated = client.is_activated(&tron_address_to_hex(to)?).await?;
+        self.estimate_transfer_with(index, to, amount, activated, &client)
+            .await
+    }
+
+    /// `estimate_transfer`, for callers that already know whether `to` is `activated`.
+    async fn estimate_transfer_with(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        activated: bool,
+        client: &TronClient,
+    ) -> Result<ResourceEstimate, Error> {
+        let block = client.get_now_block().await?;
+        let bandwidth = self.build_transfer(index, to, amount, &block)?.bandwidth(1);
+        let estimate = self.estimate(index, bandwidth, 0, client).await?;
+        Ok(if activated {
+            estimate
+        } else {
+            estimate.activating()
+        })
+    }
+
+    /// Resources a token transfer from the address at `index` consumes, and what it
+    /// burns. TRC-10 transfers only take bandwidth, and activate `to` if it isn't active
+    /// yet. The energy of TRC-20 transfers is the one of a dry run of the transfer, which
+    /// includes the storage of a first token balance of `to`; they don't activate `to`.
+    pub async fn estimate_transfer_token(
+        &self,
+        index: u32,
+        token: &str,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<ResourceEstimate, Error> {
+        let client = TronClient::new(provider);
+        let activated = match TronToken::from(token) {
+            TronToken::Trc10(_) => client.is_activated(&tron_address_to_hex(to)?).await?,
+            TronToken::Trc20(_) => true,
+        };
+        self.estimate_transfer_token_with(index, token, to, amount, activated, &client)
+            .await
+    }
+
+    /// `estimate_transfer_token`, for callers that already know whether `to` is
+    /// `activated`. Only TRC-10 transfers look at it.
+    async fn estimate_transfer_token_with(
+        &self,
+        index: u32,
+        token: &str,
+        to: &str,
+        amount: U256,
+        activated: bool,
+        client: &TronClient,
+    ) -> Result<ResourceEstimate, Error> {
+        let block = client.get_now_block().await?;
+        let bandwidth = self
+            .build_transfer_token(index, token, to, amount, &block)?
+            .bandwidth(1);
+        if let TronToken::Trc10(_) = TronToken::from(token) {
+            let estimate = self.estimate(index, bandwidth, 0, client).await?;
+            return Ok(if activated {
+                estimate
+            } else {
+                estimate.activating()
+            });
+        }
+        let energy = client
+            .trigger_constant_contract(
+                &self.tron_api_address_by_index(index)?,
+                &tron_address_to_hex(token)?,
+                TRC20_TRANSFER,
+                &trc20_transfer_parameter(to, amount)?,
+            )
+            .await?
+            .energy_used;
+        self.estimate(index, bandwidth, energy, client).await
+    }
+
+    async fn estimate(
+        &self,
+        index: u32,
+        bandwidth: u64,
+        energy: u64,
+        client: &TronClient,
+    ) -> Result<ResourceEstimate, Error> {
+        let resource = client
+            .get_account_resource(&self.tron_api_address_by_index(index)?)
+            .await?;
+        let parameters = client.get_chain_parameters().await?;
+        Ok(ResourceEstimate::new(
+            bandwidth,
+            energy,
+            &resource,
+            &parameters,
+        ))
+    }
+
+    /// Whether the address at `index` is activated. Accounts are activated by their first
+    /// incoming TRX transfer or by `activate`, and only then hold TRX or resources.
+    pub async fn is_activated(&self, index: u32, provider: &str) -> Result<bool, Error> {
+        TronClient::new(provider)
+            .is_activated(&self.tron_api_address_by_index(index)?)
+            .await
+    }
+
+    async fn tron_balance_by_index(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        let client = TronClient::new(provider);
+        let account = client
+            .get_account(&self.tron_api_address_by_index(index)?)
+            .await?;
+        Ok(account.map_or(0, |account| account.balance).into())
     }
 
     async fn tron_balance_token_by_index(
         &self,
         index: u32,
-        provider_url: &str,
+        provider: &str,
         token_addr: &str,
     ) -> Result<U256, Error> {
-        let addr = self.tron_hex_address_by_index(index)?;
-        let addr_h160 = address_str_to_h160(&addr)?;
-
-        let transport = web3::transports::Http::new(provider_url).unwrap();
-        let web3 = web3::Web3::new(transport);
+        let client = TronClient::new(provider);
+        if let TronToken::Trc10(id) = TronToken::from(token_addr) {
+            return self.tron_balance_asset_by_index(index, &id, &client).await;
+        }
+        let owner = self.tron_api_address_by_index(index)?;
+        let parameter = abi::encode(&[abi_address(&owner)?]);
+        let result = client
+            .trigger_constant_contract(
+                &owner,
+                &tron_address_to_hex(token_addr)?,
+                "balanceOf(address)",
+                &hex::encode(parameter),
+            )
+            .await?;
+        let decoded = abi::decode(&[ParamType::Uint(256)], &result.output)?;
+        Ok(decoded[0].clone().into_uint().unwrap_or_default())
+    }
+}
 
-        let token_addr_v = base58::decode(token_addr)?;
-        let token_addr_hex = hex::encode(&token_addr_v);
-        let token_addr_hex_p = "0x".to_owned() + &token_addr_hex[2..token_addr_hex.len() - 8];
-        let token_addr_h160 = address_str_to_h160(&token_addr_hex_p)?;
+/// Broadcasts the signed `tx`, then waits for it to be confirmed.
+async fn send_signed(tx: Transaction, client: &TronClient) -> Result<TransferReceipt, Error> {
+    client.broadcast_transaction(&tx).await?;
+    let info = client
+        .wait_for_transaction(&tx.tx_id, CONFIRMATION_ATTEMPTS, CONFIRMATION_INTERVAL)
+        .await?;
+    if let Some(info) = &info {
+        check_contract_result(&tx.tx_id, info)?;
+    }
+    Ok(TransferReceipt::Tron {
+        txid: tx.tx_id,
+        block_number: info.as_ref().map(|info| info.block_number),
+        fee: info.map_or(0, |info| info.fee),
+    })
+}
 
-        let contract = Contract::from_json(
-            web3.eth(),
-            token_addr_h160,
-            include_bytes!("../../../res/erc20.abi.json"),
-        )?;
+/// ABI encoded arguments of a TRC-20 transfer of `amount` to the address `to`, as hex.
+fn trc20_transfer_parameter(to: &str, amount: U256) -> Result<String, Error> {
+    Ok(hex::encode(abi::encode(&[
+        abi_address(&tron_address_to_hex(to)?)?,
+        Token::Uint(amount),
+    ])))
+}
 
-        let result = contract.query("balanceOf", (addr_h160,), None, Options::default(), None);
-        let balance: U256 = result.await?;
+/// ABI token of the hex address `41…`, the 20 bytes following the prefix.
+pub(crate) fn abi_address(hex_address: &str) -> Result<Token, Error> {
+    let bytes = tron_hex_to_bytes(hex_address)?;
+    Ok(Token::Address(H160::from_slice(&bytes[1..])))
+}
 
-        Ok(balance)
+/// Fails with the reason a contract call of `txid` did not succeed, if it didn't.
+fn check_contract_result(txid: &str, info: &TransactionInfo) -> Result<(), Error> {
+    let reason = || {
+        info.contract_result
+            .first()
+            .and_then(|output| revert_reason(output))
+            .or_else(|| info.res_message.as_deref().map(decode_message))
+            .unwrap_or_default()
+    };
+    match &info.receipt.result {
+        None | Some(ContractResult::Success) | Some(ContractResult::Default) => Ok(()),
+        Some(ContractResult::OutOfEnergy) => Err(Error::TronOutOfEnergy {
+            txid: txid.to_owned(),
+            fee: info.fee,
+        }),
+        Some(ContractResult::Revert) => Err(Error::TronReverted {
+            txid: txid.to_owned(),
+            reason: reason(),
+        }),
+        Some(result) => Err(Error::TronContractFailed {
+            txid: txid.to_owned(),
+            reason: format!("{:?} {}", result, reason()).trim_end().to_owned(),
+        }),
     }
 }
 
+/// Message of a Solidity `Error(string)` revert, from the hex output of the call.
+fn revert_reason(output: &str) -> Option<String> {
+    let output = hex::decode(output).ok()?;
+    let data = output.strip_prefix(&id("Error(string)"))?;
+    abi::decode(&[ParamType::String], data)
+        .ok()?
+        .pop()?
+        .into_string()
+}
+
 #[async_trait]
 impl Wallet for TronWallet {
     fn address(&self, index: u32) -> Result<String, Error> {
@@ -144,8 +612,8 @@ impl Wallet for TronWallet {
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error> {
-        Err(Error::TronAddrLengthError)
+    ) -> Result<TransferReceipt, Error> {
+        self.tron_transfer(index, to, amount, provider).await
     }
 
     async fn transfer_token(
@@ -155,28 +623,38 @@ impl Wallet for TronWallet {
         to: &str,
         amount: U256,
         provider: &str,
-    ) -> Result<TransactionReceipt, Error> {
-        Err(Error::TronAddrLengthError)
+    ) -> Result<TransferReceipt, Error> {
+        self.tron_transfer_token(index, token_address, to, amount, provider)
+            .await
     }
 
-    fn sweep(&self, _index: u32, _to: &str, _provider: &str) -> Result<(Transaction, U256), Error> {
-        unimplemented!()
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        self.tron_sweep(index, to, provider).await
     }
-    fn sweep_token(
+    async fn sweep_token(
         &self,
-        _index: u32,
-        _token_address: &str,
-        _to: &str,
-        _provider: &str,
-    ) -> Result<(Transaction, TokenData), Error> {
-        unimplemented!()
+        index: u32,
+        token_address: &str,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        self.tron_sweep_token(index, token_address, to, provider)
+            .await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use api::{DEFAULT_CREATE_ACCOUNT_FEE, DEFAULT_CREATE_NEW_ACCOUNT_FEE};
     use bip39::{Language, Mnemonic};
+    use ethers::types::Signature;
     const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
 
     #[test]
@@ -184,7 +662,7 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = TronWallet { seed };
+        let wallet = TronWallet::from_seed(seed);
 
         let expected_address_0 = "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH";
         assert_eq!(wallet.address(0).unwrap(), expected_address_0);
@@ -195,7 +673,7 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = TronWallet { seed };
+        let wallet = TronWallet::from_seed(seed);
 
         let expected_pubkey = "xpub6GH5FbhZEomKSf2YeFsq92oVisrWG9b1H6sHW2RYmGJtasVd7LckJXiovzCLL52Dz7GsrQJWoXTshExmhqxNtsnu8GoD1S3kHzLfg1Apo8d";
         assert_eq!(wallet.public(0).unwrap(), expected_pubkey);
@@ -206,9 +684,661 @@ mod tests {
         let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
         let seed = HDSeed { mnemonic };
 
-        let wallet = TronWallet { seed };
+        let wallet = TronWallet::from_seed(seed);
 
         let expected_privkey = "b5a4cea271ff424d7c31dc12a3e43e401df7a40d7412a15750f3f0b6b5449a28";
         assert_eq!(wallet.private(0).unwrap(), expected_privkey);
     }
+
+    pub(super) fn wallet() -> TronWallet {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        TronWallet::from_seed(HDSeed { mnemonic })
+    }
+
+    /// Transaction as `createtransaction` returns it, with a consistent id.
+    pub(super) fn unsigned_tx(raw_data_hex: &str) -> String {
+        let hash = Sha256::digest(hex::decode(raw_data_hex).unwrap());
+        format!(
+            r#"{{"visible":false,"txID":"{}","raw_data":{{"contract":[]}},"raw_data_hex":"{}"}}"#,
+            hex::encode(hash),
+            raw_data_hex
+        )
+    }
+
+    #[test]
+    fn test_tron_address_to_hex() {
+        let wallet = wallet();
+        assert_eq!(
+            tron_address_to_hex("TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH").unwrap(),
+            wallet.tron_api_address_by_index(0).unwrap()
+        );
+        assert!(tron_address_to_hex("TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdh").is_err());
+        assert_eq!(
+            address::tron_hex_to_address(&wallet.tron_api_address_by_index(0).unwrap()).unwrap(),
+            "TUEZSdKsoDHQMeZwihtdoBiN46zxhGWYdH"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trx_transfer() {
+        let wallet = wallet();
+        let raw_data_hex = "0a02a1b22208c4d5e6f7a8b9c0d140e8f3b0b0ee315a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e7472616374";
+        let unsigned = unsigned_tx(raw_data_hex);
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let to = wallet.address(1).unwrap();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccount",
+                &format!(r#"{{"address":"{}"}}"#, tron_address_to_hex(&to).unwrap()),
+            ),
+            Route::post("/wallet/createtransaction", &unsigned),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(
+                    r#"{{"id":"{}","fee":1100000,"blockNumber":61234567,"receipt":{{"net_fee":1100000}}}}"#,
+                    tx.tx_id
+                ),
+            ),
+        ])
+        .await;
+
+        let receipt = wallet
+            .transfer(0, &to, U256::from(1_500_000), &server.url)
+            .await
+            .unwrap();
+        assert!(matches!(
+            &receipt,
+            TransferReceipt::Tron {
+                block_number: Some(61234567),
+                fee: 1100000,
+                ..
+            }
+        ));
+        assert_eq!(receipt.txid(), tx.tx_id);
+
+        let requests = server.requests();
+        let create: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(
+            create["owner_address"],
+            wallet.tron_api_address_by_index(0).unwrap()
+        );
+        assert_eq!(create["to_address"], tron_address_to_hex(&to).unwrap());
+        assert_eq!(create["amount"], 1_500_000);
+
+        let broadcast: Transaction = serde_json::from_str(&requests[2].body).unwrap();
+        let signature = hex::decode(&broadcast.signature[0]).unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        let signer = signature
+            .recover(H256::from_slice(&hex::decode(&tx.tx_id).unwrap()))
+            .unwrap();
+        assert_eq!(
+            format!("41{}", hex::encode(signer)),
+            wallet.tron_api_address_by_index(0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_transfer() {
+        let wallet = wallet();
+        let mut tampered: Transaction = serde_json::from_str(&unsigned_tx("0a02a1b2")).unwrap();
+        tampered.raw_data_hex = "0a02a1b3".to_owned();
+        assert!(matches!(
+            wallet.sign_transaction(0, &mut tampered),
+            Err(Error::TronTxIdMismatch(_))
+        ));
+
+        let server = TestServer::start(vec![
+            Route::post("/wallet/getaccount", r#"{"address":"41"}"#),
+            Route::post(
+                "/wallet/createtransaction",
+                r#"{"Error":"Contract validate error : Validate TransferContract error, balance is not sufficient."}"#,
+            ),
+        ])
+        .await;
+        let result = wallet
+            .transfer(0, &wallet.address(1).unwrap(), U256::from(1), &server.url)
+            .await;
+        assert!(
+            matches!(result, Err(Error::TronApiError(message)) if message.contains("balance is not sufficient"))
+        );
+    }
+
+    /// Routes of a TRC-20 transfer confirmed with `info` as the transaction's result.
+    fn trc20_routes(tx: &Transaction, unsigned: &str, info: &str) -> Vec<Route> {
+        vec![
+            Route::post(
+                "/wallet/triggersmartcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"transaction":{}}}"#,
+                    unsigned
+                ),
+            ),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post("/wallet/gettransactioninfobyid", info),
+        ]
+    }
+
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+    #[tokio::test]
+    async fn test_trc20_transfer() {
+        let wallet = wallet().with_fee_limit(30_000_000);
+        let unsigned = unsigned_tx("0a02b1c2");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let info = format!(
+            r#"{{"id":"{}","fee":13844850,"blockNumber":61234570,"receipt":{{"energy_usage_total":31895,"result":"SUCCESS"}}}}"#,
+            tx.tx_id
+        );
+        let server = TestServer::start(trc20_routes(&tx, &unsigned, &info)).await;
+
+        let to = wallet.address(1).unwrap();
+        let receipt = wallet
+            .transfer_token(0, USDT, &to, U256::from(2_500_000), &server.url)
+            .await
+            .unwrap();
+        assert!(matches!(
+            &receipt,
+            TransferReceipt::Tron {
+                block_number: Some(61234570),
+                fee: 13844850,
+                ..
+            }
+        ));
+
+        let requests = server.requests();
+        let trigger: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(
+            trigger["contract_address"],
+            tron_address_to_hex(USDT).unwrap()
+        );
+        assert_eq!(trigger["function_selector"], "transfer(address,uint256)");
+        assert_eq!(trigger["fee_limit"], 30_000_000);
+        assert_eq!(
+            trigger["parameter"],
+            format!(
+                "000000000000000000000000{}{:064x}",
+                &tron_address_to_hex(&to).unwrap()[2..],
+                2_500_000
+            )
+        );
+        let broadcast: Transaction = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(broadcast.signature.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_trc20_transfer_failures() {
+        let wallet = wallet();
+        let to = wallet.address(1).unwrap();
+        let unsigned = unsigned_tx("0a02b1c3");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+
+        let out_of_energy = format!(
+            r#"{{"id":"{}","fee":100000000,"blockNumber":1,"result":"FAILED","receipt":{{"result":"OUT_OF_ENERGY"}}}}"#,
+            tx.tx_id
+        );
+        let server = TestServer::start(trc20_routes(&tx, &unsigned, &out_of_energy)).await;
+        let result = wallet
+            .transfer_token(0, USDT, &to, U256::from(1), &server.url)
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::TronOutOfEnergy { fee: 100000000, .. })
+        ));
+
+        // Error("insufficient balance")
+        let output = format!(
+            "{}{}",
+            hex::encode(id("Error(string)")),
+            hex::encode(abi::encode(&[Token::String(
+                "insufficient balance".to_owned()
+            )]))
+        );
+        let reverted = format!(
+            r#"{{"id":"{}","fee":345,"blockNumber":1,"result":"FAILED","resMessage":"{}","contractResult":["{}"],"receipt":{{"result":"REVERT"}}}}"#,
+            tx.tx_id,
+            hex::encode("REVERT opcode executed"),
+            output
+        );
+        let server = TestServer::start(trc20_routes(&tx, &unsigned, &reverted)).await;
+        let result = wallet
+            .transfer_token(0, USDT, &to, U256::from(1), &server.url)
+            .await;
+        assert!(
+            matches!(result, Err(Error::TronReverted { reason, .. }) if reason == "insufficient balance")
+        );
+
+        let server = TestServer::start(vec![Route::post(
+            "/wallet/triggersmartcontract",
+            &format!(
+                r#"{{"result":{{"code":"CONTRACT_VALIDATE_ERROR","message":"{}"}}}}"#,
+                hex::encode("No contract or not a valid smart contract")
+            ),
+        )])
+        .await;
+        let result = wallet
+            .transfer_token(0, USDT, &to, U256::from(1), &server.url)
+            .await;
+        assert!(
+            matches!(result, Err(Error::TronApiError(message)) if message == "CONTRACT_VALIDATE_ERROR: No contract or not a valid smart contract")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_balances() {
+        let wallet = wallet();
+        let owner = wallet.tron_api_address_by_index(0).unwrap();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}","balance":25000000,"create_time":1700000000000}}"#,
+                    owner
+                ),
+            ),
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"energy_used":935,"constant_result":["{:064x}"]}}"#,
+                    7_000_000
+                ),
+            ),
+        ])
+        .await;
+        assert_eq!(
+            wallet.balance(0, &server.url).await.unwrap(),
+            U256::from(25_000_000)
+        );
+        assert!(wallet.is_activated(0, &server.url).await.unwrap());
+        assert_eq!(
+            wallet.balance_token(0, USDT, &server.url).await.unwrap(),
+            U256::from(7_000_000)
+        );
+
+        let requests = server.requests();
+        let account: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(account["address"], owner);
+        let call: serde_json::Value = serde_json::from_str(&requests[2].body).unwrap();
+        assert_eq!(call["function_selector"], "balanceOf(address)");
+        assert_eq!(call["contract_address"], tron_address_to_hex(USDT).unwrap());
+        assert_eq!(call["parameter"], format!("{:0>64}", &owner[2..]));
+    }
+
+    #[tokio::test]
+    async fn test_unactivated_account() {
+        let wallet = wallet();
+        let server = TestServer::start(vec![Route::post("/wallet/getaccount", "{}")]).await;
+        assert!(wallet.balance(0, &server.url).await.unwrap().is_zero());
+        assert!(!wallet.is_activated(0, &server.url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_account_resource_and_block() {
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccountresource",
+                r#"{"freeNetUsed":120,"freeNetLimit":600,"NetLimit":100,"EnergyUsed":1000,"EnergyLimit":65000,"TotalEnergyLimit":180000000000,"TotalEnergyWeight":19000000000}"#,
+            ),
+            Route::post(
+                "/wallet/getnowblock",
+                r#"{"blockID":"0000000003a6f1b2","block_header":{"raw_data":{"number":61272498,"timestamp":1713000000000}}}"#,
+            ),
+        ])
+        .await;
+        let client = TronClient::new(&server.url);
+        let resource = client
+            .get_account_resource(&wallet().tron_api_address_by_index(0).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resource.bandwidth(), 580);
+        assert_eq!(resource.energy(), 64000);
+        assert_eq!(resource.total_energy_weight, 19_000_000_000);
+
+        let block = client.get_now_block().await.unwrap();
+        assert_eq!(block.number, 61272498);
+        assert_eq!(block.id, "0000000003a6f1b2");
+    }
+
+    #[tokio::test]
+    async fn test_offline_signing() {
+        let wallet = wallet();
+        let block = BlockHeader {
+            id: "0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718".to_owned(),
+            number: 61272498,
+            timestamp: 1713000000000,
+        };
+        let to = wallet.address(1).unwrap();
+        let raw = wallet
+            .build_transfer_token(0, USDT, &to, U256::from(1), &block)
+            .unwrap();
+        assert_eq!(raw.fee_limit, DEFAULT_FEE_LIMIT);
+        let tx_id = hex::encode(raw.tx_id());
+        let tx = wallet.sign_raw(0, raw).unwrap();
+        assert_eq!(tx.tx_id, tx_id);
+
+        // the JSON round-trips for broadcasttransaction
+        let json = serde_json::to_string(&tx).unwrap();
+        assert_eq!(serde_json::from_str::<Transaction>(&json).unwrap(), tx);
+
+        let encoded = transaction::encode_signed(&tx).unwrap();
+        let signature = hex::decode(&tx.signature[0]).unwrap();
+        assert_eq!(encoded[0], 0x0a);
+        assert!(encoded.ends_with(&[&[0x12, 65][..], &signature].concat()));
+
+        let server = TestServer::start(vec![Route::post(
+            "/wallet/broadcasthex",
+            &format!(r#"{{"result":true,"txid":"{}"}}"#, tx_id),
+        )])
+        .await;
+        TronClient::new(&server.url)
+            .broadcast_hex(&hex::encode(&encoded))
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[0].body).unwrap();
+        assert_eq!(body["transaction"], hex::encode(encoded));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_transfer_token() {
+        let wallet = wallet();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getnowblock",
+                r#"{"blockID":"0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718","block_header":{"raw_data":{"number":61272498,"timestamp":1713000000000}}}"#,
+            ),
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                r#"{"result":{"result":true},"energy_used":64285,"constant_result":["0000000000000000000000000000000000000000000000000000000000000001"]}"#,
+            ),
+            Route::post(
+                "/wallet/getaccountresource",
+                r#"{"freeNetLimit":600,"EnergyLimit":20000}"#,
+            ),
+            Route::post(
+                "/wallet/getchainparameters",
+                r#"{"chainParameter":[{"key":"getTransactionFee","value":1000},{"key":"getEnergyFee","value":420}]}"#,
+            ),
+        ])
+        .await;
+        let to = wallet.address(1).unwrap();
+        let estimate = wallet
+            .estimate_transfer_token(0, USDT, &to, U256::from(1_000_000), &server.url)
+            .await
+            .unwrap();
+        assert_eq!(estimate.energy, 64285);
+        assert_eq!(estimate.bandwidth, 345);
+        assert!(estimate.bandwidth_covered());
+        assert_eq!(estimate.energy_burn(), 44285 * 420);
+
+        let call: serde_json::Value = serde_json::from_str(
+            &server
+                .requests()
+                .iter()
+                .find(|r| r.path.ends_with("triggerconstantcontract"))
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert_eq!(call["function_selector"], TRC20_TRANSFER);
+    }
+
+    /// Routes of an estimate for a sender with `balance` sun and the free bandwidth, to
+    /// the unactivated address `to`.
+    fn activation_routes(wallet: &TronWallet, to: &str, balance: u64) -> Vec<Route> {
+        vec![
+            Route {
+                body_contains: Some(tron_address_to_hex(to).unwrap()),
+                ..Route::post("/wallet/getaccount", "{}")
+            },
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}","balance":{}}}"#,
+                    wallet.tron_api_address_by_index(0).unwrap(),
+                    balance
+                ),
+            ),
+            Route::post(
+                "/wallet/getnowblock",
+                r#"{"blockID":"0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718","block_header":{"raw_data":{"number":61272498,"timestamp":1713000000000}}}"#,
+            ),
+            Route::post("/wallet/getaccountresource", r#"{"freeNetLimit":600}"#),
+            Route::post("/wallet/getchainparameters", r#"{"chainParameter":[]}"#),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_transfer_to_unactivated() {
+        let wallet = wallet();
+        let to = wallet.address(1).unwrap();
+        let server = TestServer::start(activation_routes(&wallet, &to, 1_500_000)).await;
+
+        // the free bandwidth doesn't cover the creation of an account
+        let estimate = wallet
+            .estimate_transfer(0, &to, U256::from(1_000_000), &server.url)
+            .await
+            .unwrap();
+        assert!(estimate.activates);
+        assert!(!estimate.covered());
+        assert_eq!(estimate.activation_burn(), DEFAULT_CREATE_NEW_ACCOUNT_FEE);
+        assert_eq!(
+            estimate.burn(),
+            DEFAULT_CREATE_NEW_ACCOUNT_FEE + DEFAULT_CREATE_ACCOUNT_FEE
+        );
+
+        let result = wallet
+            .transfer(0, &to, U256::from(1_000_000), &server.url)
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::TronActivationUnfunded { needed, balance, .. })
+                if needed == U256::from(2_100_000) && balance == U256::from(1_500_000)
+        ));
+        let requests = server.requests();
+        assert!(!requests
+            .iter()
+            .any(|r| r.path == "/wallet/createtransaction"));
+        // the estimate and the transfer each look the destination up once
+        let to_hex = tron_address_to_hex(&to).unwrap();
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|r| r.path == "/wallet/getaccount" && r.body.contains(&to_hex))
+                .count(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_activate() {
+        let wallet = wallet();
+        let to = wallet.address(1).unwrap();
+        let unsigned = unsigned_tx("0a02d1e2");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let mut routes = activation_routes(&wallet, &to, 1_500_000);
+        routes.extend([
+            Route::post("/wallet/createaccount", &unsigned),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(
+                    r#"{{"id":"{}","fee":1100000,"blockNumber":61234567}}"#,
+                    tx.tx_id
+                ),
+            ),
+        ]);
+        let server = TestServer::start(routes).await;
+
+        let receipt = wallet.activate(0, &to, &server.url).await.unwrap().unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+        let create: serde_json::Value = serde_json::from_str(
+            &server
+                .requests()
+                .iter()
+                .find(|r| r.path == "/wallet/createaccount")
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert_eq!(
+            create["owner_address"],
+            wallet.tron_api_address_by_index(0).unwrap()
+        );
+        assert_eq!(create["account_address"], tron_address_to_hex(&to).unwrap());
+
+        // the sender is active, nothing to do
+        let owner = wallet.address(0).unwrap();
+        assert!(wallet
+            .activate(1, &owner, &server.url)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sweep() {
+        let wallet = wallet();
+        let to = wallet.address(1).unwrap();
+        let unsigned = unsigned_tx("0a02e1f3");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let mut routes = activation_routes(&wallet, &to, 5_000_000);
+        routes.extend([
+            Route::post("/wallet/createtransaction", &unsigned),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(
+                    r#"{{"id":"{}","fee":2100000,"blockNumber":61234567}}"#,
+                    tx.tx_id
+                ),
+            ),
+        ]);
+        let server = TestServer::start(routes).await;
+
+        // the destination is activated by the sweep, which burns for it
+        let (receipt, amount) = wallet.sweep(0, &to, &server.url).await.unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+        let burn = DEFAULT_CREATE_NEW_ACCOUNT_FEE + DEFAULT_CREATE_ACCOUNT_FEE;
+        assert_eq!(amount, U256::from(5_000_000 - burn));
+        let create: serde_json::Value = serde_json::from_str(
+            &server
+                .requests()
+                .iter()
+                .find(|r| r.path == "/wallet/createtransaction")
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert_eq!(create["amount"], 5_000_000 - burn);
+
+        let server = TestServer::start(activation_routes(&wallet, &to, burn)).await;
+        assert!(matches!(
+            wallet.sweep(0, &to, &server.url).await,
+            Err(Error::InsufficientFundsForFee { fee, available })
+                if fee == U256::from(burn) && available == U256::from(burn)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_token() {
+        let wallet = wallet();
+        let to = wallet.address(1).unwrap();
+        let unsigned = unsigned_tx("0a02b1c4");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let info = format!(
+            r#"{{"id":"{}","fee":13844850,"blockNumber":61234570,"receipt":{{"result":"SUCCESS"}}}}"#,
+            tx.tx_id
+        );
+        let mut routes = vec![
+            Route {
+                body_contains: Some("symbol()".to_owned()),
+                ..Route::post(
+                    "/wallet/triggerconstantcontract",
+                    &format!(
+                        r#"{{"result":{{"result":true}},"constant_result":["{}"]}}"#,
+                        hex::encode(abi::encode(&[Token::String("USDT".to_owned())]))
+                    ),
+                )
+            },
+            Route {
+                body_contains: Some("decimals()".to_owned()),
+                ..Route::post(
+                    "/wallet/triggerconstantcontract",
+                    &format!(
+                        r#"{{"result":{{"result":true}},"constant_result":["{:064x}"]}}"#,
+                        6
+                    ),
+                )
+            },
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"constant_result":["{:064x}"]}}"#,
+                    7_250_000
+                ),
+            ),
+        ];
+        routes.extend(trc20_routes(&tx, &unsigned, &info));
+        let server = TestServer::start(routes).await;
+
+        let (receipt, data) = wallet.sweep_token(0, USDT, &to, &server.url).await.unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+        assert_eq!(data.balance, U256::from(7_250_000));
+        assert_eq!(data.symbol, "USDT");
+        assert_eq!(data.balance_f, 7.25);
+        let trigger: serde_json::Value = serde_json::from_str(
+            &server
+                .requests()
+                .iter()
+                .find(|r| r.path == "/wallet/triggersmartcontract")
+                .unwrap()
+                .body,
+        )
+        .unwrap();
+        assert!(trigger["parameter"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("{:064x}", 7_250_000)));
+
+        let empty = TestServer::start(vec![
+            Route {
+                body_contains: Some("symbol()".to_owned()),
+                ..Route::post(
+                    "/wallet/triggerconstantcontract",
+                    &format!(
+                        r#"{{"result":{{"result":true}},"constant_result":["{}"]}}"#,
+                        hex::encode(abi::encode(&[Token::String("USDT".to_owned())]))
+                    ),
+                )
+            },
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"constant_result":["{:064x}"]}}"#,
+                    0
+                ),
+            ),
+        ])
+        .await;
+        assert!(matches!(
+            wallet.sweep_token(0, USDT, &to, &empty.url).await,
+            Err(Error::NothingToSweep)
+        ));
+        assert!(empty
+            .requests()
+            .iter()
+            .all(|r| r.path == "/wallet/triggerconstantcontract"));
+    }
 }
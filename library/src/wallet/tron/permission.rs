@@ -0,0 +1,313 @@
+//! Account permissions and multi-signature transactions.
+//!
+//! An account's owner permission can do anything, its active permissions the operations
+//! they list. Each permission has weighted keys and a threshold: a transaction names the
+//! permission it is signed under in `Permission_id`, and is valid once the weights of
+//! its signers reach the threshold. Signers, derived indices of this wallet or of other
+//! seeds, each add their signature to the same transaction with `sign_transaction`.
+
+use crate::{error::Error, types::transfer_receipt::TransferReceipt};
+
+use super::{
+    api::{
+        BlockHeader, Permission, PermissionKey, PermissionType, SignWeight, Transaction, TronClient,
+    },
+    send_signed,
+    transaction::{Contract, RawTransaction},
+    TronWallet,
+};
+
+/// Operations TronWeb gives new active permissions: all contract types an account can
+/// use, smart contracts, voting and Stake 2.0 (types 54 to 59) included.
+pub const DEFAULT_ACTIVE_OPERATIONS: &str =
+    "7fff1fc0033ec30f000000000000000000000000000000000000000000000000";
+
+/// Bitmask of the `contract_types`, `Transaction.Contract.ContractType` values, as the
+/// hex `operations` of an active permission.
+pub fn operations(contract_types: &[u8]) -> String {
+    let mut mask = [0u8; 32];
+    for &contract_type in contract_types {
+        mask[contract_type as usize / 8] |= 1 << (contract_type % 8);
+    }
+    hex::encode(mask)
+}
+
+impl Permission {
+    /// Owner permission with `keys`, hex addresses and their weights.
+    pub fn owner(threshold: u64, keys: Vec<PermissionKey>) -> Self {
+        Permission {
+            permission_type: PermissionType::Owner,
+            permission_name: "owner".to_owned(),
+            threshold,
+            keys,
+            ..Default::default()
+        }
+    }
+
+    /// Active permission allowing the hex `operations`. Active permissions get the ids
+    /// from 2 in the order they are set.
+    pub fn active(name: &str, threshold: u64, operations: &str, keys: Vec<PermissionKey>) -> Self {
+        Permission {
+            permission_type: PermissionType::Active,
+            permission_name: name.to_owned(),
+            threshold,
+            operations: operations.to_owned(),
+            keys,
+            ..Default::default()
+        }
+    }
+
+    /// Summed weight of the keys at the hex `addresses`.
+    pub fn weight(&self, addresses: &[String]) -> u64 {
+        self.keys
+            .iter()
+            .filter(|key| addresses.contains(&key.address))
+            .map(|key| key.weight)
+            .sum()
+    }
+}
+
+impl TronWallet {
+    /// Unsigned replacement of the permissions of the address at `index`, built without a
+    /// node from the recent `block`.
+    pub fn build_permission_update(
+        &self,
+        index: u32,
+        owner: Permission,
+        actives: Vec<Permission>,
+        block: &BlockHeader,
+    ) -> Result<RawTransaction, Error> {
+        let contract = Contract::account_permission_update(
+            &self.tron_api_address_by_index(index)?,
+            owner,
+            actives,
+        )?;
+        RawTransaction::new(contract, block)
+    }
+
+    /// Replaces the permissions of the address at `index`, signed with its own key. The
+    /// chain burns a fee for the update, 100 TRX on mainnet. Once the owner permission no
+    /// longer is the account's key alone, later updates go through `build_permission_update`
+    /// and `broadcast_multisig`.
+    pub async fn update_permissions(
+        &self,
+        index: u32,
+        owner: Permission,
+        actives: Vec<Permission>,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let block = client.get_now_block().await?;
+        let raw = self.build_permission_update(index, owner, actives, &block)?;
+        self.tron_send(index, raw.into_transaction(), &client).await
+    }
+
+    /// Weight the signatures of `tx` carry under the permission it names.
+    pub async fn sign_weight(&self, tx: &Transaction, provider: &str) -> Result<SignWeight, Error> {
+        TronClient::new(provider).get_sign_weight(tx).await
+    }
+
+    /// Broadcasts `tx`, signed by the keys of a permission, once their weight reaches the
+    /// permission's threshold, then waits for it to be confirmed.
+    pub async fn broadcast_multisig(
+        &self,
+        tx: Transaction,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let weight = client.get_sign_weight(&tx).await?;
+        if !weight.enough() {
+            return Err(Error::TronSignWeightTooLow {
+                weight: weight.current_weight,
+                threshold: weight.threshold(),
+                reason: format!("{} {}", weight.result.code, weight.result.message)
+                    .trim_end()
+                    .to_owned(),
+            });
+        }
+        send_signed(tx, &client).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test_server::{Route, TestServer},
+        wallet::{tron::tests::wallet, Wallet},
+    };
+    use ethers::types::{Signature, H256, U256};
+
+    fn block() -> BlockHeader {
+        BlockHeader {
+            id: "0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718".to_owned(),
+            number: 61272498,
+            timestamp: 1713000000000,
+        }
+    }
+
+    fn key(wallet: &TronWallet, index: u32, weight: u64) -> PermissionKey {
+        PermissionKey {
+            address: wallet.tron_api_address_by_index(index).unwrap(),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_operations() {
+        let types = [
+            0, 1, 2, 3, 4, 5, 6, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 30, 31, 32, 33,
+            41, 42, 43, 44, 45, 48, 49, 54, 55, 56, 57, 58, 59,
+        ];
+        assert_eq!(operations(&types), DEFAULT_ACTIVE_OPERATIONS);
+    }
+
+    #[test]
+    fn test_default_operations_allow_stake_v2() {
+        let mask = hex::decode(DEFAULT_ACTIVE_OPERATIONS).unwrap();
+        let allowed: Vec<usize> = (0..256)
+            .filter(|contract_type| mask[contract_type / 8] & (1 << (contract_type % 8)) != 0)
+            .collect();
+        // FreezeBalanceV2, UnfreezeBalanceV2, WithdrawExpireUnfreeze, DelegateResource,
+        // UnDelegateResource and CancelAllUnfreezeV2
+        for contract_type in 54..=59 {
+            assert!(allowed.contains(&contract_type));
+        }
+        // TransferContract, TransferAssetContract and TriggerSmartContract
+        for contract_type in [1, 2, 31] {
+            assert!(allowed.contains(&contract_type));
+        }
+        assert_eq!(allowed.last(), Some(&59));
+    }
+
+    #[test]
+    fn test_permission_update() {
+        let wallet = wallet();
+        let owner = Permission::owner(2, vec![key(&wallet, 1, 1), key(&wallet, 2, 1)]);
+        let active = Permission::active(
+            "payments",
+            2,
+            &operations(&[1, 2, 31]),
+            vec![key(&wallet, 1, 1), key(&wallet, 3, 1)],
+        );
+        let raw = wallet
+            .build_permission_update(0, owner.clone(), vec![active], &block())
+            .unwrap();
+        let tx = raw.into_transaction();
+        let contract = &tx.raw_data["contract"][0];
+        assert_eq!(contract["type"], "AccountPermissionUpdateContract");
+        let value = &contract["parameter"]["value"];
+        assert_eq!(value["owner"]["permission_name"], "owner");
+        assert_eq!(value["owner"]["threshold"], 2);
+        assert_eq!(value["actives"][0]["type"], "Active");
+        assert_eq!(
+            value["actives"][0]["keys"][1]["address"],
+            wallet.tron_api_address_by_index(3).unwrap()
+        );
+        // ContractType 46, and the owner permission with its name and threshold
+        assert!(tx.raw_data_hex.contains("082e12"));
+        assert!(tx
+            .raw_data_hex
+            .contains(&format!("1a05{}2002", hex::encode("owner"))));
+
+        let invalid = Permission::active("broken", 1, "7fff", vec![key(&wallet, 1, 1)]);
+        assert!(wallet
+            .build_permission_update(0, owner, vec![invalid], &block())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_multisig() {
+        let wallet = wallet();
+        let raw = wallet
+            .build_transfer(
+                0,
+                &wallet.address(5).unwrap(),
+                U256::from(1_000_000),
+                &block(),
+            )
+            .unwrap()
+            .permission_id(2);
+        let mut tx = raw.into_transaction();
+        assert_eq!(tx.raw_data["contract"][0]["Permission_id"], 2);
+        // Permission_id is field 5 of the contract
+        assert!(tx.raw_data_hex.contains("2802"));
+
+        wallet.sign_transaction(1, &mut tx).unwrap();
+        let once = tx.clone();
+        wallet.sign_transaction(3, &mut tx).unwrap();
+        assert_eq!(tx.signature.len(), 2);
+        let signers: Vec<String> = tx
+            .signature
+            .iter()
+            .map(|signature| {
+                let signature =
+                    Signature::try_from(hex::decode(signature).unwrap().as_slice()).unwrap();
+                let address = signature
+                    .recover(H256::from_slice(&hex::decode(&tx.tx_id).unwrap()))
+                    .unwrap();
+                format!("41{}", hex::encode(address))
+            })
+            .collect();
+        let active = Permission::active(
+            "payments",
+            2,
+            DEFAULT_ACTIVE_OPERATIONS,
+            vec![key(&wallet, 1, 1), key(&wallet, 3, 1)],
+        );
+        assert_eq!(active.weight(&signers), 2);
+
+        let permission = serde_json::to_string(&active).unwrap();
+        let server = TestServer::start(vec![
+            Route {
+                body_contains: Some(tx.signature[1].clone()),
+                ..Route::post(
+                    "/wallet/getsignweight",
+                    &format!(
+                        r#"{{"result":{{"code":"ENOUGH_PERMISSION"}},"permission":{},"current_weight":2}}"#,
+                        permission
+                    ),
+                )
+            },
+            Route::post(
+                "/wallet/getsignweight",
+                &format!(
+                    r#"{{"result":{{"code":"NOT_ENOUGH_PERMISSION","message":"{}"}},"permission":{},"current_weight":1}}"#,
+                    hex::encode("Signature count is 1"),
+                    permission
+                ),
+            ),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(r#"{{"id":"{}","blockNumber":61234567}}"#, tx.tx_id),
+            ),
+        ])
+        .await;
+
+        let weight = wallet.sign_weight(&once, &server.url).await.unwrap();
+        assert!(!weight.enough());
+        assert_eq!(weight.threshold(), 2);
+        assert!(matches!(
+            wallet.broadcast_multisig(once, &server.url).await,
+            Err(Error::TronSignWeightTooLow { weight: 1, threshold: 2, reason })
+                if reason == "NOT_ENOUGH_PERMISSION Signature count is 1"
+        ));
+
+        let receipt = wallet
+            .broadcast_multisig(tx.clone(), &server.url)
+            .await
+            .unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+        let broadcast = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == "/wallet/broadcasttransaction")
+            .unwrap();
+        let broadcast: Transaction = serde_json::from_str(&broadcast.body).unwrap();
+        assert_eq!(broadcast.signature, tx.signature);
+    }
+}
@@ -0,0 +1,176 @@
+//! Bandwidth and energy estimates of TRON transactions.
+//!
+//! Every transaction consumes bandwidth, its size in bytes, and contract calls also
+//! consume energy. Staked resources are used first; bandwidth then comes from the daily
+//! free allowance. Whatever isn't covered is paid by burning TRX: all of the bandwidth
+//! if neither source covers it alone, and the missing part of the energy.
+//!
+//! A transaction activating its recipient, a TRX transfer to a new address, also pays
+//! the activation fee, and can't use free bandwidth: without enough staked bandwidth it
+//! burns a fixed fee instead.
+
+use super::api::{AccountResource, ChainParameters};
+
+/// Resources a planned transaction consumes, those its sender has, and the TRX burnt
+/// for the difference.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceEstimate {
+    pub bandwidth: u64,
+    pub energy: u64,
+    /// Bandwidth the sender has left from staking.
+    pub staked_bandwidth: u64,
+    /// Bandwidth the sender has left of its daily free allowance.
+    pub free_bandwidth: u64,
+    /// Energy the sender has left from staking.
+    pub available_energy: u64,
+    /// Sun per unit of energy.
+    pub energy_fee: u64,
+    /// Sun per byte of bandwidth.
+    pub transaction_fee: u64,
+    /// Whether the transaction activates its recipient.
+    pub activates: bool,
+    /// Sun charged for the activation.
+    pub activation_fee: u64,
+    /// Sun burnt for the bandwidth of the activation if staked bandwidth doesn't cover it.
+    pub create_account_fee: u64,
+}
+
+impl ResourceEstimate {
+    pub fn new(
+        bandwidth: u64,
+        energy: u64,
+        resource: &AccountResource,
+        parameters: &ChainParameters,
+    ) -> Self {
+        ResourceEstimate {
+            bandwidth,
+            energy,
+            staked_bandwidth: resource.staked_bandwidth(),
+            free_bandwidth: resource.free_bandwidth(),
+            available_energy: resource.energy(),
+            energy_fee: parameters.energy_fee(),
+            transaction_fee: parameters.transaction_fee(),
+            activates: false,
+            activation_fee: parameters.create_new_account_fee(),
+            create_account_fee: parameters.create_account_fee(),
+        }
+    }
+
+    /// Marks the transaction as activating its recipient.
+    pub fn activating(mut self) -> Self {
+        self.activates = true;
+        self
+    }
+
+    /// Whether staked or free bandwidth covers the transaction. Activations only use
+    /// staked bandwidth.
+    pub fn bandwidth_covered(&self) -> bool {
+        self.staked_bandwidth >= self.bandwidth
+            || (!self.activates && self.free_bandwidth >= self.bandwidth)
+    }
+
+    /// Whether staked energy covers the transaction.
+    pub fn energy_covered(&self) -> bool {
+        self.available_energy >= self.energy
+    }
+
+    /// Whether the transaction burns no TRX.
+    pub fn covered(&self) -> bool {
+        self.bandwidth_covered() && self.energy_covered() && !self.activates
+    }
+
+    /// Sun burnt for bandwidth.
+    pub fn bandwidth_burn(&self) -> u64 {
+        if self.bandwidth_covered() {
+            0
+        } else if self.activates {
+            self.create_account_fee
+        } else {
+            self.bandwidth * self.transaction_fee
+        }
+    }
+
+    /// Sun burnt for the energy staking doesn't cover.
+    pub fn energy_burn(&self) -> u64 {
+        self.energy.saturating_sub(self.available_energy) * self.energy_fee
+    }
+
+    /// Sun charged for activating the recipient.
+    pub fn activation_burn(&self) -> u64 {
+        if self.activates {
+            self.activation_fee
+        } else {
+            0
+        }
+    }
+
+    /// Total sun burnt, what the sender needs in TRX besides the amount sent.
+    pub fn burn(&self) -> u64 {
+        self.bandwidth_burn() + self.energy_burn() + self.activation_burn()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(bandwidth: u64, energy: u64, resource: AccountResource) -> ResourceEstimate {
+        ResourceEstimate::new(bandwidth, energy, &resource, &ChainParameters::default())
+    }
+
+    #[test]
+    fn test_burn() {
+        // a fresh deposit address only has the free allowance
+        let fresh = AccountResource {
+            free_net_limit: 600,
+            ..Default::default()
+        };
+        let trx = estimate(268, 0, fresh.clone());
+        assert!(trx.covered());
+        assert_eq!(trx.burn(), 0);
+
+        let usdt = estimate(345, 64_285, fresh);
+        assert!(usdt.bandwidth_covered());
+        assert!(!usdt.energy_covered());
+        assert_eq!(usdt.burn(), 64_285 * 210);
+
+        // bandwidth is burnt in full when no source covers it alone
+        let used = AccountResource {
+            free_net_limit: 600,
+            free_net_used: 400,
+            net_limit: 300,
+            net_used: 100,
+            energy_limit: 70_000,
+            energy_used: 20_000,
+            ..Default::default()
+        };
+        let staked = estimate(345, 64_285, used);
+        assert!(!staked.bandwidth_covered());
+        assert_eq!(staked.bandwidth_burn(), 345_000);
+        assert_eq!(staked.energy_burn(), 14_285 * 210);
+        assert_eq!(staked.burn(), 345_000 + 14_285 * 210);
+    }
+
+    #[test]
+    fn test_activation_burn() {
+        // only staked bandwidth pays for the creation of an account
+        let staked = AccountResource {
+            free_net_limit: 600,
+            net_limit: 300,
+            ..Default::default()
+        };
+        let trx = estimate(268, 0, staked).activating();
+        assert!(trx.bandwidth_covered());
+        assert!(!trx.covered());
+        assert_eq!(trx.burn(), 1_000_000);
+
+        let fresh = AccountResource {
+            free_net_limit: 600,
+            ..Default::default()
+        };
+        let trx = estimate(268, 0, fresh).activating();
+        assert!(!trx.bandwidth_covered());
+        assert_eq!(trx.bandwidth_burn(), 100_000);
+        assert_eq!(trx.burn(), 1_100_000);
+    }
+}
@@ -0,0 +1,288 @@
+//! Stake 2.0: staking TRX for bandwidth and energy, and delegating them.
+//!
+//! Staked TRX gives the account resources in proportion to its share of all stakes.
+//! Unstaking takes 14 days, after which the TRX has to be withdrawn to the balance.
+//! Resources of staked TRX can be delegated to other accounts, optionally locked for a
+//! number of blocks, and taken back at any time after.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{error::Error, types::transfer_receipt::TransferReceipt};
+
+use super::{
+    address::tron_address_to_hex,
+    api::{AccountResource, DelegatedResource, Resource, TronClient, UnfrozenV2},
+    TronWallet,
+};
+
+/// Stakes of an account and the resources they give.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StakeSummary {
+    /// TRX staked for bandwidth and energy, in sun, delegated stakes excluded.
+    pub frozen_bandwidth: u64,
+    pub frozen_energy: u64,
+    /// Staked TRX whose resources can still be delegated, in sun.
+    pub delegatable_bandwidth: u64,
+    pub delegatable_energy: u64,
+    /// Pending unstakes.
+    pub unfreezing: Vec<UnfrozenV2>,
+    /// Expired unstakes, in sun, waiting to be withdrawn.
+    pub withdrawable: u64,
+    /// Bandwidth and energy left today.
+    pub resource: AccountResource,
+}
+
+impl TronWallet {
+    /// Stakes `amount` sun of the address at `index` for `resource`.
+    pub async fn freeze(
+        &self,
+        index: u32,
+        amount: u64,
+        resource: Resource,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let tx = client
+            .freeze_balance_v2(&self.tron_api_address_by_index(index)?, amount, resource)
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Starts unstaking `amount` sun staked for `resource`.
+    pub async fn unfreeze(
+        &self,
+        index: u32,
+        amount: u64,
+        resource: Resource,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let tx = client
+            .unfreeze_balance_v2(&self.tron_api_address_by_index(index)?, amount, resource)
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Withdraws the expired unstakes of the address at `index` to its balance.
+    pub async fn withdraw_unfrozen(
+        &self,
+        index: u32,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let tx = client
+            .withdraw_expire_unfreeze(&self.tron_api_address_by_index(index)?)
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Delegates the `resource` of `amount` staked sun to `receiver`, locked for
+    /// `lock_period` blocks if set.
+    pub async fn delegate(
+        &self,
+        index: u32,
+        receiver: &str,
+        amount: u64,
+        resource: Resource,
+        lock_period: Option<u64>,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let tx = client
+            .delegate_resource(
+                &self.tron_api_address_by_index(index)?,
+                &tron_address_to_hex(receiver)?,
+                amount,
+                resource,
+                lock_period,
+            )
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Takes back the `resource` of `amount` sun delegated to `receiver`.
+    pub async fn undelegate(
+        &self,
+        index: u32,
+        receiver: &str,
+        amount: u64,
+        resource: Resource,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = TronClient::new(provider);
+        let tx = client
+            .undelegate_resource(
+                &self.tron_api_address_by_index(index)?,
+                &tron_address_to_hex(receiver)?,
+                amount,
+                resource,
+            )
+            .await?;
+        self.tron_send(index, tx, &client).await
+    }
+
+    /// Resources the address at `index` delegates, one entry per receiver.
+    pub async fn delegations(
+        &self,
+        index: u32,
+        provider: &str,
+    ) -> Result<Vec<DelegatedResource>, Error> {
+        let client = TronClient::new(provider);
+        let owner = self.tron_api_address_by_index(index)?;
+        let mut delegations = vec![];
+        for receiver in client.get_delegation_index(&owner).await?.to_accounts {
+            delegations.extend(client.get_delegated_resource(&owner, &receiver).await?);
+        }
+        Ok(delegations)
+    }
+
+    /// Stakes of the address at `index` and the resources it has available.
+    pub async fn stake_summary(&self, index: u32, provider: &str) -> Result<StakeSummary, Error> {
+        let client = TronClient::new(provider);
+        let owner = self.tron_api_address_by_index(index)?;
+        let Some(account) = client.get_account(&owner).await? else {
+            return Ok(StakeSummary::default());
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        Ok(StakeSummary {
+            frozen_bandwidth: account.frozen(Resource::Bandwidth),
+            frozen_energy: account.frozen(Resource::Energy),
+            delegatable_bandwidth: client
+                .get_can_delegated_max_size(&owner, Resource::Bandwidth)
+                .await?,
+            delegatable_energy: client
+                .get_can_delegated_max_size(&owner, Resource::Energy)
+                .await?,
+            withdrawable: account.withdrawable(now),
+            unfreezing: account.unfrozen_v2,
+            resource: client.get_account_resource(&owner).await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test_server::{Route, TestServer},
+        wallet::{
+            tron::{
+                api::Transaction,
+                tests::{unsigned_tx, wallet},
+            },
+            Wallet,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_freeze_and_delegate() {
+        let wallet = wallet();
+        let unsigned = unsigned_tx("0a02c1d2");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let server = TestServer::start(vec![
+            Route::post("/wallet/freezebalancev2", &unsigned),
+            Route::post("/wallet/delegateresource", &unsigned),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(r#"{{"id":"{}","blockNumber":61234567}}"#, tx.tx_id),
+            ),
+        ])
+        .await;
+        let receiver = wallet.address(1).unwrap();
+        wallet
+            .freeze(0, 500_000_000, Resource::Energy, &server.url)
+            .await
+            .unwrap();
+        let receipt = wallet
+            .delegate(
+                0,
+                &receiver,
+                100_000_000,
+                Resource::Energy,
+                Some(28_800),
+                &server.url,
+            )
+            .await
+            .unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+
+        let requests = server.requests();
+        let freeze: serde_json::Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(freeze["frozen_balance"], 500_000_000);
+        assert_eq!(freeze["resource"], "ENERGY");
+        let delegate = requests
+            .iter()
+            .find(|r| r.path == "/wallet/delegateresource")
+            .unwrap();
+        let delegate: serde_json::Value = serde_json::from_str(&delegate.body).unwrap();
+        assert_eq!(
+            delegate["receiver_address"],
+            tron_address_to_hex(&receiver).unwrap()
+        );
+        assert_eq!(delegate["balance"], 100_000_000);
+        assert_eq!(delegate["lock"], true);
+        assert_eq!(delegate["lock_period"], 28_800);
+    }
+
+    #[tokio::test]
+    async fn test_stake_summary_and_delegations() {
+        let wallet = wallet();
+        let owner = wallet.tron_api_address_by_index(0).unwrap();
+        let receiver = wallet.tron_api_address_by_index(1).unwrap();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}","balance":1000000,"frozenV2":[{{"amount":2000000}},{{"type":"ENERGY","amount":900000000}},{{"type":"TRON_POWER"}}],"unfrozenV2":[{{"type":"ENERGY","unfreeze_amount":5000000,"unfreeze_expire_time":1600000000000}},{{"unfreeze_amount":7000000,"unfreeze_expire_time":99999999999999}}]}}"#,
+                    owner
+                ),
+            ),
+            Route::post("/wallet/getcandelegatedmaxsize", r#"{"max_size":800000000}"#),
+            Route::post(
+                "/wallet/getaccountresource",
+                r#"{"freeNetLimit":600,"EnergyLimit":120000}"#,
+            ),
+            Route::post(
+                "/wallet/getdelegatedresourceaccountindexv2",
+                &format!(r#"{{"account":"{}","toAccounts":["{}"]}}"#, owner, receiver),
+            ),
+            Route::post(
+                "/wallet/getdelegatedresourcev2",
+                &format!(
+                    r#"{{"delegatedResource":[{{"from":"{}","to":"{}","frozen_balance_for_energy":100000000}}]}}"#,
+                    owner, receiver
+                ),
+            ),
+        ])
+        .await;
+
+        let summary = wallet.stake_summary(0, &server.url).await.unwrap();
+        assert_eq!(summary.frozen_bandwidth, 2_000_000);
+        assert_eq!(summary.frozen_energy, 900_000_000);
+        assert_eq!(summary.delegatable_energy, 800_000_000);
+        assert_eq!(summary.withdrawable, 5_000_000);
+        assert_eq!(summary.unfreezing.len(), 2);
+        assert_eq!(summary.unfreezing[1].resource, Resource::Bandwidth);
+        assert_eq!(summary.resource.energy(), 120_000);
+
+        let delegations = wallet.delegations(0, &server.url).await.unwrap();
+        assert_eq!(delegations.len(), 1);
+        assert_eq!(delegations[0].to, receiver);
+        assert_eq!(delegations[0].frozen_balance(Resource::Energy), 100_000_000);
+        let request = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == "/wallet/getdelegatedresourcev2")
+            .unwrap();
+        let request: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        assert_eq!(request["fromAddress"], owner);
+        assert_eq!(request["toAddress"], receiver);
+    }
+}
@@ -0,0 +1,562 @@
+//! TRC-20 sweeps of deposit addresses paid with delegated energy.
+//!
+//! A TRC-20 transfer from a deposit address without energy burns its TRX, which deposit
+//! addresses usually don't have. Instead, a source address with staked TRX delegates the
+//! energy the transfer needs to the deposit address, the deposit address sends its whole
+//! token balance, and the source takes the delegation back. Bandwidth still comes from
+//! the deposit address's free daily allowance.
+//!
+//! Sweeps are planned first, which only reads the chain; a dry run stops there.
+
+use std::fmt;
+
+use ethers::types::U256;
+
+use crate::error::Error;
+
+use super::{
+    address::tron_address_to_hex,
+    api::{AccountResource, Resource, TronClient},
+    resources::ResourceEstimate,
+    TronWallet,
+};
+
+/// Extra share of the estimated energy delegated, as the energy price of a contract can
+/// rise between the estimate and the transfer.
+pub const ENERGY_MARGIN_PERCENT: u64 = 10;
+
+/// Smallest delegation the chain accepts, 1 TRX.
+pub const MIN_DELEGATION: u64 = 1_000_000;
+
+const SUN_PER_TRX: u128 = 1_000_000;
+
+/// Where a deposit address stands in a sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SweepStatus {
+    /// Ready to sweep.
+    Planned,
+    /// No token balance, nothing to sweep.
+    Empty,
+    /// The address has never received TRX, so it can't receive energy.
+    NotActivated,
+    /// The address lacks the TRX its bandwidth burns, in sun.
+    InsufficientTrx {
+        needed: u64,
+        balance: u64,
+    },
+    /// The source can't delegate as much staked TRX as the transfer needs, in sun.
+    InsufficientStake {
+        needed: u64,
+        delegatable: u64,
+    },
+    Swept {
+        txid: String,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+impl fmt::Display for SweepStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepStatus::Planned => write!(f, "planned"),
+            SweepStatus::Empty => write!(f, "empty"),
+            SweepStatus::NotActivated => write!(f, "not activated"),
+            SweepStatus::InsufficientTrx { needed, balance } => write!(
+                f,
+                "needs {} sun of TRX for bandwidth, has {}",
+                needed, balance
+            ),
+            SweepStatus::InsufficientStake {
+                needed,
+                delegatable,
+            } => write!(
+                f,
+                "needs {} sun of delegated stake, the source can delegate {}",
+                needed, delegatable
+            ),
+            SweepStatus::Swept { txid } => write!(f, "swept in {}", txid),
+            SweepStatus::Failed { reason } => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
+/// A deposit address of a sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepItem {
+    pub index: u32,
+    pub address: String,
+    /// Token balance to sweep.
+    pub amount: U256,
+    pub estimate: ResourceEstimate,
+    /// Staked TRX whose energy is delegated for the transfer, in sun, 0 if the address
+    /// has enough energy.
+    pub delegation: u64,
+    pub status: SweepStatus,
+}
+
+/// Sweep of `token` from deposit addresses to `to`, with the energy of `source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepPlan {
+    pub source: u32,
+    pub token: String,
+    pub to: String,
+    pub items: Vec<SweepItem>,
+}
+
+impl SweepPlan {
+    /// Token amount of the items still to sweep.
+    pub fn planned_amount(&self) -> U256 {
+        self.items
+            .iter()
+            .filter(|item| item.status == SweepStatus::Planned)
+            .fold(U256::zero(), |total, item| total + item.amount)
+    }
+
+    /// Token amount of the swept items.
+    pub fn swept_amount(&self) -> U256 {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.status, SweepStatus::Swept { .. }))
+            .fold(U256::zero(), |total, item| total + item.amount)
+    }
+}
+
+/// Staked sun whose energy covers what `estimate` lacks, with `ENERGY_MARGIN_PERCENT`,
+/// at the network-wide ratio of energy to staked TRX in `resource`.
+pub fn delegation_for(estimate: &ResourceEstimate, resource: &AccountResource) -> u64 {
+    let energy = estimate.energy * (100 + ENERGY_MARGIN_PERCENT) / 100;
+    let missing = energy.saturating_sub(estimate.available_energy) as u128;
+    if missing == 0 || resource.total_energy_limit == 0 {
+        return 0;
+    }
+    // the total weight is in TRX
+    let limit = resource.total_energy_limit as u128;
+    let sun = (missing * resource.total_energy_weight as u128 * SUN_PER_TRX).div_ceil(limit);
+    (sun as u64).max(MIN_DELEGATION)
+}
+
+impl TronWallet {
+    /// Plans the sweep of `token` from the addresses at `indices` to `to`, with energy
+    /// delegated by the address at `source`. Only reads the chain. An address whose
+    /// reads fail is marked `Failed` without stopping the plan.
+    pub async fn plan_token_sweep(
+        &self,
+        source: u32,
+        token: &str,
+        to: &str,
+        indices: &[u32],
+        provider: &str,
+    ) -> Result<SweepPlan, Error> {
+        tron_address_to_hex(to)?;
+        let client = TronClient::new(provider);
+        let delegatable = client
+            .get_can_delegated_max_size(&self.tron_api_address_by_index(source)?, Resource::Energy)
+            .await?;
+        let mut items = vec![];
+        for &index in indices {
+            let mut item = SweepItem {
+                index,
+                address: self.tron_address_by_index(index)?,
+                amount: U256::zero(),
+                estimate: ResourceEstimate::default(),
+                delegation: 0,
+                status: SweepStatus::Planned,
+            };
+            if let Err(error) = self
+                .plan_sweep_item(&mut item, token, to, delegatable, &client, provider)
+                .await
+            {
+                item.status = SweepStatus::Failed {
+                    reason: error.to_string(),
+                };
+            }
+            items.push(item);
+        }
+        Ok(SweepPlan {
+            source,
+            token: token.to_owned(),
+            to: to.to_owned(),
+            items,
+        })
+    }
+
+    async fn plan_sweep_item(
+        &self,
+        item: &mut SweepItem,
+        token: &str,
+        to: &str,
+        delegatable: u64,
+        client: &TronClient,
+        provider: &str,
+    ) -> Result<(), Error> {
+        let index = item.index;
+        let hex_address = self.tron_api_address_by_index(index)?;
+        let Some(account) = client.get_account(&hex_address).await? else {
+            item.status = SweepStatus::NotActivated;
+            return Ok(());
+        };
+        item.amount = self
+            .tron_balance_token_by_index(index, provider, token)
+            .await?;
+        if item.amount.is_zero() {
+            item.status = SweepStatus::Empty;
+            return Ok(());
+        }
+        item.estimate = self
+            .estimate_transfer_token(index, token, to, item.amount, provider)
+            .await?;
+        let resource = client.get_account_resource(&hex_address).await?;
+        item.delegation = delegation_for(&item.estimate, &resource);
+        item.status = if item.estimate.bandwidth_burn() > account.balance {
+            SweepStatus::InsufficientTrx {
+                needed: item.estimate.bandwidth_burn(),
+                balance: account.balance,
+            }
+        } else if item.delegation > delegatable {
+            SweepStatus::InsufficientStake {
+                needed: item.delegation,
+                delegatable,
+            }
+        } else {
+            SweepStatus::Planned
+        };
+        Ok(())
+    }
+
+    /// Sweeps the planned items of `plan` one by one, updating their status. A failed
+    /// item doesn't stop the sweep, and the delegation is taken back in any case.
+    pub async fn execute_token_sweep(&self, plan: &mut SweepPlan, provider: &str) {
+        for item in plan
+            .items
+            .iter_mut()
+            .filter(|item| item.status == SweepStatus::Planned)
+        {
+            item.status = self
+                .sweep_item(plan.source, &plan.token, &plan.to, item, provider)
+                .await;
+        }
+    }
+
+    async fn sweep_item(
+        &self,
+        source: u32,
+        token: &str,
+        to: &str,
+        item: &SweepItem,
+        provider: &str,
+    ) -> SweepStatus {
+        let failed = |error: Error| SweepStatus::Failed {
+            reason: error.to_string(),
+        };
+        if item.delegation > 0 {
+            if let Err(error) = self
+                .delegate(
+                    source,
+                    &item.address,
+                    item.delegation,
+                    Resource::Energy,
+                    None,
+                    provider,
+                )
+                .await
+            {
+                return failed(error);
+            }
+        }
+        let transfer = self
+            .tron_transfer_token(item.index, token, to, item.amount, provider)
+            .await;
+        let reclaim = match item.delegation {
+            0 => Ok(()),
+            delegation => self
+                .undelegate(
+                    source,
+                    &item.address,
+                    delegation,
+                    Resource::Energy,
+                    provider,
+                )
+                .await
+                .map(|_| ()),
+        };
+        match (transfer, reclaim) {
+            (Ok(receipt), Ok(())) => SweepStatus::Swept {
+                txid: receipt.txid(),
+            },
+            (Ok(receipt), Err(error)) => SweepStatus::Failed {
+                reason: format!(
+                    "swept in {} but the delegation was not taken back: {}",
+                    receipt.txid(),
+                    error
+                ),
+            },
+            (Err(error), _) => failed(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test_server::{Route, TestServer},
+        wallet::{
+            tron::{
+                api::Transaction,
+                tests::{unsigned_tx, wallet},
+            },
+            Wallet,
+        },
+    };
+
+    const USDT: &str = "TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t";
+
+    fn resource() -> AccountResource {
+        AccountResource {
+            free_net_limit: 600,
+            total_energy_limit: 180_000_000_000,
+            total_energy_weight: 19_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_delegation_for() {
+        let estimate = ResourceEstimate {
+            energy: 64_285,
+            ..Default::default()
+        };
+        // 70_713 energy at 9.47 energy per staked TRX
+        assert_eq!(delegation_for(&estimate, &resource()), 7_464_150_000);
+
+        let covered = ResourceEstimate {
+            available_energy: 80_000,
+            ..estimate
+        };
+        assert_eq!(delegation_for(&covered, &resource()), 0);
+        let almost = ResourceEstimate {
+            available_energy: 70_710,
+            ..estimate
+        };
+        assert_eq!(delegation_for(&almost, &resource()), MIN_DELEGATION);
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_execute() {
+        let wallet = wallet();
+        let unsigned = unsigned_tx("0a02d1e2");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let unactivated = wallet.tron_api_address_by_index(2).unwrap();
+        let server = TestServer::start(vec![
+            Route {
+                body_contains: Some(unactivated),
+                ..Route::post("/wallet/getaccount", "{}")
+            },
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}"}}"#,
+                    wallet.tron_api_address_by_index(1).unwrap()
+                ),
+            ),
+            Route::post(
+                "/wallet/getcandelegatedmaxsize",
+                r#"{"max_size":10000000000}"#,
+            ),
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"energy_used":64285,"constant_result":["{:064x}"]}}"#,
+                    100_000_000
+                ),
+            ),
+            Route::post(
+                "/wallet/getnowblock",
+                r#"{"blockID":"0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718","block_header":{"raw_data":{"number":61272498,"timestamp":1713000000000}}}"#,
+            ),
+            Route::post(
+                "/wallet/getaccountresource",
+                r#"{"freeNetLimit":600,"TotalEnergyLimit":180000000000,"TotalEnergyWeight":19000000000}"#,
+            ),
+            Route::post(
+                "/wallet/getchainparameters",
+                r#"{"chainParameter":[{"key":"getEnergyFee","value":210}]}"#,
+            ),
+            Route::post("/wallet/delegateresource", &unsigned),
+            Route::post("/wallet/undelegateresource", &unsigned),
+            Route::post(
+                "/wallet/triggersmartcontract",
+                &format!(r#"{{"result":{{"result":true}},"transaction":{}}}"#, unsigned),
+            ),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(
+                    r#"{{"id":"{}","blockNumber":1,"receipt":{{"result":"SUCCESS"}}}}"#,
+                    tx.tx_id
+                ),
+            ),
+        ])
+        .await;
+
+        let to = wallet.address(0).unwrap();
+        let mut plan = wallet
+            .plan_token_sweep(0, USDT, &to, &[1, 2], &server.url)
+            .await
+            .unwrap();
+        assert_eq!(plan.items[0].status, SweepStatus::Planned);
+        assert_eq!(plan.items[0].amount, U256::from(100_000_000));
+        assert_eq!(plan.items[0].delegation, 7_464_150_000);
+        assert_eq!(plan.items[0].estimate.burn(), 64_285 * 210);
+        assert_eq!(plan.items[1].status, SweepStatus::NotActivated);
+        assert_eq!(plan.planned_amount(), U256::from(100_000_000));
+        // planning is a dry run
+        assert!(server
+            .requests()
+            .iter()
+            .all(|r| !r.path.contains("delegateresource") && !r.path.contains("broadcast")));
+
+        wallet.execute_token_sweep(&mut plan, &server.url).await;
+        assert_eq!(
+            plan.items[0].status,
+            SweepStatus::Swept {
+                txid: tx.tx_id.clone()
+            }
+        );
+        assert_eq!(plan.items[1].status, SweepStatus::NotActivated);
+        assert_eq!(plan.swept_amount(), U256::from(100_000_000));
+
+        let paths: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|r| r.path)
+            .filter(|path| {
+                [
+                    "/wallet/delegateresource",
+                    "/wallet/triggersmartcontract",
+                    "/wallet/undelegateresource",
+                ]
+                .contains(&path.as_str())
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/wallet/delegateresource",
+                "/wallet/triggersmartcontract",
+                "/wallet/undelegateresource"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_read_fails_only_its_item() {
+        let wallet = wallet();
+        let broken = wallet.tron_api_address_by_index(2).unwrap();
+        let server = TestServer::start(vec![
+            Route {
+                body_contains: Some(broken),
+                status: 502,
+                ..Route::post("/wallet/getaccountresource", "Bad Gateway")
+            },
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}","balance":1000000}}"#,
+                    wallet.tron_api_address_by_index(1).unwrap()
+                ),
+            ),
+            Route::post(
+                "/wallet/getcandelegatedmaxsize",
+                r#"{"max_size":10000000000}"#,
+            ),
+            Route::post(
+                "/wallet/triggerconstantcontract",
+                &format!(
+                    r#"{{"result":{{"result":true}},"energy_used":64285,"constant_result":["{:064x}"]}}"#,
+                    100_000_000
+                ),
+            ),
+            Route::post(
+                "/wallet/getaccountresource",
+                r#"{"freeNetLimit":600,"TotalEnergyLimit":180000000000,"TotalEnergyWeight":19000000000}"#,
+            ),
+            Route::post(
+                "/wallet/getnowblock",
+                r#"{"blockID":"0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718","block_header":{"raw_data":{"number":61272498,"timestamp":1713000000000}}}"#,
+            ),
+            Route::post(
+                "/wallet/getchainparameters",
+                r#"{"chainParameter":[{"key":"getEnergyFee","value":210}]}"#,
+            ),
+        ])
+        .await;
+
+        let plan = wallet
+            .plan_token_sweep(
+                0,
+                USDT,
+                &wallet.address(0).unwrap(),
+                &[1, 2, 3],
+                &server.url,
+            )
+            .await
+            .unwrap();
+        assert_eq!(plan.items.len(), 3);
+        assert_eq!(plan.items[0].status, SweepStatus::Planned);
+        assert!(matches!(plan.items[1].status, SweepStatus::Failed { .. }));
+        assert_eq!(plan.items[1].address, wallet.address(2).unwrap());
+        assert_eq!(plan.items[2].status, SweepStatus::Planned);
+        assert_eq!(plan.planned_amount(), U256::from(200_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_failed_transfer_reclaims_energy() {
+        let wallet = wallet();
+        let unsigned = unsigned_tx("0a02d1e3");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let server = TestServer::start(vec![
+            Route::post("/wallet/delegateresource", &unsigned),
+            Route::post("/wallet/undelegateresource", &unsigned),
+            Route::post(
+                "/wallet/triggersmartcontract",
+                r#"{"result":{"code":"CONTRACT_VALIDATE_ERROR","message":""}}"#,
+            ),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(r#"{{"id":"{}","blockNumber":1}}"#, tx.tx_id),
+            ),
+        ])
+        .await;
+        let mut plan = SweepPlan {
+            source: 0,
+            token: USDT.to_owned(),
+            to: wallet.address(0).unwrap(),
+            items: vec![SweepItem {
+                index: 1,
+                address: wallet.address(1).unwrap(),
+                amount: U256::from(5),
+                estimate: ResourceEstimate::default(),
+                delegation: MIN_DELEGATION,
+                status: SweepStatus::Planned,
+            }],
+        };
+        wallet.execute_token_sweep(&mut plan, &server.url).await;
+        assert!(
+            matches!(&plan.items[0].status, SweepStatus::Failed { reason } if reason.contains("CONTRACT_VALIDATE_ERROR"))
+        );
+        assert!(server
+            .requests()
+            .iter()
+            .any(|r| r.path == "/wallet/undelegateresource"));
+    }
+}
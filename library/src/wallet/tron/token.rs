@@ -0,0 +1,207 @@
+//! TRC-10 and TRC-20 tokens.
+//!
+//! TRC-10 assets are native to the chain: they are identified by a numeric id, their
+//! balances are part of the account, and transfers only consume bandwidth. TRC-20 tokens
+//! are contracts, identified by their address.
+
+use ethers::{
+    abi::{self, ParamType},
+    types::U256,
+};
+
+use crate::{error::Error, types::token_data::TokenData};
+
+use super::{address::tron_address_to_hex, api::TronClient, TronWallet};
+
+/// A token as `Settings::tron_tokens` lists it: the id of a TRC-10 asset, e.g.
+/// `1002000`, or the base58 address of a TRC-20 contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TronToken {
+    Trc10(String),
+    Trc20(String),
+}
+
+impl From<&str> for TronToken {
+    fn from(token: &str) -> Self {
+        if !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit()) {
+            TronToken::Trc10(token.to_owned())
+        } else {
+            TronToken::Trc20(token.to_owned())
+        }
+    }
+}
+
+impl TronWallet {
+    /// Balance of `token` at the address at `index`, with the token's symbol and decimals.
+    pub async fn token_data(
+        &self,
+        index: u32,
+        token: &str,
+        provider: &str,
+    ) -> Result<TokenData, Error> {
+        let client = TronClient::new(provider);
+        let owner = self.tron_api_address_by_index(index)?;
+        let (symbol, decimals) = match TronToken::from(token) {
+            TronToken::Trc10(id) => {
+                let asset = client
+                    .get_asset_issue_by_id(&id)
+                    .await?
+                    .ok_or_else(|| Error::TronApiError(format!("No TRC-10 asset {}", id)))?;
+                (asset.symbol(), asset.precision)
+            }
+            TronToken::Trc20(address) => {
+                let contract = tron_address_to_hex(&address)?;
+                let symbol = client
+                    .trigger_constant_contract(&owner, &contract, "symbol()", "")
+                    .await?;
+                let decimals = client
+                    .trigger_constant_contract(&owner, &contract, "decimals()", "")
+                    .await?;
+                let symbol = abi::decode(&[ParamType::String], &symbol.output)?
+                    .pop()
+                    .and_then(|token| token.into_string())
+                    .unwrap_or_default();
+                let decimals = abi::decode(&[ParamType::Uint(8)], &decimals.output)?
+                    .pop()
+                    .and_then(|token| token.into_uint())
+                    .unwrap_or_default();
+                (symbol, decimals.low_u32() as u8)
+            }
+        };
+        let balance = self
+            .tron_balance_token_by_index(index, provider, token)
+            .await?;
+        Ok(TokenData {
+            balance,
+            balance_f: ethers::utils::format_units(balance, decimals as u32)
+                .map_or(0.0, |units| units.parse().unwrap_or_default()),
+            decimals,
+            symbol,
+            address: token.to_owned(),
+        })
+    }
+
+    /// Balance of the TRC-10 asset `id` at the address at `index`.
+    pub(super) async fn tron_balance_asset_by_index(
+        &self,
+        index: u32,
+        id: &str,
+        client: &TronClient,
+    ) -> Result<U256, Error> {
+        let account = client
+            .get_account(&self.tron_api_address_by_index(index)?)
+            .await?;
+        Ok(account.map_or(0, |account| account.asset(id)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::test_server::{Route, TestServer},
+        wallet::{
+            tron::{
+                api::Transaction,
+                tests::{unsigned_tx, wallet},
+            },
+            Wallet,
+        },
+    };
+
+    #[test]
+    fn test_token_syntax() {
+        assert_eq!(
+            TronToken::from("1002000"),
+            TronToken::Trc10("1002000".to_owned())
+        );
+        assert_eq!(
+            TronToken::from("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t"),
+            TronToken::Trc20("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trc10_balance_and_metadata() {
+        let wallet = wallet();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccount",
+                &format!(
+                    r#"{{"address":"{}","balance":1000000,"assetV2":[{{"key":"1000001","value":5}},{{"key":"1002000","value":123456789}}]}}"#,
+                    wallet.tron_api_address_by_index(0).unwrap()
+                ),
+            ),
+            Route::post(
+                "/wallet/getassetissuebyid",
+                &format!(
+                    r#"{{"id":"1002000","name":"{}","abbr":"{}","precision":6,"total_supply":990000000000000000}}"#,
+                    hex::encode("BitTorrent"),
+                    hex::encode("BTT")
+                ),
+            ),
+        ])
+        .await;
+
+        let balance = wallet
+            .balance_token(0, "1002000", &server.url)
+            .await
+            .unwrap();
+        assert_eq!(balance, U256::from(123_456_789));
+        assert!(wallet
+            .balance_token(0, "1000002", &server.url)
+            .await
+            .unwrap()
+            .is_zero());
+
+        let data = wallet.token_data(0, "1002000", &server.url).await.unwrap();
+        assert_eq!(data.symbol, "BTT");
+        assert_eq!(data.decimals, 6);
+        assert_eq!(data.balance_f, 123.456789);
+        let request = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == "/wallet/getassetissuebyid")
+            .unwrap();
+        assert_eq!(request.body, r#"{"value":"1002000"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_trc10_transfer() {
+        let wallet = wallet();
+        let unsigned = unsigned_tx("0a02e1f2");
+        let tx: Transaction = serde_json::from_str(&unsigned).unwrap();
+        let to = wallet.address(1).unwrap();
+        let server = TestServer::start(vec![
+            Route::post(
+                "/wallet/getaccount",
+                &format!(r#"{{"address":"{}"}}"#, tron_address_to_hex(&to).unwrap()),
+            ),
+            Route::post("/wallet/transferasset", &unsigned),
+            Route::post(
+                "/wallet/broadcasttransaction",
+                &format!(r#"{{"result":true,"txid":"{}"}}"#, tx.tx_id),
+            ),
+            Route::post(
+                "/wallet/gettransactioninfobyid",
+                &format!(r#"{{"id":"{}","blockNumber":61234567}}"#, tx.tx_id),
+            ),
+        ])
+        .await;
+
+        let receipt = wallet
+            .transfer_token(0, "1002000", &to, U256::from(1_000_000), &server.url)
+            .await
+            .unwrap();
+        assert_eq!(receipt.txid(), tx.tx_id);
+        let transfer = server
+            .requests()
+            .into_iter()
+            .find(|r| r.path == "/wallet/transferasset")
+            .unwrap();
+        let transfer: serde_json::Value = serde_json::from_str(&transfer.body).unwrap();
+        assert_eq!(transfer["asset_name"], hex::encode("1002000"));
+        assert_eq!(transfer["to_address"], tron_address_to_hex(&to).unwrap());
+        assert_eq!(transfer["amount"], 1_000_000);
+    }
+}
@@ -0,0 +1,573 @@
+//! Offline construction of TRON transactions.
+//!
+//! `Transaction.raw` is encoded locally from a reference block, so a host without a
+//! node can build and sign transactions; the node only checks that the reference block
+//! is recent. Signed transactions are the API's `Transaction` JSON, for
+//! `broadcasttransaction`, or the protobuf encoding as hex, for `broadcasthex`.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethers::{
+    abi::{self, Token},
+    types::U256,
+    utils::id,
+};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::Error,
+    utils::{
+        amount::amount_as,
+        protobuf::{any, ProtoWriter},
+    },
+};
+
+use super::{
+    abi_address,
+    address::tron_hex_to_bytes,
+    api::{BlockHeader, Permission, PermissionType, Transaction},
+};
+
+/// Validity of a transaction after its reference block, as TronWeb sets it.
+pub const DEFAULT_EXPIRATION: Duration = Duration::from_secs(60);
+
+/// Longest validity nodes accept, 24 hours.
+pub const MAX_EXPIRATION: Duration = Duration::from_secs(24 * 60 * 60);
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/protocol.";
+
+/// Room nodes charge for the result of each contract on top of the transaction's size.
+const MAX_RESULT_SIZE_IN_TX: u64 = 64;
+
+const SIGNATURE_SIZE: usize = 65;
+
+/// The contract, TRON's name for the operation, a transaction carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Contract {
+    /// TRX transfer of `amount` sun.
+    Transfer {
+        owner_address: [u8; 21],
+        to_address: [u8; 21],
+        amount: u64,
+    },
+    /// Transfer of `amount` of the TRC-10 asset whose id is `asset_name`.
+    TransferAsset {
+        asset_name: Vec<u8>,
+        owner_address: [u8; 21],
+        to_address: [u8; 21],
+        amount: u64,
+    },
+    /// Call of a smart contract with the ABI encoded `data`, paying `call_value` sun.
+    TriggerSmartContract {
+        owner_address: [u8; 21],
+        contract_address: [u8; 21],
+        call_value: u64,
+        data: Vec<u8>,
+    },
+    /// Replacement of the permissions of `owner_address`. The witness permission is
+    /// only kept by super representatives, and left out here.
+    AccountPermissionUpdate {
+        owner_address: [u8; 21],
+        owner: Permission,
+        actives: Vec<Permission>,
+    },
+}
+
+impl Contract {
+    /// TRX transfer between the hex addresses `owner` and `to`.
+    pub fn transfer(owner: &str, to: &str, amount: U256) -> Result<Self, Error> {
+        Ok(Contract::Transfer {
+            owner_address: tron_hex_to_bytes(owner)?,
+            to_address: tron_hex_to_bytes(to)?,
+            // amounts are int64 on chain
+            amount: amount_as::<i64>(amount)? as u64,
+        })
+    }
+
+    /// Transfer of the TRC-10 asset `id` between the hex addresses `owner` and `to`.
+    pub fn trc10_transfer(owner: &str, id: &str, to: &str, amount: U256) -> Result<Self, Error> {
+        Ok(Contract::TransferAsset {
+            asset_name: id.as_bytes().to_vec(),
+            owner_address: tron_hex_to_bytes(owner)?,
+            to_address: tron_hex_to_bytes(to)?,
+            // amounts are int64 on chain
+            amount: amount_as::<i64>(amount)? as u64,
+        })
+    }
+
+    /// TRC-20 `transfer(to, amount)` call of the contract `token`, all hex addresses.
+    pub fn trc20_transfer(owner: &str, token: &str, to: &str, amount: U256) -> Result<Self, Error> {
+        let mut data = id("transfer(address,uint256)").to_vec();
+        data.extend(abi::encode(&[abi_address(to)?, Token::Uint(amount)]));
+        Ok(Contract::TriggerSmartContract {
+            owner_address: tron_hex_to_bytes(owner)?,
+            contract_address: tron_hex_to_bytes(token)?,
+            call_value: 0,
+            data,
+        })
+    }
+
+    /// Update of the permissions of the hex address `owner`. Key addresses are hex too.
+    pub fn account_permission_update(
+        owner: &str,
+        owner_permission: Permission,
+        actives: Vec<Permission>,
+    ) -> Result<Self, Error> {
+        for key in owner_permission
+            .keys
+            .iter()
+            .chain(actives.iter().flat_map(|active| &active.keys))
+        {
+            tron_hex_to_bytes(&key.address)?;
+        }
+        for active in &actives {
+            if active.operations.len() != 64 {
+                return Err(Error::TronApiError(format!(
+                    "operations of {} are not 32 bytes",
+                    active.permission_name
+                )));
+            }
+            hex::decode(&active.operations)?;
+        }
+        Ok(Contract::AccountPermissionUpdate {
+            owner_address: tron_hex_to_bytes(owner)?,
+            owner: owner_permission,
+            actives,
+        })
+    }
+
+    /// Name of the contract type, e.g. `TransferContract`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Contract::Transfer { .. } => "TransferContract",
+            Contract::TransferAsset { .. } => "TransferAssetContract",
+            Contract::TriggerSmartContract { .. } => "TriggerSmartContract",
+            Contract::AccountPermissionUpdate { .. } => "AccountPermissionUpdateContract",
+        }
+    }
+
+    /// `Transaction.Contract.ContractType` value.
+    fn type_code(&self) -> u64 {
+        match self {
+            Contract::Transfer { .. } => 1,
+            Contract::TransferAsset { .. } => 2,
+            Contract::TriggerSmartContract { .. } => 31,
+            Contract::AccountPermissionUpdate { .. } => 46,
+        }
+    }
+
+    fn encode_value(&self) -> Vec<u8> {
+        match self {
+            Contract::Transfer {
+                owner_address,
+                to_address,
+                amount,
+            } => ProtoWriter::new()
+                .bytes(1, owner_address)
+                .bytes(2, to_address)
+                .uint64(3, *amount)
+                .into_bytes(),
+            Contract::TransferAsset {
+                asset_name,
+                owner_address,
+                to_address,
+                amount,
+            } => ProtoWriter::new()
+                .bytes(1, asset_name)
+                .bytes(2, owner_address)
+                .bytes(3, to_address)
+                .uint64(4, *amount)
+                .into_bytes(),
+            Contract::TriggerSmartContract {
+                owner_address,
+                contract_address,
+                call_value,
+                data,
+            } => ProtoWriter::new()
+                .bytes(1, owner_address)
+                .bytes(2, contract_address)
+                .uint64(3, *call_value)
+                .bytes(4, data)
+                .into_bytes(),
+            Contract::AccountPermissionUpdate {
+                owner_address,
+                owner,
+                actives,
+            } => {
+                let mut writer = ProtoWriter::new()
+                    .bytes(1, owner_address)
+                    .message(2, &encode_permission(owner));
+                for active in actives {
+                    writer = writer.message(4, &encode_permission(active));
+                }
+                writer.into_bytes()
+            }
+        }
+    }
+
+    /// `parameter.value` as the API prints it, without default values.
+    fn json_value(&self) -> Value {
+        let mut value = Map::new();
+        match self {
+            Contract::Transfer {
+                owner_address,
+                to_address,
+                amount,
+            } => {
+                value.insert("owner_address".into(), hex::encode(owner_address).into());
+                value.insert("to_address".into(), hex::encode(to_address).into());
+                if *amount != 0 {
+                    value.insert("amount".into(), (*amount).into());
+                }
+            }
+            Contract::TransferAsset {
+                asset_name,
+                owner_address,
+                to_address,
+                amount,
+            } => {
+                value.insert("asset_name".into(), hex::encode(asset_name).into());
+                value.insert("owner_address".into(), hex::encode(owner_address).into());
+                value.insert("to_address".into(), hex::encode(to_address).into());
+                if *amount != 0 {
+                    value.insert("amount".into(), (*amount).into());
+                }
+            }
+            Contract::TriggerSmartContract {
+                owner_address,
+                contract_address,
+                call_value,
+                data,
+            } => {
+                value.insert("owner_address".into(), hex::encode(owner_address).into());
+                value.insert(
+                    "contract_address".into(),
+                    hex::encode(contract_address).into(),
+                );
+                if *call_value != 0 {
+                    value.insert("call_value".into(), (*call_value).into());
+                }
+                if !data.is_empty() {
+                    value.insert("data".into(), hex::encode(data).into());
+                }
+            }
+            Contract::AccountPermissionUpdate {
+                owner_address,
+                owner,
+                actives,
+            } => {
+                value.insert("owner_address".into(), hex::encode(owner_address).into());
+                value.insert("owner".into(), json!(owner));
+                value.insert("actives".into(), json!(actives));
+            }
+        }
+        Value::Object(value)
+    }
+
+    fn encode(&self, permission_id: u32) -> Vec<u8> {
+        let type_url = format!("{}{}", TYPE_URL_PREFIX, self.type_name());
+        ProtoWriter::new()
+            .uint64(1, self.type_code())
+            .message(2, &any(&type_url, &self.encode_value()))
+            .uint64(5, permission_id.into())
+            .into_bytes()
+    }
+
+    fn to_json(&self, permission_id: u32) -> Value {
+        let mut contract = json!({
+            "type": self.type_name(),
+            "parameter": {
+                "type_url": format!("{}{}", TYPE_URL_PREFIX, self.type_name()),
+                "value": self.json_value(),
+            },
+        });
+        if permission_id != 0 {
+            contract["Permission_id"] = permission_id.into();
+        }
+        contract
+    }
+}
+
+/// `Permission` message. Key addresses and operations were checked to be hex.
+fn encode_permission(permission: &Permission) -> Vec<u8> {
+    let permission_type = match permission.permission_type {
+        PermissionType::Owner => 0,
+        PermissionType::Witness => 1,
+        PermissionType::Active => 2,
+    };
+    let mut writer = ProtoWriter::new()
+        .uint64(1, permission_type)
+        .uint64(2, permission.id.into())
+        .string(3, &permission.permission_name)
+        .uint64(4, permission.threshold)
+        .bytes(6, &hex::decode(&permission.operations).unwrap_or_default());
+    for key in &permission.keys {
+        let address = hex::decode(&key.address).unwrap_or_default();
+        writer = writer.message(
+            7,
+            &ProtoWriter::new()
+                .bytes(1, &address)
+                .uint64(2, key.weight)
+                .into_bytes(),
+        );
+    }
+    writer.into_bytes()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// `Transaction.raw`, the part of a transaction its id and signatures commit to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransaction {
+    /// Bytes 6 and 7 of the reference block's number.
+    pub ref_block_bytes: [u8; 2],
+    /// Bytes 8 to 15 of the reference block's id.
+    pub ref_block_hash: [u8; 8],
+    /// Milliseconds since the epoch.
+    pub expiration: u64,
+    /// Milliseconds since the epoch.
+    pub timestamp: u64,
+    /// Most the transaction may burn, in sun, for smart contract calls.
+    pub fee_limit: u64,
+    pub contract: Contract,
+    /// Permission of the owner the transaction is signed under, 0 for the owner
+    /// permission and from 2 for active ones.
+    pub permission_id: u32,
+}
+
+impl RawTransaction {
+    /// Transaction of `contract` referencing `block`, created now and expiring
+    /// `DEFAULT_EXPIRATION` after the block.
+    pub fn new(contract: Contract, block: &BlockHeader) -> Result<Self, Error> {
+        let id = hex::decode(&block.id)?;
+        if id.len() != 32 {
+            return Err(Error::TronApiError(format!(
+                "invalid block id {}",
+                block.id
+            )));
+        }
+        Ok(RawTransaction {
+            ref_block_bytes: [(block.number >> 8) as u8, block.number as u8],
+            ref_block_hash: id[8..16].try_into().expect("8 bytes"),
+            expiration: block.timestamp + DEFAULT_EXPIRATION.as_millis() as u64,
+            timestamp: now_millis(),
+            fee_limit: 0,
+            contract,
+            permission_id: 0,
+        })
+    }
+
+    /// Expiration in milliseconds since the epoch, for hosts signing long after they
+    /// got the reference block.
+    pub fn expiration(mut self, expiration: u64) -> Self {
+        self.expiration = expiration;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn fee_limit(mut self, fee_limit: u64) -> Self {
+        self.fee_limit = fee_limit;
+        self
+    }
+
+    /// Signs the transaction under the permission `permission_id` of its owner, whose
+    /// keys are then the ones to sign it.
+    pub fn permission_id(mut self, permission_id: u32) -> Self {
+        self.permission_id = permission_id;
+        self
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        ProtoWriter::new()
+            .bytes(1, &self.ref_block_bytes)
+            .bytes(4, &self.ref_block_hash)
+            .uint64(8, self.expiration)
+            .message(11, &self.contract.encode(self.permission_id))
+            .uint64(14, self.timestamp)
+            .uint64(18, self.fee_limit)
+            .into_bytes()
+    }
+
+    /// Bandwidth the transaction consumes once signed with `signatures` keys: the size
+    /// of the signed transaction and of its result.
+    pub fn bandwidth(&self, signatures: usize) -> u64 {
+        let mut signed = ProtoWriter::new().message(1, &self.encode());
+        for _ in 0..signatures {
+            signed = signed.bytes(2, &[0xff; SIGNATURE_SIZE]);
+        }
+        signed.into_bytes().len() as u64 + MAX_RESULT_SIZE_IN_TX
+    }
+
+    /// Transaction id, the SHA-256 of the encoding.
+    pub fn tx_id(&self) -> [u8; 32] {
+        Sha256::digest(self.encode()).into()
+    }
+
+    /// `raw_data` as the API prints it.
+    pub fn to_json(&self) -> Value {
+        let mut raw_data = json!({
+            "contract": [self.contract.to_json(self.permission_id)],
+            "ref_block_bytes": hex::encode(self.ref_block_bytes),
+            "ref_block_hash": hex::encode(self.ref_block_hash),
+            "expiration": self.expiration,
+            "timestamp": self.timestamp,
+        });
+        if self.fee_limit != 0 {
+            raw_data["fee_limit"] = self.fee_limit.into();
+        }
+        raw_data
+    }
+
+    /// Unsigned transaction, as `createtransaction` would return it.
+    pub fn into_transaction(self) -> Transaction {
+        Transaction {
+            tx_id: hex::encode(self.tx_id()),
+            raw_data: self.to_json(),
+            raw_data_hex: hex::encode(self.encode()),
+            signature: vec![],
+            visible: false,
+        }
+    }
+}
+
+/// Protobuf encoding of a signed transaction, as `broadcasthex` takes it.
+pub fn encode_signed(tx: &Transaction) -> Result<Vec<u8>, Error> {
+    let mut encoded = ProtoWriter::new().message(1, &hex::decode(&tx.raw_data_hex)?);
+    for signature in &tx.signature {
+        encoded = encoded.bytes(2, &hex::decode(signature)?);
+    }
+    Ok(encoded.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block() -> BlockHeader {
+        BlockHeader {
+            id: "0000000003a6f1b20102030405060708090a0b0c0d0e0f101112131415161718".to_owned(),
+            number: 61272498,
+            timestamp: 1713000000000,
+        }
+    }
+
+    fn address(byte: u8) -> String {
+        format!("41{}", hex::encode([byte; 20]))
+    }
+
+    #[test]
+    fn test_transfer_encoding() {
+        let contract =
+            Contract::transfer(&address(0x11), &address(0x22), U256::from(1_000_000)).unwrap();
+        let raw = RawTransaction::new(contract, &block())
+            .unwrap()
+            .timestamp(1713000000000);
+        assert_eq!(raw.ref_block_bytes, [0xf1, 0xb2]);
+        assert_eq!(raw.expiration, 1713000060000);
+        assert_eq!(
+            hex::encode(raw.encode()),
+            "0a02f1b22208010203040506070840e0a88ab6ed315a67080112630a2d747970652e676f6f676c65617069732e636f6d2f70726f746f636f6c2e5472616e73666572436f6e747261637412320a15411111111111111111111111111111111111111111121541222222222222222222222222222222222222222218c0843d7080d486b6ed31"
+        );
+
+        let tx = raw.into_transaction();
+        assert_eq!(
+            tx.tx_id,
+            "1dda050aba04687cb91a0dc21b2606437e07a0810f8fc0122dbd60fab5c1f56a"
+        );
+        let contract = &tx.raw_data["contract"][0];
+        assert_eq!(contract["type"], "TransferContract");
+        assert_eq!(contract["parameter"]["value"]["amount"], 1_000_000);
+        assert_eq!(contract["parameter"]["value"]["to_address"], address(0x22));
+        assert_eq!(tx.raw_data["ref_block_hash"], "0102030405060708");
+        assert!(tx.raw_data.get("fee_limit").is_none());
+    }
+
+    #[test]
+    fn test_trigger_smart_contract_encoding() {
+        let contract = Contract::trc20_transfer(
+            &address(0x11),
+            &address(0x33),
+            &address(0x22),
+            U256::from(5),
+        )
+        .unwrap();
+        let raw = RawTransaction::new(contract, &block())
+            .unwrap()
+            .fee_limit(30_000_000);
+        let encoded = hex::encode(raw.encode());
+        // ContractType 31, the type URL, and fee_limit as field 18
+        assert!(encoded.contains("081f12"));
+        assert!(encoded.contains(&hex::encode(
+            "type.googleapis.com/protocol.TriggerSmartContract"
+        )));
+        assert!(encoded.ends_with(&format!("9001{}", "8087a70e")));
+
+        let tx = raw.into_transaction();
+        let value = &tx.raw_data["contract"][0]["parameter"]["value"];
+        assert_eq!(value["contract_address"], address(0x33));
+        assert_eq!(
+            value["data"],
+            format!(
+                "a9059cbb000000000000000000000000{}{:064x}",
+                hex::encode([0x22; 20]),
+                5
+            )
+        );
+        assert_eq!(tx.raw_data["fee_limit"], 30_000_000);
+    }
+
+    #[test]
+    fn test_transfer_asset_encoding() {
+        let contract =
+            Contract::trc10_transfer(&address(0x11), "1002000", &address(0x22), U256::from(1000))
+                .unwrap();
+        assert_eq!(
+            hex::encode(contract.encode_value()),
+            format!(
+                "0a07{}1215{}1a15{}20e807",
+                hex::encode("1002000"),
+                address(0x11),
+                address(0x22)
+            )
+        );
+        let tx = RawTransaction::new(contract, &block())
+            .unwrap()
+            .into_transaction();
+        let contract = &tx.raw_data["contract"][0];
+        assert_eq!(contract["type"], "TransferAssetContract");
+        assert_eq!(
+            contract["parameter"]["value"]["asset_name"],
+            hex::encode("1002000")
+        );
+        // ContractType 2
+        assert!(tx.raw_data_hex.contains("08021270"));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        assert!(matches!(
+            Contract::transfer(&address(0x11), &address(0x22), U256::from(u64::MAX)),
+            Err(Error::AmountOverflow(_))
+        ));
+        assert!(matches!(
+            Contract::transfer(&address(0x11)[2..], &address(0x22), U256::one()),
+            Err(Error::TronAddrLengthError)
+        ));
+        let contract = Contract::transfer(&address(0x11), &address(0x22), U256::one()).unwrap();
+        let block = BlockHeader {
+            id: "00".to_owned(),
+            ..block()
+        };
+        assert!(RawTransaction::new(contract, &block).is_err());
+    }
+}
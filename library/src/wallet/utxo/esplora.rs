@@ -0,0 +1,132 @@
+//! Client for Esplora-compatible HTTP APIs, such as blockstream.info or mempool.space.
+
+use std::collections::HashMap;
+
+use bitcoin::{
+    consensus::encode::{deserialize, serialize_hex},
+    Transaction,
+};
+use serde::Deserialize;
+
+use crate::error::Error;
+
+/// Confirmation target, in blocks, used to pick a fee rate.
+pub const DEFAULT_CONFIRMATION_TARGET: u16 = 6;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UtxoStatus {
+    pub confirmed: bool,
+    pub block_height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Utxo {
+    pub txid: String,
+    pub vout: u32,
+    pub value: u64,
+    pub status: UtxoStatus,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TxoStats {
+    funded_txo_sum: u64,
+    spent_txo_sum: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct AddressInfo {
+    chain_stats: TxoStats,
+    mempool_stats: TxoStats,
+}
+
+pub struct EsploraClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl EsploraClient {
+    /// Creates a client for the API at `base_url`, e.g. `https://blockstream.info/api`.
+    pub fn new(base_url: &str) -> Self {
+        EsploraClient {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Balance of `address` in satoshis, including unconfirmed transactions.
+    pub async fn address_balance(&self, address: &str) -> Result<u64, Error> {
+        let info: AddressInfo = self
+            .client
+            .get(format!("{}/address/{}", self.base_url, address))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let funded = info.chain_stats.funded_txo_sum + info.mempool_stats.funded_txo_sum;
+        let spent = info.chain_stats.spent_txo_sum + info.mempool_stats.spent_txo_sum;
+        Ok(funded.saturating_sub(spent))
+    }
+
+    /// Unspent outputs of `address`, including unconfirmed ones.
+    pub async fn utxos(&self, address: &str) -> Result<Vec<Utxo>, Error> {
+        let utxos = self
+            .client
+            .get(format!("{}/address/{}/utxo", self.base_url, address))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(utxos)
+    }
+
+    /// Transaction `txid`, as signers of legacy inputs need the transactions they spend.
+    pub async fn transaction(&self, txid: &str) -> Result<Transaction, Error> {
+        let tx = self
+            .client
+            .get(format!("{}/tx/{}/hex", self.base_url, txid))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(deserialize(&hex::decode(tx.trim())?)?)
+    }
+
+    /// Fee rate in sat/vB expected to confirm within `target` blocks.
+    pub async fn fee_rate(&self, target: u16) -> Result<f64, Error> {
+        let estimates: HashMap<String, f64> = self
+            .client
+            .get(format!("{}/fee-estimates", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        // pick the estimate for the closest target not above the requested one
+        estimates
+            .iter()
+            .filter_map(|(blocks, rate)| Some((blocks.parse::<u16>().ok()?, *rate)))
+            .filter(|(blocks, _)| *blocks <= target)
+            .max_by_key(|(blocks, _)| *blocks)
+            .map(|(_, rate)| rate)
+            .ok_or(Error::FeeEstimateUnavailable)
+    }
+
+    /// Broadcasts `tx` and returns its txid.
+    pub async fn broadcast(&self, tx: &Transaction) -> Result<String, Error> {
+        let response = self
+            .client
+            .post(format!("{}/tx", self.base_url))
+            .body(serialize_hex(tx))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::BroadcastError(body));
+        }
+        Ok(body.trim().to_owned())
+    }
+}
@@ -0,0 +1,875 @@
+pub mod esplora;
+pub mod network;
+pub mod psbt;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bitcoin::{
+    bip32::{DerivationPath, Fingerprint},
+    psbt::Psbt,
+    secp256k1::{PublicKey, SecretKey},
+    OutPoint, ScriptBuf, Transaction, TxOut, Txid,
+};
+use ethers::types::U256;
+
+use crate::{
+    error::Error,
+    types::{
+        hdseed::{FromSeed, HDSeed},
+        token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    },
+    utils::{
+        amount::amount_as,
+        key::{keypair_by_index, master_fingerprint},
+    },
+};
+
+use self::{
+    esplora::{EsploraClient, DEFAULT_CONFIRMATION_TARGET},
+    network::UtxoNetwork,
+    psbt::{build_psbt, finalize_psbt, select_all, select_coins, sign_psbt, SpendableUtxo},
+};
+use super::Wallet;
+
+/// Script type of the addresses a `UtxoWallet` derives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressType {
+    /// Legacy P2PKH, derived under BIP44 `m/44'/coin'/0'`.
+    Legacy,
+    /// Native SegWit P2WPKH, derived under BIP84 `m/84'/coin'/0'`.
+    SegWit,
+    /// Taproot P2TR key-path, derived under BIP86 `m/86'/coin'/0'`.
+    Taproot,
+}
+
+impl AddressType {
+    pub fn purpose(&self) -> u32 {
+        match self {
+            AddressType::Legacy => 44,
+            AddressType::SegWit => 84,
+            AddressType::Taproot => 86,
+        }
+    }
+}
+
+/// External (receive) or internal (change) branch of an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyChain {
+    Receive,
+    Change,
+}
+
+impl KeyChain {
+    fn index(&self) -> u32 {
+        match self {
+            KeyChain::Receive => 0,
+            KeyChain::Change => 1,
+        }
+    }
+}
+
+/// Wallet for Bitcoin and the chains that share its transaction format, described
+/// by a `UtxoNetwork`. Index `i` owns the receive address `.../0/i` and the change
+/// address `.../1/i`; balances, transfers and sweeps of an index cover both.
+pub struct UtxoWallet {
+    pub seed: HDSeed,
+    pub address_type: AddressType,
+    pub network: UtxoNetwork,
+}
+
+impl FromSeed for UtxoWallet {
+    fn from_seed(seed: HDSeed) -> Self {
+        UtxoWallet::bitcoin(seed)
+    }
+}
+
+impl UtxoWallet {
+    pub fn new(
+        seed: HDSeed,
+        address_type: AddressType,
+        network: UtxoNetwork,
+    ) -> Result<Self, Error> {
+        if !network.supports(address_type) {
+            return Err(Error::UnsupportedAddressType(network.name));
+        }
+        Ok(UtxoWallet {
+            seed,
+            address_type,
+            network,
+        })
+    }
+
+    /// Bitcoin mainnet wallet with native SegWit addresses.
+    pub fn bitcoin(seed: HDSeed) -> Self {
+        UtxoWallet {
+            seed,
+            address_type: AddressType::SegWit,
+            network: UtxoNetwork::BITCOIN,
+        }
+    }
+
+    /// Litecoin mainnet wallet with native SegWit `ltc1` addresses.
+    pub fn litecoin(seed: HDSeed) -> Self {
+        UtxoWallet {
+            seed,
+            address_type: AddressType::SegWit,
+            network: UtxoNetwork::LITECOIN,
+        }
+    }
+
+    /// Dogecoin mainnet wallet. Dogecoin has no SegWit, addresses are P2PKH.
+    pub fn dogecoin(seed: HDSeed) -> Self {
+        UtxoWallet {
+            seed,
+            address_type: AddressType::Legacy,
+            network: UtxoNetwork::DOGECOIN,
+        }
+    }
+
+    pub fn get_hd_path(&self, chain: KeyChain, index: u32) -> Result<DerivationPath, Error> {
+        let str_path = format!(
+            "m/{}'/{}'/0'/{}/{}",
+            self.address_type.purpose(),
+            self.network.coin_type,
+            chain.index(),
+            index
+        );
+        Ok(DerivationPath::from_str(&str_path)?)
+    }
+
+    fn secret_key(&self, path: &DerivationPath) -> Result<SecretKey, Error> {
+        let (privk, _) = keypair_by_index(&self.seed.mnemonic, path)?;
+        Ok(privk.private_key)
+    }
+
+    fn public_key(&self, path: &DerivationPath) -> Result<PublicKey, Error> {
+        let (_, pubk) = keypair_by_index(&self.seed.mnemonic, path)?;
+        Ok(pubk.public_key)
+    }
+
+    fn script_for_key(&self, public_key: &PublicKey) -> Result<ScriptBuf, Error> {
+        self.network.script_pubkey(self.address_type, public_key)
+    }
+
+    pub fn address_at(&self, chain: KeyChain, index: u32) -> Result<String, Error> {
+        let path = self.get_hd_path(chain, index)?;
+        self.network
+            .encode_address(self.address_type, &self.public_key(&path)?)
+    }
+
+    pub fn receive_address(&self, index: u32) -> Result<String, Error> {
+        self.address_at(KeyChain::Receive, index)
+    }
+
+    pub fn change_address(&self, index: u32) -> Result<String, Error> {
+        self.address_at(KeyChain::Change, index)
+    }
+
+    fn change_script(&self, index: u32) -> Result<ScriptBuf, Error> {
+        let path = self.get_hd_path(KeyChain::Change, index)?;
+        self.script_for_key(&self.public_key(&path)?)
+    }
+
+    /// Unspent outputs of the receive and change addresses of `index`.
+    pub async fn utxos(&self, index: u32, provider: &str) -> Result<Vec<SpendableUtxo>, Error> {
+        let client = EsploraClient::new(provider);
+        let mut spendable = vec![];
+        for chain in [KeyChain::Receive, KeyChain::Change] {
+            let path = self.get_hd_path(chain, index)?;
+            let public_key = self.public_key(&path)?;
+            let script_pubkey = self.script_for_key(&public_key)?;
+            let address = self.network.script_to_address(&script_pubkey)?;
+            for utxo in client.utxos(&address).await? {
+                let previous_tx = match self.address_type {
+                    AddressType::Legacy => Some(client.transaction(&utxo.txid).await?),
+                    AddressType::SegWit | AddressType::Taproot => None,
+                };
+                spendable.push(SpendableUtxo {
+                    outpoint: OutPoint {
+                        txid: Txid::from_str(&utxo.txid)?,
+                        vout: utxo.vout,
+                    },
+                    txout: TxOut {
+                        value: bitcoin::Amount::from_sat(utxo.value),
+                        script_pubkey: script_pubkey.clone(),
+                    },
+                    path: path.clone(),
+                    public_key,
+                    previous_tx,
+                });
+            }
+        }
+        Ok(spendable)
+    }
+
+    fn master_fingerprint(&self) -> Result<Fingerprint, Error> {
+        master_fingerprint(&self.seed.mnemonic)
+    }
+
+    /// Builds an unsigned PSBT paying `amount` satoshis to `to`, with change going
+    /// to the change address of `index`. `fee_rate` is in the network's fee unit.
+    pub fn build_transfer_psbt(
+        &self,
+        index: u32,
+        utxos: Vec<SpendableUtxo>,
+        to: &str,
+        amount: u64,
+        fee_rate: f64,
+    ) -> Result<Psbt, Error> {
+        let recipient = self.network.parse_address(to)?;
+        let change = self.change_script(index)?;
+        let selection = select_coins(
+            utxos,
+            amount,
+            &recipient,
+            &change,
+            self.address_type,
+            self.network.fee_rate_sat_per_vbyte(fee_rate),
+            self.network.dust_limit,
+        )?;
+        let mut outputs: Vec<(ScriptBuf, u64)> = vec![(recipient, amount)];
+        if selection.change > 0 {
+            outputs.push((change, selection.change));
+        }
+        build_psbt(
+            &selection.inputs,
+            outputs,
+            self.address_type,
+            self.master_fingerprint()?,
+        )
+    }
+
+    /// Builds an unsigned PSBT spending all of `utxos` to `to`.
+    /// Returns the PSBT and the amount it sends after the fee.
+    pub fn build_sweep_psbt(
+        &self,
+        utxos: Vec<SpendableUtxo>,
+        to: &str,
+        fee_rate: f64,
+    ) -> Result<(Psbt, u64), Error> {
+        let recipient = self.network.parse_address(to)?;
+        let (selection, amount) = select_all(
+            utxos,
+            &recipient,
+            self.address_type,
+            self.network.fee_rate_sat_per_vbyte(fee_rate),
+            self.network.dust_limit,
+        )?;
+        let psbt = build_psbt(
+            &selection.inputs,
+            vec![(recipient, amount)],
+            self.address_type,
+            self.master_fingerprint()?,
+        )?;
+        Ok((psbt, amount))
+    }
+
+    /// Signs the inputs of `psbt` whose key origin belongs to this wallet's seed.
+    pub fn sign_psbt(&self, psbt: &mut Psbt) -> Result<(), Error> {
+        let fingerprint = self.master_fingerprint()?;
+        for (i, input) in psbt.inputs.iter().enumerate() {
+            let origin_fingerprint = input
+                .bip32_derivation
+                .values()
+                .map(|(fp, _)| *fp)
+                .chain(input.tap_key_origins.values().map(|(_, (fp, _))| *fp))
+                .next();
+            if origin_fingerprint != Some(fingerprint) {
+                return Err(Error::PsbtInputError(i));
+            }
+        }
+        sign_psbt(psbt, |path| self.secret_key(path))
+    }
+
+    /// Signs and finalizes `psbt`, returning the transaction ready for broadcast.
+    pub fn sign_transaction(&self, mut psbt: Psbt) -> Result<Transaction, Error> {
+        self.sign_psbt(&mut psbt)?;
+        finalize_psbt(psbt)
+    }
+
+    async fn utxo_balance_by_index(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        let client = EsploraClient::new(provider);
+        let mut balance = 0u64;
+        for chain in [KeyChain::Receive, KeyChain::Change] {
+            let address = self.address_at(chain, index)?;
+            balance += client.address_balance(&address).await?;
+        }
+        Ok(U256::from(balance))
+    }
+
+    async fn utxo_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: u64,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let client = EsploraClient::new(provider);
+        let fee_rate = self
+            .network
+            .fee_rate_in_unit(client.fee_rate(DEFAULT_CONFIRMATION_TARGET).await?);
+        let utxos = self.utxos(index, provider).await?;
+        let psbt = self.build_transfer_psbt(index, utxos, to, amount, fee_rate)?;
+        let tx = self.sign_transaction(psbt)?;
+        let txid = client.broadcast(&tx).await?;
+        Ok(TransferReceipt::Broadcast { txid })
+    }
+
+    async fn utxo_sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let client = EsploraClient::new(provider);
+        let fee_rate = self
+            .network
+            .fee_rate_in_unit(client.fee_rate(DEFAULT_CONFIRMATION_TARGET).await?);
+        let utxos = self.utxos(index, provider).await?;
+        let (psbt, amount) = self.build_sweep_psbt(utxos, to, fee_rate)?;
+        let tx = self.sign_transaction(psbt)?;
+        let txid = client.broadcast(&tx).await?;
+        Ok((TransferReceipt::Broadcast { txid }, U256::from(amount)))
+    }
+}
+
+#[async_trait]
+impl Wallet for UtxoWallet {
+    fn address(&self, index: u32) -> Result<String, Error> {
+        self.receive_address(index)
+    }
+    fn public(&self, index: u32) -> Result<String, Error> {
+        let path = self.get_hd_path(KeyChain::Receive, index)?;
+        let (_, pubk) = keypair_by_index(&self.seed.mnemonic, &path)?;
+        Ok(pubk.to_string())
+    }
+    fn private(&self, index: u32) -> Result<String, Error> {
+        let path = self.get_hd_path(KeyChain::Receive, index)?;
+        Ok(self.network.encode_wif(&self.secret_key(&path)?))
+    }
+    fn keypair(&self, index: u32) -> Result<(String, String), Error> {
+        Ok((self.private(index)?, self.public(index)?))
+    }
+    async fn balance(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        self.utxo_balance_by_index(index, provider).await
+    }
+    async fn balance_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _provider: &str,
+    ) -> Result<U256, Error> {
+        Err(Error::TokensNotSupported)
+    }
+    async fn transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.utxo_transfer(index, to, amount_as(amount)?, provider)
+            .await
+    }
+    async fn transfer_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _to: &str,
+        _amount: U256,
+        _provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        Err(Error::TokensNotSupported)
+    }
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        self.utxo_sweep(index, to, provider).await
+    }
+    async fn sweep_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _to: &str,
+        _provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        Err(Error::TokensNotSupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use bip39::{Language, Mnemonic};
+    use bitcoin::{
+        consensus::encode::{deserialize, serialize_hex},
+        hashes::Hash,
+        key::TapTweak,
+        secp256k1::{schnorr, Message, Secp256k1},
+        sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType},
+    };
+
+    const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn seed() -> HDSeed {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        HDSeed { mnemonic }
+    }
+
+    fn wallet(address_type: AddressType) -> UtxoWallet {
+        UtxoWallet::new(seed(), address_type, UtxoNetwork::BITCOIN).unwrap()
+    }
+
+    fn utxo(wallet: &UtxoWallet, chain: KeyChain, vout: u32, value: u64) -> SpendableUtxo {
+        let path = wallet.get_hd_path(chain, 0).unwrap();
+        let public_key = wallet.public_key(&path).unwrap();
+        let txout = TxOut {
+            value: bitcoin::Amount::from_sat(value),
+            script_pubkey: wallet.script_for_key(&public_key).unwrap(),
+        };
+        let mut outpoint = OutPoint {
+            txid: Txid::from_byte_array([vout as u8 + 1; 32]),
+            vout,
+        };
+        // legacy inputs need the transaction they spend, here one with the output at vout
+        let previous_tx = (wallet.address_type == AddressType::Legacy).then(|| Transaction {
+            version: bitcoin::transaction::Version::ONE,
+            lock_time: bitcoin::absolute::LockTime::ZERO,
+            input: vec![bitcoin::TxIn {
+                previous_output: outpoint,
+                ..Default::default()
+            }],
+            output: vec![txout.clone(); vout as usize + 1],
+        });
+        if let Some(tx) = &previous_tx {
+            outpoint.txid = tx.txid();
+        }
+        SpendableUtxo {
+            outpoint,
+            txout,
+            path,
+            public_key,
+            previous_tx,
+        }
+    }
+
+    #[test]
+    fn test_bip84_addresses() {
+        let wallet = wallet(AddressType::SegWit);
+        assert_eq!(
+            wallet.receive_address(0).unwrap(),
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert_eq!(
+            wallet.change_address(0).unwrap(),
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el"
+        );
+        assert_eq!(
+            wallet.private(0).unwrap(),
+            "KyZpNDKnfs94vbrwhJneDi77V6jF64PWPF8x5cdJb8ifgg2DUc9d"
+        );
+    }
+
+    #[test]
+    fn test_bip86_addresses() {
+        let wallet = wallet(AddressType::Taproot);
+        assert_eq!(
+            wallet.receive_address(0).unwrap(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(
+            wallet.change_address(0).unwrap(),
+            "bc1p3qkhfews2uk44qtvauqyr2ttdsw7svhkl9nkm9s9c3x4ax5h60wqwruhk7"
+        );
+    }
+
+    #[test]
+    fn test_sign_segwit_transfer() {
+        let wallet = wallet(AddressType::SegWit);
+        let utxos = vec![
+            utxo(&wallet, KeyChain::Receive, 0, 20_000),
+            utxo(&wallet, KeyChain::Change, 1, 50_000),
+        ];
+        let to = "bc1qm34lsc65zpw79lxes69zkqmk6ee3ewf0j77s3h";
+        let psbt = wallet
+            .build_transfer_psbt(0, utxos, to, 30_000, 2.0)
+            .unwrap();
+        // the largest output alone covers the payment, the rest goes to change
+        assert_eq!(psbt.unsigned_tx.input.len(), 1);
+        assert_eq!(psbt.unsigned_tx.output.len(), 2);
+        let fee = 50_000 - 30_000 - psbt.unsigned_tx.output[1].value.to_sat();
+        assert_eq!(fee, psbt::fee_for(11 + 68 + 31 + 31, 2.0));
+
+        let prevout = psbt.inputs[0].witness_utxo.clone().unwrap();
+        let tx = wallet.sign_transaction(psbt).unwrap();
+        let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+        let (sig, pubkey) = (witness[0], witness[1]);
+        let sighash = SighashCache::new(&tx)
+            .p2wpkh_signature_hash(
+                0,
+                &prevout.script_pubkey,
+                prevout.value,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        let sig = bitcoin::ecdsa::Signature::from_slice(sig).unwrap();
+        Secp256k1::new()
+            .verify_ecdsa(
+                &Message::from_digest(sighash.to_byte_array()),
+                &sig.sig,
+                &PublicKey::from_slice(pubkey).unwrap(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_taproot_sweep() {
+        let wallet = wallet(AddressType::Taproot);
+        let utxos = vec![
+            utxo(&wallet, KeyChain::Receive, 0, 10_000),
+            utxo(&wallet, KeyChain::Receive, 1, 15_000),
+        ];
+        let to = "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr";
+        let (psbt, amount) = wallet.build_sweep_psbt(utxos, to, 3.0).unwrap();
+        assert_eq!(amount, 25_000 - psbt::fee_for(11 + 2 * 58 + 43, 3.0));
+
+        let prevouts: Vec<TxOut> = psbt
+            .inputs
+            .iter()
+            .map(|input| input.witness_utxo.clone().unwrap())
+            .collect();
+        let tx = wallet.sign_transaction(psbt).unwrap();
+        let secp = Secp256k1::new();
+        let (internal_key, _) = wallet
+            .public_key(&wallet.get_hd_path(KeyChain::Receive, 0).unwrap())
+            .unwrap()
+            .x_only_public_key();
+        let (output_key, _) = internal_key.tap_tweak(&secp, None);
+        for i in 0..2 {
+            let sighash = SighashCache::new(&tx)
+                .taproot_key_spend_signature_hash(
+                    i,
+                    &Prevouts::All(&prevouts),
+                    TapSighashType::Default,
+                )
+                .unwrap();
+            let sig = schnorr::Signature::from_slice(&tx.input[i].witness.to_vec()[0]).unwrap();
+            secp.verify_schnorr(
+                &sig,
+                &Message::from_digest(sighash.to_byte_array()),
+                &output_key.to_inner(),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_litecoin_addresses() {
+        let wallet = UtxoWallet::litecoin(seed());
+        assert_eq!(
+            wallet
+                .get_hd_path(KeyChain::Receive, 0)
+                .unwrap()
+                .to_string(),
+            "m/84'/2'/0'/0/0"
+        );
+        assert_eq!(
+            wallet.receive_address(0).unwrap(),
+            "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh"
+        );
+        assert_eq!(
+            wallet.private(0).unwrap(),
+            "T5ZCYhLqXu6EJKk2nhjvwsaLH357CisixhLGWpKXEiqWTUtzte6o"
+        );
+        let legacy = UtxoWallet::new(seed(), AddressType::Legacy, UtxoNetwork::LITECOIN).unwrap();
+        assert_eq!(
+            legacy.receive_address(0).unwrap(),
+            "LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ez"
+        );
+    }
+
+    #[test]
+    fn test_dogecoin_addresses() {
+        let wallet = UtxoWallet::dogecoin(seed());
+        assert_eq!(
+            wallet
+                .get_hd_path(KeyChain::Receive, 0)
+                .unwrap()
+                .to_string(),
+            "m/44'/3'/0'/0/0"
+        );
+        assert_eq!(
+            wallet.receive_address(0).unwrap(),
+            "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC"
+        );
+        assert_eq!(
+            wallet.private(0).unwrap(),
+            "QPkeC1ZfHx3c9g7WTj9cQ8gnvk2iSAfAcbq1aVAWjNTwDAKfZUzx"
+        );
+        assert!(matches!(
+            UtxoWallet::new(seed(), AddressType::SegWit, UtxoNetwork::DOGECOIN),
+            Err(Error::UnsupportedAddressType("dogecoin"))
+        ));
+    }
+
+    #[test]
+    fn test_addresses_of_other_networks_are_rejected() {
+        let litecoin = UtxoNetwork::LITECOIN;
+        assert!(litecoin
+            .parse_address("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+            .is_err());
+        assert!(litecoin
+            .parse_address("DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC")
+            .is_err());
+        let script = litecoin
+            .parse_address("ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh")
+            .unwrap();
+        assert_eq!(
+            litecoin.script_to_address(&script).unwrap(),
+            "ltc1qjmxnz78nmc8nq77wuxh25n2es7rzm5c2rkk4wh"
+        );
+    }
+
+    #[test]
+    fn test_sign_litecoin_transfer() {
+        let wallet = UtxoWallet::litecoin(seed());
+        let utxos = vec![utxo(&wallet, KeyChain::Receive, 0, 1_000_000)];
+        let to = "LUWPbpM43E2p7ZSh8cyTBEkvpHmr3cB8Ez";
+        let psbt = wallet
+            .build_transfer_psbt(0, utxos, to, 400_000, 10.0)
+            .unwrap();
+        let prevout = psbt.inputs[0].witness_utxo.clone().unwrap();
+        let tx = wallet.sign_transaction(psbt).unwrap();
+        assert_eq!(
+            tx.output[1].value.to_sat(),
+            1_000_000 - 400_000 - psbt::fee_for(11 + 68 + 34 + 31, 10.0)
+        );
+
+        let witness: Vec<&[u8]> = tx.input[0].witness.iter().collect();
+        let sighash = SighashCache::new(&tx)
+            .p2wpkh_signature_hash(
+                0,
+                &prevout.script_pubkey,
+                prevout.value,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+        let sig = bitcoin::ecdsa::Signature::from_slice(witness[0]).unwrap();
+        Secp256k1::new()
+            .verify_ecdsa(
+                &Message::from_digest(sighash.to_byte_array()),
+                &sig.sig,
+                &PublicKey::from_slice(witness[1]).unwrap(),
+            )
+            .unwrap();
+        // RFC 6979 signatures make the signed transaction deterministic
+        assert_eq!(serialize_hex(&tx), "0200000000010101010101010101010101010101010101010101010101010101010101010101010000000000fdffffff02801a0600000000001976a91465d4f0444069f3881221e24bb6a99b1d53e008cf88ac2022090000000000160014267f2c10ac39e483c00c982efdd0112bcc3e628302483045022100eb57f87f73acbdafba0437d782f51c9007cebf7de68ce33a99c2a793ed2b04c502205f0b527b83827a375c4a9a41289922b01b74e4c700782ee167795fb24e6c2833012102e49c9b9b5d0f127235dc26a0c252814c52fb333d651a946773f59d72c2da990400000000");
+    }
+
+    #[test]
+    fn test_sign_dogecoin_transfer() {
+        let wallet = UtxoWallet::dogecoin(seed());
+        let utxos = vec![
+            utxo(&wallet, KeyChain::Receive, 0, 500_000_000),
+            utxo(&wallet, KeyChain::Change, 1, 300_000_000),
+        ];
+        let to = "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC";
+        let mut missing = utxos.clone();
+        missing[1].previous_tx = None;
+        assert!(matches!(
+            wallet.build_transfer_psbt(0, missing, to, 600_000_000, 100_000.0),
+            Err(Error::PsbtInputError(1))
+        ));
+        // below the relay minimum of 0.01 DOGE/kB, the minimum is used
+        let psbt = wallet
+            .build_transfer_psbt(0, utxos, to, 600_000_000, 100_000.0)
+            .unwrap();
+        assert_eq!(psbt.unsigned_tx.input.len(), 2);
+        assert!(psbt.inputs.iter().all(|input| input.witness_utxo.is_none()));
+        let prevouts: Vec<TxOut> = psbt
+            .inputs
+            .iter()
+            .map(|input| {
+                let previous_tx = input.non_witness_utxo.as_ref().unwrap();
+                previous_tx.output[previous_tx.output.len() - 1].clone()
+            })
+            .collect();
+        let tx = wallet.sign_transaction(psbt).unwrap();
+        assert_eq!(
+            tx.output[1].value.to_sat(),
+            800_000_000 - 600_000_000 - psbt::fee_for(11 + 2 * 148 + 2 * 34, 1000.0)
+        );
+
+        let secp = Secp256k1::new();
+        for (i, prevout) in prevouts.iter().enumerate() {
+            assert!(tx.input[i].witness.is_empty());
+            let pushes: Vec<Vec<u8>> = tx.input[i]
+                .script_sig
+                .instructions()
+                .map(|ins| ins.unwrap().push_bytes().unwrap().as_bytes().to_vec())
+                .collect();
+            let sighash = SighashCache::new(&tx)
+                .legacy_signature_hash(i, &prevout.script_pubkey, EcdsaSighashType::All.to_u32())
+                .unwrap();
+            let sig = bitcoin::ecdsa::Signature::from_slice(&pushes[0]).unwrap();
+            let public_key = PublicKey::from_slice(&pushes[1]).unwrap();
+            assert_eq!(
+                prevout.script_pubkey,
+                UtxoNetwork::DOGECOIN
+                    .script_pubkey(AddressType::Legacy, &public_key)
+                    .unwrap()
+            );
+            secp.verify_ecdsa(
+                &Message::from_digest(sighash.to_byte_array()),
+                &sig.sig,
+                &public_key,
+            )
+            .unwrap();
+        }
+        assert_eq!(serialize_hex(&tx), "02000000023a71dbc63e970e707b7db75f00a438da60c30e27a8e9bcd93775aa3225f40b53000000006b48304502210083b7288a3763fb080254b0bb8a656b0c518e9c30abe38a5faf076db7a12abd350220070b2ce1cf138522ce63be6e90ae026e9da541fdc4638e7489636c0113da89ed012102cc6b0dc33aabcf3a23643e5e2919a80c50fb3dd2129ce409bbc5f0d4643d05e0fdffffff483cdd470528a2b2589008738020219edf2ace46205a29b6617cf0299725b1ad010000006a4730440220604adb138e9179e65a4d814cd837b52146727d5ff0a49b4721b666533f747b430220323b3cb5b9731cd7e0c6a16848fc6e33aa78ddb1d6e2eae8f3e0982de82f6ee50121020745c065a8b7cb03a843a57339cf9164d675a2e5010c6fbd61c09239bbb5f4dffdffffff020046c323000000001976a9144a483568665dcdfa68dd58a1f62893448a64333988ac2809e60b000000001976a91419115174dcc75cce4802aaec82e63b491274c2e288ac00000000");
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let wallet = wallet(AddressType::SegWit);
+        let utxos = vec![utxo(&wallet, KeyChain::Receive, 0, 1_000)];
+        let to = "bc1qm34lsc65zpw79lxes69zkqmk6ee3ewf0j77s3h";
+        assert!(matches!(
+            wallet.build_transfer_psbt(0, utxos, to, 5_000, 1.0),
+            Err(Error::InsufficientFunds {
+                available: 1_000,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_amount_below_dust() {
+        let wallet = wallet(AddressType::SegWit);
+        let utxos = vec![utxo(&wallet, KeyChain::Receive, 0, 100_000)];
+        let to = "bc1qm34lsc65zpw79lxes69zkqmk6ee3ewf0j77s3h";
+        assert!(matches!(
+            wallet.build_transfer_psbt(0, utxos.clone(), to, 545, 1.0),
+            Err(Error::AmountBelowDust {
+                amount: 545,
+                dust_limit: 546
+            })
+        ));
+        assert!(wallet.build_transfer_psbt(0, utxos, to, 546, 1.0).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_against_esplora_stand_in() {
+        let wallet = wallet(AddressType::SegWit);
+        let receive = wallet.receive_address(0).unwrap();
+        let change = wallet.change_address(0).unwrap();
+        let txid = "aa".repeat(32);
+        let server = TestServer::start(vec![
+            Route::get("/fee-estimates", r#"{"1": 20.0, "6": 5.0, "144": 1.0}"#),
+            Route::get(
+                &format!("/address/{}/utxo", receive),
+                &format!(
+                    r#"[{{"txid": "{}", "vout": 0, "value": 40000, "status": {{"confirmed": true, "block_height": 800000}}}}]"#,
+                    txid
+                ),
+            ),
+            Route::get(&format!("/address/{}/utxo", change), "[]"),
+            Route::post("/tx", &"bb".repeat(32)),
+        ])
+        .await;
+
+        let (receipt, amount) = wallet
+            .sweep(0, "bc1qm34lsc65zpw79lxes69zkqmk6ee3ewf0j77s3h", &server.url)
+            .await
+            .unwrap();
+        assert_eq!(receipt.txid(), "bb".repeat(32));
+        assert_eq!(
+            amount,
+            U256::from(40_000 - psbt::fee_for(11 + 68 + 31, 5.0))
+        );
+
+        let broadcast = server.requests().pop().unwrap();
+        assert_eq!(broadcast.path, "/tx");
+        let tx: Transaction = deserialize(&hex::decode(&broadcast.body).unwrap()).unwrap();
+        assert_eq!(tx.input[0].previous_output.txid.to_string(), txid);
+        assert_eq!(tx.output[0].value.to_sat(), amount.as_u64());
+    }
+
+    #[tokio::test]
+    async fn test_dogecoin_sweep_against_esplora_stand_in() {
+        let wallet = UtxoWallet::dogecoin(seed());
+        let funding = utxo(&wallet, KeyChain::Receive, 0, 500_000_000);
+        let previous_tx = funding.previous_tx.clone().unwrap();
+        let txid = funding.outpoint.txid.to_string();
+        let server = TestServer::start(vec![
+            // sat/vB, 2 DOGE per kB
+            Route::get("/fee-estimates", r#"{"1": 4000.0, "6": 2000.0}"#),
+            Route::get(
+                &format!("/address/{}/utxo", wallet.receive_address(0).unwrap()),
+                &format!(
+                    r#"[{{"txid": "{}", "vout": 0, "value": 500000000, "status": {{"confirmed": true}}}}]"#,
+                    txid
+                ),
+            ),
+            Route::get(
+                &format!("/address/{}/utxo", wallet.change_address(0).unwrap()),
+                "[]",
+            ),
+            Route::get(&format!("/tx/{}/hex", txid), &serialize_hex(&previous_tx)),
+            Route::post("/tx", &"bb".repeat(32)),
+        ])
+        .await;
+
+        let (_, amount) = wallet
+            .sweep(0, "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC", &server.url)
+            .await
+            .unwrap();
+        // one P2PKH input and output, at the estimate rather than the relay minimum
+        assert_eq!(
+            amount,
+            U256::from(500_000_000 - psbt::fee_for(11 + 148 + 34, 2000.0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_legacy_utxos_carry_previous_tx() {
+        let wallet = UtxoWallet::dogecoin(seed());
+        let funding = utxo(&wallet, KeyChain::Receive, 1, 500_000_000);
+        let previous_tx = funding.previous_tx.clone().unwrap();
+        let txid = funding.outpoint.txid.to_string();
+        let server = TestServer::start(vec![
+            Route::get(
+                &format!("/address/{}/utxo", wallet.receive_address(0).unwrap()),
+                &format!(
+                    r#"[{{"txid": "{}", "vout": 1, "value": 500000000, "status": {{"confirmed": true}}}}]"#,
+                    txid
+                ),
+            ),
+            Route::get(
+                &format!("/address/{}/utxo", wallet.change_address(0).unwrap()),
+                "[]",
+            ),
+            Route::get(&format!("/tx/{}/hex", txid), &serialize_hex(&previous_tx)),
+        ])
+        .await;
+
+        let utxos = wallet.utxos(0, &server.url).await.unwrap();
+        assert_eq!(utxos[0].previous_tx, Some(previous_tx));
+        let psbt = wallet
+            .build_transfer_psbt(
+                0,
+                utxos,
+                "DBus3bamQjgJULBJtYXpEzDWQRwF5iwxgC",
+                100_000_000,
+                0.0,
+            )
+            .unwrap();
+        assert!(psbt.inputs[0].non_witness_utxo.is_some());
+    }
+}
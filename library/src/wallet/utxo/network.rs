@@ -0,0 +1,273 @@
+//! Parameters of the UTXO chains served by `UtxoWallet`.
+
+use bitcoin::{
+    base58,
+    bech32::{self, Fe32, Hrp},
+    hashes::Hash,
+    secp256k1::{PublicKey, Secp256k1, SecretKey},
+    PubkeyHash, ScriptBuf, ScriptHash, WitnessProgram, WitnessVersion,
+};
+
+use crate::error::Error;
+
+use super::AddressType;
+
+/// Unit in which a network quotes fee rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeUnit {
+    /// Satoshis per virtual byte, as Bitcoin and Litecoin do.
+    SatPerVbyte,
+    /// Smallest units per 1000 bytes, as Dogecoin does.
+    SatPerKilobyte,
+}
+
+/// Address encoding, derivation and relay policy of a UTXO chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UtxoNetwork {
+    pub name: &'static str,
+    /// SLIP-44 coin type.
+    pub coin_type: u32,
+    /// Base58 version byte of P2PKH addresses.
+    pub p2pkh_prefix: u8,
+    /// Base58 version byte of P2SH addresses.
+    pub p2sh_prefix: u8,
+    /// Version byte of WIF private keys.
+    pub wif_prefix: u8,
+    /// Human readable part of SegWit addresses, `None` if the chain has no SegWit.
+    pub bech32_hrp: Option<&'static str>,
+    /// Whether Taproot outputs are standard on the chain.
+    pub taproot: bool,
+    /// Smallest output value, in satoshis, that nodes relay.
+    pub dust_limit: u64,
+    pub fee_unit: FeeUnit,
+    /// Lowest fee rate nodes relay, in `fee_unit`.
+    pub min_fee_rate: f64,
+}
+
+impl UtxoNetwork {
+    pub const BITCOIN: UtxoNetwork = UtxoNetwork {
+        name: "bitcoin",
+        coin_type: 0,
+        p2pkh_prefix: 0x00,
+        p2sh_prefix: 0x05,
+        wif_prefix: 0x80,
+        bech32_hrp: Some("bc"),
+        taproot: true,
+        dust_limit: 546,
+        fee_unit: FeeUnit::SatPerVbyte,
+        min_fee_rate: 1.0,
+    };
+
+    pub const BITCOIN_TESTNET: UtxoNetwork = UtxoNetwork {
+        name: "bitcoin-testnet",
+        coin_type: 1,
+        p2pkh_prefix: 0x6f,
+        p2sh_prefix: 0xc4,
+        wif_prefix: 0xef,
+        bech32_hrp: Some("tb"),
+        ..UtxoNetwork::BITCOIN
+    };
+
+    pub const BITCOIN_REGTEST: UtxoNetwork = UtxoNetwork {
+        name: "bitcoin-regtest",
+        bech32_hrp: Some("bcrt"),
+        ..UtxoNetwork::BITCOIN_TESTNET
+    };
+
+    pub const LITECOIN: UtxoNetwork = UtxoNetwork {
+        name: "litecoin",
+        coin_type: 2,
+        p2pkh_prefix: 0x30,
+        p2sh_prefix: 0x32,
+        wif_prefix: 0xb0,
+        bech32_hrp: Some("ltc"),
+        taproot: true,
+        dust_limit: 5460,
+        fee_unit: FeeUnit::SatPerVbyte,
+        min_fee_rate: 1.0,
+    };
+
+    pub const LITECOIN_TESTNET: UtxoNetwork = UtxoNetwork {
+        name: "litecoin-testnet",
+        coin_type: 1,
+        p2pkh_prefix: 0x6f,
+        p2sh_prefix: 0x3a,
+        wif_prefix: 0xef,
+        bech32_hrp: Some("tltc"),
+        ..UtxoNetwork::LITECOIN
+    };
+
+    pub const DOGECOIN: UtxoNetwork = UtxoNetwork {
+        name: "dogecoin",
+        coin_type: 3,
+        p2pkh_prefix: 0x1e,
+        p2sh_prefix: 0x16,
+        wif_prefix: 0x9e,
+        bech32_hrp: None,
+        taproot: false,
+        // 0.01 DOGE
+        dust_limit: 1_000_000,
+        fee_unit: FeeUnit::SatPerKilobyte,
+        // 0.01 DOGE per kB
+        min_fee_rate: 1_000_000.0,
+    };
+
+    pub const DOGECOIN_TESTNET: UtxoNetwork = UtxoNetwork {
+        name: "dogecoin-testnet",
+        coin_type: 1,
+        p2pkh_prefix: 0x71,
+        p2sh_prefix: 0xc4,
+        wif_prefix: 0xf1,
+        ..UtxoNetwork::DOGECOIN
+    };
+
+    /// Built-in network called `name`, e.g. `litecoin-testnet`.
+    pub fn from_name(name: &str) -> Option<UtxoNetwork> {
+        [
+            UtxoNetwork::BITCOIN,
+            UtxoNetwork::BITCOIN_TESTNET,
+            UtxoNetwork::BITCOIN_REGTEST,
+            UtxoNetwork::LITECOIN,
+            UtxoNetwork::LITECOIN_TESTNET,
+            UtxoNetwork::DOGECOIN,
+            UtxoNetwork::DOGECOIN_TESTNET,
+        ]
+        .into_iter()
+        .find(|network| network.name == name)
+    }
+
+    pub fn supports(&self, address_type: AddressType) -> bool {
+        match address_type {
+            AddressType::Legacy => true,
+            AddressType::SegWit => self.bech32_hrp.is_some(),
+            AddressType::Taproot => self.bech32_hrp.is_some() && self.taproot,
+        }
+    }
+
+    /// Converts a fee rate in sat/vB, as Esplora quotes it, to `fee_unit`.
+    pub fn fee_rate_in_unit(&self, sat_per_vbyte: f64) -> f64 {
+        match self.fee_unit {
+            FeeUnit::SatPerVbyte => sat_per_vbyte,
+            FeeUnit::SatPerKilobyte => sat_per_vbyte * 1000.0,
+        }
+    }
+
+    /// Converts a fee rate quoted in `fee_unit` to sat/vB, raised to the relay minimum.
+    pub fn fee_rate_sat_per_vbyte(&self, fee_rate: f64) -> f64 {
+        let fee_rate = fee_rate.max(self.min_fee_rate);
+        match self.fee_unit {
+            FeeUnit::SatPerVbyte => fee_rate,
+            FeeUnit::SatPerKilobyte => fee_rate / 1000.0,
+        }
+    }
+
+    fn hrp(&self) -> Result<Hrp, Error> {
+        let hrp = self
+            .bech32_hrp
+            .ok_or(Error::UnsupportedAddressType(self.name))?;
+        Hrp::parse(hrp).map_err(|e| Error::UtxoAddressError(e.to_string()))
+    }
+
+    /// Output script paying to `public_key` with `address_type`.
+    pub fn script_pubkey(
+        &self,
+        address_type: AddressType,
+        public_key: &PublicKey,
+    ) -> Result<ScriptBuf, Error> {
+        if !self.supports(address_type) {
+            return Err(Error::UnsupportedAddressType(self.name));
+        }
+        let public_key = bitcoin::PublicKey::new(*public_key);
+        Ok(match address_type {
+            AddressType::Legacy => ScriptBuf::new_p2pkh(&public_key.pubkey_hash()),
+            AddressType::SegWit => ScriptBuf::new_p2wpkh(
+                &public_key
+                    .wpubkey_hash()
+                    .ok_or(Error::UnsupportedAddressType(self.name))?,
+            ),
+            AddressType::Taproot => {
+                let secp = Secp256k1::verification_only();
+                let (internal_key, _) = public_key.inner.x_only_public_key();
+                ScriptBuf::new_p2tr(&secp, internal_key, None)
+            }
+        })
+    }
+
+    /// Address of `public_key` with `address_type`.
+    pub fn encode_address(
+        &self,
+        address_type: AddressType,
+        public_key: &PublicKey,
+    ) -> Result<String, Error> {
+        let script = self.script_pubkey(address_type, public_key)?;
+        self.script_to_address(&script)
+    }
+
+    /// Address of a P2PKH, P2SH or SegWit output script.
+    pub fn script_to_address(&self, script: &ScriptBuf) -> Result<String, Error> {
+        let bytes = script.as_bytes();
+        if script.is_p2pkh() {
+            return Ok(base58_address(self.p2pkh_prefix, &bytes[3..23]));
+        }
+        if script.is_p2sh() {
+            return Ok(base58_address(self.p2sh_prefix, &bytes[2..22]));
+        }
+        if script.is_witness_program() {
+            let version = Fe32::try_from(script.witness_version().map_or(0, |v| v.to_num()))
+                .map_err(|e| Error::UtxoAddressError(e.to_string()))?;
+            return bech32::segwit::encode(&self.hrp()?, version, &bytes[2..])
+                .map_err(|e| Error::UtxoAddressError(e.to_string()));
+        }
+        Err(Error::UtxoAddressError(format!(
+            "no address form for script {}",
+            script
+        )))
+    }
+
+    /// Output script an address of this network pays to.
+    pub fn parse_address(&self, addr: &str) -> Result<ScriptBuf, Error> {
+        if let Some(hrp) = self.bech32_hrp {
+            if addr.to_lowercase().starts_with(&format!("{}1", hrp)) {
+                let (decoded_hrp, version, program) = bech32::segwit::decode(addr)
+                    .map_err(|e| Error::UtxoAddressError(e.to_string()))?;
+                if decoded_hrp.to_lowercase() != hrp {
+                    return Err(Error::UtxoAddressError(addr.to_owned()));
+                }
+                let version = WitnessVersion::try_from(version.to_u8())
+                    .map_err(|e| Error::UtxoAddressError(e.to_string()))?;
+                let program = WitnessProgram::new(version, program)
+                    .map_err(|e| Error::UtxoAddressError(e.to_string()))?;
+                return Ok(ScriptBuf::new_witness_program(&program));
+            }
+        }
+        let decoded = base58::decode_check(addr)?;
+        if decoded.len() != 21 {
+            return Err(Error::UtxoAddressError(addr.to_owned()));
+        }
+        match decoded[0] {
+            prefix if prefix == self.p2pkh_prefix => Ok(ScriptBuf::new_p2pkh(
+                &PubkeyHash::from_slice(&decoded[1..]).expect("20 bytes"),
+            )),
+            prefix if prefix == self.p2sh_prefix => Ok(ScriptBuf::new_p2sh(
+                &ScriptHash::from_slice(&decoded[1..]).expect("20 bytes"),
+            )),
+            _ => Err(Error::UtxoAddressError(addr.to_owned())),
+        }
+    }
+
+    /// WIF encoding of a private key with a compressed public key.
+    pub fn encode_wif(&self, secret_key: &SecretKey) -> String {
+        let mut payload = Vec::with_capacity(34);
+        payload.push(self.wif_prefix);
+        payload.extend_from_slice(&secret_key.secret_bytes());
+        payload.push(0x01);
+        base58::encode_check(&payload)
+    }
+}
+
+fn base58_address(prefix: u8, hash: &[u8]) -> String {
+    let mut payload = Vec::with_capacity(21);
+    payload.push(prefix);
+    payload.extend_from_slice(hash);
+    base58::encode_check(&payload)
+}
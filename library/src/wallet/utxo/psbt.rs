@@ -0,0 +1,328 @@
+//! PSBT construction, coin selection and signing for the keys of a `UtxoWallet`.
+//!
+//! Every input records the BIP32 origin of its key (`bip32_derivation` for legacy and
+//! SegWit, `tap_key_origins` for Taproot), so the signer derives the key of each input
+//! from the PSBT itself and external signers can process the same PSBT.
+//!
+//! Legacy inputs carry the whole previous transaction in `non_witness_utxo`, as their
+//! signatures don't commit to the amount they spend; SegWit and Taproot inputs carry
+//! their previous output in `witness_utxo`.
+
+use bitcoin::{
+    absolute::LockTime,
+    bip32::{DerivationPath, Fingerprint},
+    ecdsa,
+    hashes::Hash,
+    key::TapTweak,
+    psbt::Psbt,
+    script::{Builder, PushBytesBuf},
+    secp256k1::{Keypair, Message, PublicKey, Secp256k1, SecretKey},
+    sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType},
+    taproot,
+    transaction::Version,
+    Amount, OutPoint, Script, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Witness,
+};
+
+use crate::error::Error;
+
+use super::AddressType;
+
+/// Virtual size of the version, locktime, in/out counts and SegWit marker.
+pub const TX_OVERHEAD_VSIZE: u64 = 11;
+
+/// An unspent output together with the key that can spend it.
+#[derive(Debug, Clone)]
+pub struct SpendableUtxo {
+    pub outpoint: OutPoint,
+    pub txout: TxOut,
+    pub path: DerivationPath,
+    pub public_key: PublicKey,
+    /// Transaction creating the output, required to spend legacy outputs.
+    pub previous_tx: Option<Transaction>,
+}
+
+/// Inputs picked for a payment, with the resulting fee and change in satoshis.
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    pub inputs: Vec<SpendableUtxo>,
+    pub fee: u64,
+    pub change: u64,
+}
+
+/// Virtual size of an input spending `address_type`.
+pub fn input_vsize(address_type: AddressType) -> u64 {
+    match address_type {
+        // 41 bytes + 1 + 72 + 1 + 33 bytes of script_sig
+        AddressType::Legacy => 148,
+        // 41 bytes + (1 + 72 + 1 + 33) / 4 witness bytes
+        AddressType::SegWit => 68,
+        // 41 bytes + (1 + 1 + 64) / 4 witness bytes
+        AddressType::Taproot => 58,
+    }
+}
+
+/// Virtual size of an output paying to `script`.
+pub fn output_vsize(script: &Script) -> u64 {
+    8 + 1 + script.len() as u64
+}
+
+/// Fee in satoshis for `vsize` at `fee_rate` sat/vB.
+pub fn fee_for(vsize: u64, fee_rate: f64) -> u64 {
+    (vsize as f64 * fee_rate).ceil() as u64
+}
+
+/// Largest-first coin selection paying `amount` to `recipient`.
+///
+/// Nodes don't relay outputs below `dust_limit`: a smaller `amount` fails with
+/// `Error::AmountBelowDust`, and smaller change is added to the fee.
+pub fn select_coins(
+    mut utxos: Vec<SpendableUtxo>,
+    amount: u64,
+    recipient: &Script,
+    change: &Script,
+    address_type: AddressType,
+    fee_rate: f64,
+    dust_limit: u64,
+) -> Result<CoinSelection, Error> {
+    if amount < dust_limit {
+        return Err(Error::AmountBelowDust { amount, dust_limit });
+    }
+    utxos.sort_by_key(|u| std::cmp::Reverse(u.txout.value));
+    let available: u64 = utxos.iter().map(|u| u.txout.value.to_sat()).sum();
+
+    let mut inputs = vec![];
+    let mut total = 0u64;
+    for utxo in utxos {
+        total += utxo.txout.value.to_sat();
+        inputs.push(utxo);
+
+        let base_vsize = TX_OVERHEAD_VSIZE
+            + inputs.len() as u64 * input_vsize(address_type)
+            + output_vsize(recipient);
+        let fee_with_change = fee_for(base_vsize + output_vsize(change), fee_rate);
+        if total >= amount + fee_with_change {
+            let change_value = total - amount - fee_with_change;
+            if change_value >= dust_limit {
+                return Ok(CoinSelection {
+                    inputs,
+                    fee: fee_with_change,
+                    change: change_value,
+                });
+            }
+        }
+        let fee_without_change = fee_for(base_vsize, fee_rate);
+        if total >= amount + fee_without_change {
+            return Ok(CoinSelection {
+                inputs,
+                fee: total - amount,
+                change: 0,
+            });
+        }
+    }
+
+    let needed = amount
+        + fee_for(
+            TX_OVERHEAD_VSIZE + input_vsize(address_type) + output_vsize(recipient),
+            fee_rate,
+        );
+    Err(Error::InsufficientFunds { needed, available })
+}
+
+/// Spends all of `utxos` to `recipient`, returning the amount sent after the fee.
+pub fn select_all(
+    utxos: Vec<SpendableUtxo>,
+    recipient: &Script,
+    address_type: AddressType,
+    fee_rate: f64,
+    dust_limit: u64,
+) -> Result<(CoinSelection, u64), Error> {
+    let available: u64 = utxos.iter().map(|u| u.txout.value.to_sat()).sum();
+    let vsize = TX_OVERHEAD_VSIZE
+        + utxos.len() as u64 * input_vsize(address_type)
+        + output_vsize(recipient);
+    let fee = fee_for(vsize, fee_rate);
+    let amount = available.saturating_sub(fee);
+    if utxos.is_empty() || amount < dust_limit {
+        return Err(Error::InsufficientFunds {
+            needed: fee + dust_limit,
+            available,
+        });
+    }
+    Ok((
+        CoinSelection {
+            inputs: utxos,
+            fee,
+            change: 0,
+        },
+        amount,
+    ))
+}
+
+/// Builds an unsigned PSBT spending `inputs` to `outputs`.
+pub fn build_psbt(
+    inputs: &[SpendableUtxo],
+    outputs: Vec<(ScriptBuf, u64)>,
+    address_type: AddressType,
+    master_fingerprint: Fingerprint,
+) -> Result<Psbt, Error> {
+    let tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: inputs
+            .iter()
+            .map(|utxo| TxIn {
+                previous_output: utxo.outpoint,
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            })
+            .collect(),
+        output: outputs
+            .into_iter()
+            .map(|(script_pubkey, value)| TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey,
+            })
+            .collect(),
+    };
+
+    let mut psbt = Psbt::from_unsigned_tx(tx)?;
+    for (i, (input, utxo)) in psbt.inputs.iter_mut().zip(inputs).enumerate() {
+        if address_type == AddressType::Legacy {
+            let previous_tx = utxo
+                .previous_tx
+                .as_ref()
+                .filter(|tx| {
+                    tx.txid() == utxo.outpoint.txid
+                        && tx.output.get(utxo.outpoint.vout as usize) == Some(&utxo.txout)
+                })
+                .ok_or(Error::PsbtInputError(i))?;
+            input.non_witness_utxo = Some(previous_tx.clone());
+        } else {
+            input.witness_utxo = Some(utxo.txout.clone());
+        }
+        let origin = (master_fingerprint, utxo.path.clone());
+        match address_type {
+            AddressType::Legacy | AddressType::SegWit => {
+                input.bip32_derivation.insert(utxo.public_key, origin);
+            }
+            AddressType::Taproot => {
+                let (internal_key, _) = utxo.public_key.x_only_public_key();
+                input.tap_internal_key = Some(internal_key);
+                input.tap_key_origins.insert(internal_key, (vec![], origin));
+            }
+        }
+    }
+    Ok(psbt)
+}
+
+/// Signs every input of `psbt` with the key `key_for` derives for the input's key origin.
+pub fn sign_psbt<F>(psbt: &mut Psbt, key_for: F) -> Result<(), Error>
+where
+    F: Fn(&DerivationPath) -> Result<SecretKey, Error>,
+{
+    let secp = Secp256k1::new();
+    let prevouts = prevouts(psbt)?;
+    let unsigned_tx = psbt.unsigned_tx.clone();
+    let mut cache = SighashCache::new(&unsigned_tx);
+
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        if let Some((public_key, (_, path))) = input.bip32_derivation.first_key_value() {
+            let secret_key = key_for(path)?;
+            let script_pubkey = &prevouts[i].script_pubkey;
+            let digest = if script_pubkey.is_p2pkh() {
+                cache
+                    .legacy_signature_hash(i, script_pubkey, EcdsaSighashType::All.to_u32())?
+                    .to_byte_array()
+            } else {
+                cache
+                    .p2wpkh_signature_hash(
+                        i,
+                        script_pubkey,
+                        prevouts[i].value,
+                        EcdsaSighashType::All,
+                    )?
+                    .to_byte_array()
+            };
+            let sig = secp.sign_ecdsa(&Message::from_digest(digest), &secret_key);
+            let public_key = bitcoin::PublicKey::new(*public_key);
+            input.partial_sigs.insert(
+                public_key,
+                ecdsa::Signature {
+                    sig,
+                    hash_ty: EcdsaSighashType::All,
+                },
+            );
+        } else if let Some((_, (_, (_, path)))) = input.tap_key_origins.first_key_value() {
+            let secret_key = key_for(path)?;
+            let keypair = Keypair::from_secret_key(&secp, &secret_key);
+            let tweaked = keypair.tap_tweak(&secp, input.tap_merkle_root);
+            let sighash = cache.taproot_key_spend_signature_hash(
+                i,
+                &Prevouts::All(&prevouts),
+                TapSighashType::Default,
+            )?;
+            let sig = secp.sign_schnorr_no_aux_rand(
+                &Message::from_digest(sighash.to_byte_array()),
+                &tweaked.to_inner(),
+            );
+            input.tap_key_sig = Some(taproot::Signature {
+                sig,
+                hash_ty: TapSighashType::Default,
+            });
+        } else {
+            return Err(Error::PsbtInputError(i));
+        }
+    }
+    Ok(())
+}
+
+/// Moves the signatures of a signed PSBT into the input scripts and witnesses and
+/// extracts the transaction.
+///
+/// The fee rate isn't checked against Bitcoin's limits, Dogecoin fees exceed them.
+pub fn finalize_psbt(mut psbt: Psbt) -> Result<Transaction, Error> {
+    let prevouts = prevouts(&psbt)?;
+    for (i, input) in psbt.inputs.iter_mut().enumerate() {
+        let is_p2pkh = prevouts[i].script_pubkey.is_p2pkh();
+        if let Some(sig) = input.tap_key_sig {
+            input.final_script_witness = Some(Witness::from_slice(&[sig.to_vec()]));
+        } else if let Some((public_key, sig)) = input.partial_sigs.first_key_value() {
+            if is_p2pkh {
+                let sig =
+                    PushBytesBuf::try_from(sig.to_vec()).map_err(|_| Error::PsbtInputError(i))?;
+                input.final_script_sig = Some(
+                    Builder::new()
+                        .push_slice(sig)
+                        .push_key(public_key)
+                        .into_script(),
+                );
+            } else {
+                input.final_script_witness = Some(Witness::p2wpkh(sig, &public_key.inner));
+            }
+        } else {
+            return Err(Error::PsbtInputError(i));
+        }
+        input.partial_sigs.clear();
+        input.bip32_derivation.clear();
+        input.tap_key_sig = None;
+        input.tap_key_origins.clear();
+        input.tap_internal_key = None;
+    }
+    Ok(psbt.extract_tx_unchecked_fee_rate())
+}
+
+/// Outputs the inputs of `psbt` spend, from `witness_utxo` or `non_witness_utxo`.
+fn prevouts(psbt: &Psbt) -> Result<Vec<TxOut>, Error> {
+    (0..psbt.inputs.len())
+        .map(|i| {
+            let vout = psbt.unsigned_tx.input[i].previous_output.vout as usize;
+            let input = &psbt.inputs[i];
+            input
+                .witness_utxo
+                .clone()
+                .or_else(|| input.non_witness_utxo.as_ref()?.output.get(vout).cloned())
+                .ok_or(Error::PsbtInputError(i))
+        })
+        .collect()
+}
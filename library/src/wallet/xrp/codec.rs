@@ -0,0 +1,131 @@
+//! Ripple base58 account addresses and the canonical binary format of `Payment`s.
+
+use bitcoin::hashes::{sha256d, Hash};
+use sha2::{Digest, Sha512};
+
+use crate::error::Error;
+
+const ACCOUNT_ID_VERSION: u8 = 0x00;
+
+/// Prefix of the bytes a transaction signature commits to (`STX\0`).
+const SIGNING_PREFIX: [u8; 4] = [0x53, 0x54, 0x58, 0x00];
+/// Prefix of the bytes a transaction id is the hash of (`TXN\0`).
+const TX_ID_PREFIX: [u8; 4] = [0x54, 0x58, 0x4e, 0x00];
+
+/// r-address of a 20-byte account id.
+pub fn encode_address(account_id: &[u8; 20]) -> String {
+    let mut payload = vec![ACCOUNT_ID_VERSION];
+    payload.extend_from_slice(account_id);
+    let checksum = sha256d::Hash::hash(&payload);
+    payload.extend_from_slice(&checksum[..4]);
+    bs58::encode(payload)
+        .with_alphabet(bs58::Alphabet::RIPPLE)
+        .into_string()
+}
+
+/// Account id of an r-address.
+pub fn decode_address(address: &str) -> Result<[u8; 20], Error> {
+    let invalid = || Error::XrpAddressError(address.to_owned());
+    let data = bs58::decode(address)
+        .with_alphabet(bs58::Alphabet::RIPPLE)
+        .into_vec()
+        .map_err(|_| invalid())?;
+    if data.len() != 25 || data[0] != ACCOUNT_ID_VERSION {
+        return Err(invalid());
+    }
+    let checksum = sha256d::Hash::hash(&data[..21]);
+    if checksum[..4] != data[21..] {
+        return Err(invalid());
+    }
+    let mut account_id = [0u8; 20];
+    account_id.copy_from_slice(&data[1..21]);
+    Ok(account_id)
+}
+
+/// First half of SHA-512, the hash the XRP Ledger uses for signing and ids.
+pub fn sha512_half(data: &[u8]) -> [u8; 32] {
+    let digest = Sha512::digest(data);
+    let mut half = [0u8; 32];
+    half.copy_from_slice(&digest[..32]);
+    half
+}
+
+/// A `Payment` of XRP. Amounts are in drops.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub account: [u8; 20],
+    pub destination: [u8; 20],
+    pub amount: u64,
+    pub fee: u64,
+    pub sequence: u32,
+    pub destination_tag: Option<u32>,
+    pub last_ledger_sequence: Option<u32>,
+    pub signing_pub_key: Vec<u8>,
+}
+
+fn field_id(out: &mut Vec<u8>, type_code: u8, field_code: u8) {
+    if field_code < 16 {
+        out.push((type_code << 4) | field_code);
+    } else {
+        out.push(type_code << 4);
+        out.push(field_code);
+    }
+}
+
+fn uint32(out: &mut Vec<u8>, field_code: u8, value: u32) {
+    field_id(out, 2, field_code);
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn xrp_amount(out: &mut Vec<u8>, field_code: u8, drops: u64) {
+    field_id(out, 6, field_code);
+    // not an issued currency, positive
+    out.extend_from_slice(&(0x4000_0000_0000_0000 | drops).to_be_bytes());
+}
+
+/// Variable-length field; the lengths of payments' fields fit the one byte form.
+fn blob(out: &mut Vec<u8>, type_code: u8, field_code: u8, data: &[u8]) {
+    field_id(out, type_code, field_code);
+    out.push(data.len() as u8);
+    out.extend_from_slice(data);
+}
+
+impl Payment {
+    /// Canonical serialization, fields ordered by type and field code.
+    pub fn serialize(&self, txn_signature: Option<&[u8]>) -> Vec<u8> {
+        let mut out = vec![];
+        // TransactionType: Payment
+        field_id(&mut out, 1, 2);
+        out.extend_from_slice(&0u16.to_be_bytes());
+        uint32(&mut out, 4, self.sequence);
+        if let Some(tag) = self.destination_tag {
+            uint32(&mut out, 14, tag);
+        }
+        if let Some(ledger) = self.last_ledger_sequence {
+            uint32(&mut out, 27, ledger);
+        }
+        xrp_amount(&mut out, 1, self.amount);
+        xrp_amount(&mut out, 8, self.fee);
+        blob(&mut out, 7, 3, &self.signing_pub_key);
+        if let Some(signature) = txn_signature {
+            blob(&mut out, 7, 4, signature);
+        }
+        blob(&mut out, 8, 1, &self.account);
+        blob(&mut out, 8, 3, &self.destination);
+        out
+    }
+
+    /// Hash the transaction signature commits to.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut data = SIGNING_PREFIX.to_vec();
+        data.extend(self.serialize(None));
+        sha512_half(&data)
+    }
+}
+
+/// Transaction id of a signed transaction blob, as shown by explorers.
+pub fn tx_id(signed: &[u8]) -> String {
+    let mut data = TX_ID_PREFIX.to_vec();
+    data.extend_from_slice(signed);
+    hex::encode_upper(sha512_half(&data))
+}
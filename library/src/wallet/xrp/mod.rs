@@ -0,0 +1,410 @@
+pub mod codec;
+pub mod rpc;
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use bitcoin::{
+    bip32::DerivationPath,
+    hashes::{hash160, Hash},
+};
+use ethers::types::U256;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+use crate::{
+    error::Error,
+    types::{
+        hdseed::{FromSeed, HDSeed},
+        token_data::TokenData,
+        transfer_receipt::TransferReceipt,
+    },
+    utils::{amount::amount_as, key::keypair_by_index},
+};
+
+use self::{
+    codec::{decode_address, encode_address, tx_id, Payment},
+    rpc::{Reserves, XrplRpcClient},
+};
+use super::Wallet;
+
+/// Ledgers a signed payment stays valid for, so a dropped one fails for good.
+const LEDGER_OFFSET: u32 = 20;
+
+/// Destination of a payment: an r-address, optionally with a destination tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Destination {
+    pub account: [u8; 20],
+    pub tag: Option<u32>,
+}
+
+impl FromStr for Destination {
+    type Err = Error;
+
+    /// Parses `rAddress` or `rAddress?dt=TAG`, the form of XRPL payment URIs.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, tag) = match s.split_once("?dt=") {
+            Some((address, tag)) => (
+                address,
+                Some(
+                    tag.parse()
+                        .map_err(|_| Error::XrpAddressError(s.to_owned()))?,
+                ),
+            ),
+            None => (s, None),
+        };
+        Ok(Destination {
+            account: decode_address(address)?,
+            tag,
+        })
+    }
+}
+
+/// Split of a balance into the reserved part and what a sweep can send, in drops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sweepable {
+    pub balance: u64,
+    /// Base reserve plus the owner reserve of every object the account owns.
+    pub reserve: u64,
+    pub fee: u64,
+    pub amount: u64,
+}
+
+/// XRP Ledger wallet deriving secp256k1 keys under `m/44'/144'/0'/0/i`.
+/// Amounts are in drops. Destinations may carry a destination tag as `rAddress?dt=TAG`.
+/// Issued currencies are not supported.
+pub struct XrpWallet {
+    pub seed: HDSeed,
+}
+
+impl FromSeed for XrpWallet {
+    fn from_seed(seed: HDSeed) -> Self {
+        XrpWallet { seed }
+    }
+}
+
+impl XrpWallet {
+    pub fn get_hd_path(&self, index: u32) -> Result<DerivationPath, Error> {
+        Ok(DerivationPath::from_str(&format!(
+            "m/44'/144'/0'/0/{}",
+            index
+        ))?)
+    }
+
+    fn keys(&self, index: u32) -> Result<(SecretKey, PublicKey), Error> {
+        let (privk, pubk) = keypair_by_index(&self.seed.mnemonic, &self.get_hd_path(index)?)?;
+        Ok((privk.private_key, pubk.public_key))
+    }
+
+    fn account_id(&self, index: u32) -> Result<[u8; 20], Error> {
+        let (_, public_key) = self.keys(index)?;
+        Ok(hash160::Hash::hash(&public_key.serialize()).to_byte_array())
+    }
+
+    /// Signs a payment of `amount` drops from the account of `index`.
+    /// Returns the transaction blob ready for `submit`.
+    pub fn sign_payment(
+        &self,
+        index: u32,
+        destination: &Destination,
+        amount: u64,
+        fee: u64,
+        sequence: u32,
+        last_ledger_sequence: Option<u32>,
+    ) -> Result<Vec<u8>, Error> {
+        let (secret_key, public_key) = self.keys(index)?;
+        let payment = Payment {
+            account: self.account_id(index)?,
+            destination: destination.account,
+            amount,
+            fee,
+            sequence,
+            destination_tag: destination.tag,
+            last_ledger_sequence,
+            signing_pub_key: public_key.serialize().to_vec(),
+        };
+        let message = Message::from_digest(payment.signing_hash());
+        let signature = Secp256k1::new()
+            .sign_ecdsa(&message, &secret_key)
+            .serialize_der();
+        Ok(payment.serialize(Some(&signature)))
+    }
+
+    /// Balance of the account of `index` and how much of it a sweep can send after the
+    /// reserves and the fee. Fails with `InsufficientFunds` if nothing is left.
+    pub async fn sweepable(&self, index: u32, provider: &str) -> Result<Sweepable, Error> {
+        let client = XrplRpcClient::new(provider);
+        let address = self.address(index)?;
+        let account = client
+            .account_info(&address)
+            .await?
+            .ok_or(Error::XrpAccountNotFound(address))?;
+        let Reserves { base, increment } = client.reserves().await?;
+        let reserve = base + account.owner_count as u64 * increment;
+        let fee = client.fee().await?;
+        if account.balance <= reserve + fee {
+            return Err(Error::InsufficientFunds {
+                needed: reserve + fee + 1,
+                available: account.balance,
+            });
+        }
+        Ok(Sweepable {
+            balance: account.balance,
+            reserve,
+            fee,
+            amount: account.balance - reserve - fee,
+        })
+    }
+
+    async fn xrp_transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: u64,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        let destination: Destination = to.parse()?;
+        let client = XrplRpcClient::new(provider);
+        let address = self.address(index)?;
+        let account = client
+            .account_info(&address)
+            .await?
+            .ok_or(Error::XrpAccountNotFound(address))?;
+        // a payment creating an account must fund at least its base reserve
+        let to_address = encode_address(&destination.account);
+        if client.account_info(&to_address).await?.is_none() {
+            let reserves = client.reserves().await?;
+            if amount < reserves.base {
+                return Err(Error::XrpUnfundedDestination {
+                    address: to_address,
+                    reserve: reserves.base,
+                });
+            }
+        }
+        let fee = client.fee().await?;
+        let blob = self.sign_payment(
+            index,
+            &destination,
+            amount,
+            fee,
+            account.sequence,
+            Some(account.ledger_current_index + LEDGER_OFFSET),
+        )?;
+        client.submit(&blob).await?;
+        Ok(TransferReceipt::Broadcast { txid: tx_id(&blob) })
+    }
+}
+
+#[async_trait]
+impl Wallet for XrpWallet {
+    fn address(&self, index: u32) -> Result<String, Error> {
+        Ok(encode_address(&self.account_id(index)?))
+    }
+    fn public(&self, index: u32) -> Result<String, Error> {
+        let (_, public_key) = self.keys(index)?;
+        Ok(hex::encode_upper(public_key.serialize()))
+    }
+    fn private(&self, index: u32) -> Result<String, Error> {
+        let (secret_key, _) = self.keys(index)?;
+        Ok(hex::encode_upper(secret_key.secret_bytes()))
+    }
+    fn keypair(&self, index: u32) -> Result<(String, String), Error> {
+        Ok((self.private(index)?, self.public(index)?))
+    }
+    async fn balance(&self, index: u32, provider: &str) -> Result<U256, Error> {
+        let client = XrplRpcClient::new(provider);
+        let account = client.account_info(&self.address(index)?).await?;
+        Ok(U256::from(account.map_or(0, |account| account.balance)))
+    }
+    async fn balance_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _provider: &str,
+    ) -> Result<U256, Error> {
+        Err(Error::TokensNotSupported)
+    }
+    async fn transfer(
+        &self,
+        index: u32,
+        to: &str,
+        amount: U256,
+        provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        self.xrp_transfer(index, to, amount_as(amount)?, provider)
+            .await
+    }
+    async fn transfer_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _to: &str,
+        _amount: U256,
+        _provider: &str,
+    ) -> Result<TransferReceipt, Error> {
+        Err(Error::TokensNotSupported)
+    }
+    async fn sweep(
+        &self,
+        index: u32,
+        to: &str,
+        provider: &str,
+    ) -> Result<(TransferReceipt, U256), Error> {
+        let sweepable = self.sweepable(index, provider).await?;
+        let receipt = self
+            .xrp_transfer(index, to, sweepable.amount, provider)
+            .await?;
+        Ok((receipt, U256::from(sweepable.amount)))
+    }
+    async fn sweep_token(
+        &self,
+        _index: u32,
+        _token_address: &str,
+        _to: &str,
+        _provider: &str,
+    ) -> Result<(TransferReceipt, TokenData), Error> {
+        Err(Error::TokensNotSupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::{Route, TestServer};
+    use bip39::{Language, Mnemonic};
+    use secp256k1::ecdsa::Signature;
+
+    const PHRASE : &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn wallet() -> XrpWallet {
+        let mnemonic = Mnemonic::from_phrase(PHRASE, Language::English).unwrap();
+        XrpWallet::from_seed(HDSeed { mnemonic })
+    }
+
+    #[test]
+    fn test_xrp_address_by_index() {
+        let wallet = wallet();
+        assert_eq!(
+            wallet.address(0).unwrap(),
+            "rHsMGQEkVNJmpGWs8XUBoTBiAAbwxZN5v3"
+        );
+        let address = wallet.address(1).unwrap();
+        assert_eq!(encode_address(&decode_address(&address).unwrap()), address);
+        assert!(decode_address(&address.replace('r', "p")).is_err());
+    }
+
+    #[test]
+    fn test_destination_tag() {
+        let address = wallet().address(1).unwrap();
+        let destination: Destination = format!("{}?dt=12345", address).parse().unwrap();
+        assert_eq!(destination.tag, Some(12345));
+        assert_eq!(destination.account, decode_address(&address).unwrap());
+        assert_eq!(address.parse::<Destination>().unwrap().tag, None);
+        assert!(format!("{}?dt=x", address).parse::<Destination>().is_err());
+    }
+
+    #[test]
+    fn test_sign_payment() {
+        let wallet = wallet();
+        let destination: Destination = format!("{}?dt=7", wallet.address(1).unwrap())
+            .parse()
+            .unwrap();
+        let blob = wallet
+            .sign_payment(0, &destination, 1_000_000, 12, 5, Some(100))
+            .unwrap();
+        let (_, public_key) = wallet.keys(0).unwrap();
+        let payment = Payment {
+            account: wallet.account_id(0).unwrap(),
+            destination: destination.account,
+            amount: 1_000_000,
+            fee: 12,
+            sequence: 5,
+            destination_tag: Some(7),
+            last_ledger_sequence: Some(100),
+            signing_pub_key: public_key.serialize().to_vec(),
+        };
+        // the signature field sits between the signing key and the account
+        let unsigned = payment.serialize(None);
+        let split = unsigned.len() - 44;
+        assert_eq!(blob[..split], unsigned[..split]);
+        assert_eq!(blob[blob.len() - 44..], unsigned[split..]);
+        assert_eq!(blob[split], 0x74);
+        let der = &blob[split + 2..split + 2 + blob[split + 1] as usize];
+        Secp256k1::new()
+            .verify_ecdsa(
+                &Message::from_digest(payment.signing_hash()),
+                &Signature::from_der(der).unwrap(),
+                &public_key,
+            )
+            .unwrap();
+        assert_eq!(hex::encode_upper(&blob), "12000024000000052E00000007201B000000646140000000000F424068400000000000000C7321031D68BC1A142E6766B2BDFB006CCFE135EF2E0E2E94ABB5CF5C9AB6104776FBAE744730450221009EE5F2B9D6E222BAB276C30CCC95DE625F91EB9B74C3885FBF10FD671AC8805A02202F9E1836BD499FF654AEE6A4CA4A6B465B21A9A5EE19F59F8C10F0A554236A1F8114AFF3C2E33458B30714CA16FFEE19952DD35C17C883145720939C1336A7356A70ED861D5934345C6B6360");
+    }
+
+    #[tokio::test]
+    async fn test_sweep_keeps_reserve() {
+        let wallet = wallet();
+        let address = wallet.address(0).unwrap();
+        let to = format!("{}?dt=99", wallet.address(1).unwrap());
+        let server = TestServer::start(vec![
+            Route::rpc(
+                "account_info",
+                &format!(
+                    r#"{{"account_data":{{"Account":"{}","Balance":"25000000","Sequence":3,"OwnerCount":2}},"ledger_current_index":500,"status":"success"}}"#,
+                    address
+                ),
+            ),
+            Route::rpc(
+                "server_state",
+                r#"{"state":{"validated_ledger":{"reserve_base":1000000,"reserve_inc":200000}},"status":"success"}"#,
+            ),
+            Route::rpc(
+                "fee",
+                r#"{"drops":{"base_fee":"10","open_ledger_fee":"12"},"status":"success"}"#,
+            ),
+            Route::rpc(
+                "submit",
+                r#"{"engine_result":"tesSUCCESS","engine_result_message":"The transaction was applied.","status":"success"}"#,
+            ),
+        ])
+        .await;
+
+        let sweepable = wallet.sweepable(0, &server.url).await.unwrap();
+        assert_eq!(sweepable.reserve, 1_400_000);
+        assert_eq!(sweepable.amount, 25_000_000 - 1_400_000 - 12);
+
+        let (receipt, amount) = wallet.sweep(0, &to, &server.url).await.unwrap();
+        assert_eq!(amount, U256::from(sweepable.amount));
+
+        let submit = server.requests().pop().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&submit.body).unwrap();
+        let blob = hex::decode(body["params"][0]["tx_blob"].as_str().unwrap()).unwrap();
+        let expected = wallet
+            .sign_payment(0, &to.parse().unwrap(), sweepable.amount, 12, 3, Some(520))
+            .unwrap();
+        assert_eq!(blob, expected);
+        assert_eq!(receipt.txid(), tx_id(&expected));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_below_reserve() {
+        let wallet = wallet();
+        let server = TestServer::start(vec![
+            Route::rpc(
+                "account_info",
+                r#"{"account_data":{"Balance":"1000010","Sequence":1,"OwnerCount":0},"ledger_current_index":1}"#,
+            ),
+            Route::rpc(
+                "server_state",
+                r#"{"state":{"validated_ledger":{"reserve_base":1000000,"reserve_inc":200000}}}"#,
+            ),
+            Route::rpc("fee", r#"{"drops":{"base_fee":"10","open_ledger_fee":"10"}}"#),
+        ])
+        .await;
+        assert!(matches!(
+            wallet.sweepable(0, &server.url).await,
+            Err(Error::InsufficientFunds {
+                needed: 1_000_011,
+                available: 1_000_010
+            })
+        ));
+    }
+}
@@ -0,0 +1,149 @@
+//! Client for the rippled JSON-RPC API.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::error::Error;
+
+/// State of an account in the current open ledger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+    /// Balance in drops.
+    pub balance: u64,
+    pub sequence: u32,
+    /// Objects the account owns, each raising its reserve.
+    pub owner_count: u32,
+    pub ledger_current_index: u32,
+}
+
+/// Reserves of the last validated ledger, in drops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reserves {
+    pub base: u64,
+    pub increment: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Value,
+}
+
+fn field_u64(value: &Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+pub struct XrplRpcClient {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl XrplRpcClient {
+    /// Creates a client for the rippled node at `url`, e.g. `https://s1.ripple.com:51234`.
+    pub fn new(url: &str) -> Self {
+        XrplRpcClient {
+            url: url.to_owned(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let response: RpcResponse = self
+            .client
+            .post(&self.url)
+            .json(&json!({ "method": method, "params": [params] }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.result)
+    }
+
+    fn rpc_error(method: &str, result: &Value) -> Error {
+        Error::XrplRpcError(format!(
+            "{}: {}",
+            method,
+            result["error_message"]
+                .as_str()
+                .or(result["error"].as_str())
+                .unwrap_or("unexpected response")
+        ))
+    }
+
+    /// Account `address` in the current ledger, `None` if it hasn't been funded.
+    pub async fn account_info(&self, address: &str) -> Result<Option<AccountInfo>, Error> {
+        let result = self
+            .call(
+                "account_info",
+                json!({ "account": address, "ledger_index": "current" }),
+            )
+            .await?;
+        if result["error"] == "actNotFound" {
+            return Ok(None);
+        }
+        let data = &result["account_data"];
+        match (
+            field_u64(&data["Balance"]),
+            field_u64(&data["Sequence"]),
+            field_u64(&data["OwnerCount"]),
+            field_u64(&result["ledger_current_index"]),
+        ) {
+            (Some(balance), Some(sequence), Some(owner_count), Some(ledger)) => {
+                Ok(Some(AccountInfo {
+                    balance,
+                    sequence: sequence as u32,
+                    owner_count: owner_count as u32,
+                    ledger_current_index: ledger as u32,
+                }))
+            }
+            _ => Err(Self::rpc_error("account_info", &result)),
+        }
+    }
+
+    /// Transaction cost of the open ledger, in drops.
+    pub async fn fee(&self) -> Result<u64, Error> {
+        let result = self.call("fee", json!({})).await?;
+        let drops = &result["drops"];
+        match (
+            field_u64(&drops["open_ledger_fee"]),
+            field_u64(&drops["base_fee"]),
+        ) {
+            (Some(open), Some(base)) => Ok(open.max(base)),
+            _ => Err(Self::rpc_error("fee", &result)),
+        }
+    }
+
+    pub async fn reserves(&self) -> Result<Reserves, Error> {
+        let result = self.call("server_state", json!({})).await?;
+        let ledger = &result["state"]["validated_ledger"];
+        match (
+            field_u64(&ledger["reserve_base"]),
+            field_u64(&ledger["reserve_inc"]),
+        ) {
+            (Some(base), Some(increment)) => Ok(Reserves { base, increment }),
+            _ => Err(Self::rpc_error("server_state", &result)),
+        }
+    }
+
+    /// Submits a signed transaction blob. Fails unless the transaction applied
+    /// or was queued.
+    pub async fn submit(&self, tx_blob: &[u8]) -> Result<(), Error> {
+        let result = self
+            .call("submit", json!({ "tx_blob": hex::encode_upper(tx_blob) }))
+            .await?;
+        let engine_result = result["engine_result"].as_str().unwrap_or_default();
+        if engine_result.starts_with("tes") || engine_result == "terQUEUED" {
+            return Ok(());
+        }
+        if engine_result.is_empty() {
+            return Err(Self::rpc_error("submit", &result));
+        }
+        Err(Error::BroadcastError(format!(
+            "{}: {}",
+            engine_result,
+            result["engine_result_message"].as_str().unwrap_or_default()
+        )))
+    }
+}